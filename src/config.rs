@@ -1,43 +1,740 @@
 //! 用户配置管理模块
 //! 
 //! 负责保存和加载用户偏好设置，包括：
-//! - 保存的 WAQL 查询语句
+//! - 保存的 WAQL 查询语句（可导出/导入为查询集文件）
 //! - UI 主题选择
 //! - 字体大小设置
 //! - 自定义关键词
+//! - 离线演示模式
+//! - 命名的 WAAPI 连接及最近使用的连接
+//! - 各导出格式最近使用的目录
+//! - CSV 导出的分隔符和引号风格
+//! - 结果表格的单元格着色规则
+//! - 连接健康检查的间隔和重连退避
+//! - WAAPI 认证令牌（单独保存在令牌文件中）
+//!
+//! 另外提供崩溃恢复文件，定期保存未保存的编辑内容，
+//! 以及记录修改项目的 WAAPI 调用的审计日志
 
 use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::query_executor::{CsvDelimiter, CsvFormat, CsvQuoting, HealthCheck, PostRule};
+use crate::waapi::{ClientConfig, DEFAULT_HOST, DEFAULT_PORT};
 
 /// 配置文件名
 const CONFIG_FILE_NAME: &str = "user_data.json";
 
+/// 认证令牌文件名
+///
+/// 令牌不写入配置文件，避免分享配置时泄露
+const TOKEN_FILE_NAME: &str = "waapi_token";
+
+/// 崩溃恢复文件名
+const RECOVERY_FILE_NAME: &str = "recovery.json";
+
+/// 审计日志文件名
+const AUDIT_LOG_FILE_NAME: &str = "audit_log.jsonl";
+
 /// 默认字体大小
 const DEFAULT_FONT_SIZE: f32 = 18.0;
 
+/// 默认的补全弹窗最多显示的候选数
+const DEFAULT_MAX_SUGGESTIONS: usize = 12;
+
+/// 查询集文件扩展名
+pub const QUERY_SET_EXTENSION: &str = "waqlset";
+
+/// 最近使用的连接数量上限
+const MAX_RECENT_CONNECTIONS: usize = 5;
+
+/// 查询历史记录数量上限
+const MAX_QUERY_HISTORY: usize = 50;
+
+/// 启动时填入编辑器的查询的环境变量
+pub const ENV_DEFAULT_QUERY: &str = "WAQL_DEFAULT_QUERY";
+
+/// 启动时覆盖当前连接主机的环境变量
+pub const ENV_HOST: &str = "WAQL_HOST";
+
+/// 启动时覆盖当前连接端口的环境变量
+pub const ENV_PORT: &str = "WAQL_PORT";
+
+/// 忽略空白和大小写后，编辑距离不超过较长查询长度的该百分比时视为相似
+///
+/// 按长度计算阈值，短查询中改一个类型名或数字就是不同的查询，不会被当作相似
+const SIMILAR_QUERY_MAX_PERCENT: usize = 5;
+
+/// 默认连接名称
+const DEFAULT_CONNECTION_NAME: &str = "Local";
+
+/// 查询返回空列表时默认显示的消息
+const DEFAULT_EMPTY_RESULT_MESSAGE: &str = "Query returned 0 objects";
+
+/// 默认视为对象数量很多的类型，查询这些类型且没有筛选条件时先确认
+const DEFAULT_BROAD_TYPES: [&str; 5] = ["Sound", "AudioFileSource", "Event", "Action", "Voice"];
+
+/// 自动追加的 `select` 子句默认选择的字段
+const DEFAULT_AUTO_SELECT_FIELDS: [&str; 3] = ["name", "id", "type"];
+
+/// 确认大查询时建议追加的 `take` 数量
+const DEFAULT_LARGE_QUERY_LIMIT: usize = 1000;
+
+/// 默认请求超时秒数，包括读取完整响应的时间
+const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// 用户添加的查询模板
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct QueryTemplate {
+    /// 模板名称，显示在模板菜单中
+    pub name: String,
+    /// WAQL 查询语句
+    pub query: String,
+}
+
+/// 命名的 WAAPI 连接
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Connection {
+    /// 连接名称，用于在列表中区分不同连接
+    pub name: String,
+    /// WAAPI 主机
+    pub host: String,
+    /// WAAPI HTTP 端口
+    pub port: u16,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_CONNECTION_NAME.to_string(),
+            host: DEFAULT_HOST.to_string(),
+            port: DEFAULT_PORT,
+        }
+    }
+}
+
+/// 单元格着色规则的比较方式
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RuleOp {
+    /// 小于
+    #[default]
+    Lt,
+    /// 小于等于
+    Le,
+    /// 大于
+    Gt,
+    /// 大于等于
+    Ge,
+    /// 等于（两边都是数字时按数值比较）
+    Eq,
+    /// 不等于
+    Ne,
+    /// 包含文本（忽略大小写）
+    Contains,
+}
+
+impl RuleOp {
+    /// 所有比较方式
+    pub const ALL: [RuleOp; 7] = [
+        RuleOp::Lt,
+        RuleOp::Le,
+        RuleOp::Gt,
+        RuleOp::Ge,
+        RuleOp::Eq,
+        RuleOp::Ne,
+        RuleOp::Contains,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            RuleOp::Lt => "<",
+            RuleOp::Le => "<=",
+            RuleOp::Gt => ">",
+            RuleOp::Ge => ">=",
+            RuleOp::Eq => "=",
+            RuleOp::Ne => "!=",
+            RuleOp::Contains => "contains",
+        }
+    }
+
+    /// 是否只能比较数值
+    fn is_numeric(&self) -> bool {
+        matches!(self, RuleOp::Lt | RuleOp::Le | RuleOp::Gt | RuleOp::Ge)
+    }
+}
+
+/// 结果表格中空单元格和 null 值的显示方式，只影响显示，不影响导出
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NullDisplay {
+    /// 留空
+    #[default]
+    Blank,
+    /// 破折号
+    Dash,
+    /// `NULL`
+    Null,
+    /// `(empty)`
+    Empty,
+}
+
+impl NullDisplay {
+    /// 所有显示方式
+    pub const ALL: [NullDisplay; 4] = [
+        NullDisplay::Blank,
+        NullDisplay::Dash,
+        NullDisplay::Null,
+        NullDisplay::Empty,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            NullDisplay::Blank => "Blank",
+            NullDisplay::Dash => "—",
+            NullDisplay::Null => "NULL",
+            NullDisplay::Empty => "(empty)",
+        }
+    }
+
+    /// 单元格中显示的占位文本
+    pub fn placeholder(&self) -> &'static str {
+        match self {
+            NullDisplay::Blank => "",
+            other => other.label(),
+        }
+    }
+}
+
+/// 编辑器中按回车键的行为
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnterBehavior {
+    /// 回车执行查询，多行编辑器中 Shift+Enter 换行
+    Run,
+    /// 回车换行（单行编辑器中不做任何事），使用 Ctrl+Enter 执行
+    Newline,
+}
+
+impl EnterBehavior {
+    /// 所有行为
+    pub const ALL: [EnterBehavior; 2] = [EnterBehavior::Run, EnterBehavior::Newline];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            EnterBehavior::Run => "Run query",
+            EnterBehavior::Newline => "Insert newline",
+        }
+    }
+}
+
+/// 单元格着色规则，如"`@Volume` 小于 -12 时显示为红色"
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CellRule {
+    /// 列名，与表头完全一致
+    pub column: String,
+    /// 比较方式
+    pub op: RuleOp,
+    /// 比较的值
+    pub value: String,
+    /// 匹配时的文字颜色（sRGB）
+    pub color: [u8; 3],
+}
+
+impl Default for CellRule {
+    fn default() -> Self {
+        Self {
+            column: String::new(),
+            op: RuleOp::default(),
+            value: String::new(),
+            color: [230, 80, 80],
+        }
+    }
+}
+
+impl CellRule {
+    /// 检查规则是否有效
+    ///
+    /// # Errors
+    ///
+    /// 列名为空，或数值比较的值不是数字时返回原因
+    pub fn validate(&self) -> Result<(), String> {
+        if self.column.trim().is_empty() {
+            return Err("column is empty".to_string());
+        }
+        if self.op.is_numeric() && self.value.trim().parse::<f64>().is_err() {
+            return Err(format!("'{}' is not a number", self.value));
+        }
+        Ok(())
+    }
+
+    /// 判断单元格是否匹配规则，无效的规则不匹配任何单元格
+    ///
+    /// 数值比较使用单元格的原始 JSON 值，字符串形式的数字也参与比较
+    pub fn matches(&self, cell: &serde_json::Value) -> bool {
+        if self.validate().is_err() {
+            return false;
+        }
+        let text = match cell {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+        let number = cell.as_f64().or_else(|| text.trim().parse().ok());
+        let target = self.value.trim().parse::<f64>().ok();
+
+        match (self.op, number, target) {
+            (RuleOp::Lt, Some(n), Some(t)) => n < t,
+            (RuleOp::Le, Some(n), Some(t)) => n <= t,
+            (RuleOp::Gt, Some(n), Some(t)) => n > t,
+            (RuleOp::Ge, Some(n), Some(t)) => n >= t,
+            (RuleOp::Eq, Some(n), Some(t)) => n == t,
+            (RuleOp::Ne, Some(n), Some(t)) => n != t,
+            (RuleOp::Eq, _, _) => text == self.value,
+            (RuleOp::Ne, _, _) => text != self.value,
+            (RuleOp::Contains, _, _) => text.to_lowercase().contains(&self.value.to_lowercase()),
+            _ => false,
+        }
+    }
+}
+
+/// 保存的查询语句
+///
+/// 兼容旧版本配置中直接保存为字符串的查询语句
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(from = "SavedQueryRepr")]
+pub struct SavedQuery {
+    /// 查询名称，可以为空
+    pub name: String,
+    /// WAQL 查询语句
+    pub query: String,
+    /// 标签列表
+    pub tags: Vec<String>,
+    /// 所属的连接名称，为 `None` 时在所有连接中显示
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connection: Option<String>,
+}
+
+impl SavedQuery {
+    /// 创建没有名称和标签的查询
+    pub fn new(query: String) -> Self {
+        Self {
+            query,
+            ..Default::default()
+        }
+    }
+
+    /// 是否在指定连接中显示，没有所属连接的查询在所有连接中显示
+    pub fn is_visible_in(&self, connection: &str) -> bool {
+        self.connection.as_deref().is_none_or(|c| c == connection)
+    }
+
+    /// 用于显示的标题，没有名称时显示查询语句
+    pub fn title(&self) -> &str {
+        if self.name.is_empty() {
+            &self.query
+        } else {
+            &self.name
+        }
+    }
+}
+
+/// 保存的查询语句的序列化表示
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedQueryRepr {
+    /// 旧版本格式：仅查询语句
+    Plain(String),
+    /// 完整格式
+    Full {
+        #[serde(default)]
+        name: String,
+        query: String,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        connection: Option<String>,
+    },
+}
+
+impl From<SavedQueryRepr> for SavedQuery {
+    fn from(repr: SavedQueryRepr) -> Self {
+        match repr {
+            SavedQueryRepr::Plain(query) => SavedQuery::new(query),
+            SavedQueryRepr::Full {
+                name,
+                query,
+                tags,
+                connection,
+            } => SavedQuery {
+                name,
+                query,
+                tags,
+                connection,
+            },
+        }
+    }
+}
+
+/// 崩溃恢复数据
+///
+/// 与用户配置分开保存：运行期间定期写入，正常退出时删除，
+/// 因此启动时文件仍存在说明上次异常退出
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct RecoveryData {
+    /// 编辑器中的查询语句（包含选项部分）
+    pub code: String,
+    /// 最近一次查询的原始结果，过大时不保存
+    pub result: String,
+}
+
+impl RecoveryData {
+    /// 是否没有需要恢复的内容
+    pub fn is_empty(&self) -> bool {
+        self.code.trim().is_empty() && self.result.is_empty()
+    }
+
+    /// 读取恢复文件
+    ///
+    /// 文件不存在、无法解析或没有内容时返回 `None`
+    pub fn load() -> Option<Self> {
+        let content = fs::read_to_string(UserConfig::get_data_path(RECOVERY_FILE_NAME)).ok()?;
+        serde_json::from_str::<Self>(&content)
+            .ok()
+            .filter(|data| !data.is_empty())
+    }
+
+    /// 写入恢复文件
+    ///
+    /// # Errors
+    ///
+    /// 如果序列化或写入文件失败，返回错误
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string(self)?;
+        fs::write(UserConfig::get_data_path(RECOVERY_FILE_NAME), json)?;
+        Ok(())
+    }
+
+    /// 删除恢复文件
+    pub fn clear() {
+        let _ = fs::remove_file(UserConfig::get_data_path(RECOVERY_FILE_NAME));
+    }
+}
+
+/// 审计日志中的一条记录，对应一次修改项目的 WAAPI 调用
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    /// 调用时间（Unix 时间戳，秒）
+    pub timestamp: u64,
+    /// WAAPI 接口
+    pub uri: String,
+    /// 调用参数
+    pub args: serde_json::Value,
+}
+
+impl AuditEntry {
+    /// 创建当前时刻的记录
+    pub fn new(uri: &str, args: serde_json::Value) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Self {
+            timestamp,
+            uri: uri.to_string(),
+            args,
+        }
+    }
+
+    /// 审计日志文件路径
+    pub fn log_path() -> PathBuf {
+        UserConfig::get_data_path(AUDIT_LOG_FILE_NAME)
+    }
+
+    /// 以一行 JSON 的形式追加到审计日志
+    ///
+    /// # Errors
+    ///
+    /// 如果序列化或写入文件失败，返回错误
+    pub fn append(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let line = serde_json::to_string(self)?;
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::log_path())?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+/// 解析逗号分隔的标签文本
+pub fn parse_tags(text: &str) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in text.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        if !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// 去掉空白并转换为小写，用于比较相似的查询
+fn query_key(query: &str) -> Vec<char> {
+    query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 两个字符序列的编辑距离（插入、删除、替换各计 1）
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != cb);
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// 从环境变量读取的启动设置，用于脚本或展台方式启动
+///
+/// 未设置或只有空白的变量视为没有设置
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EnvOverrides {
+    /// 启动时填入编辑器的查询
+    pub query: Option<String>,
+    /// 覆盖当前连接的主机
+    pub host: Option<String>,
+    /// 覆盖当前连接的端口
+    pub port: Option<u16>,
+    /// 无效的值，忽略后沿用配置
+    pub warnings: Vec<String>,
+}
+
+impl EnvOverrides {
+    /// 读取进程的环境变量
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// 通过 `lookup` 按名称读取变量
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let read = |name| {
+            lookup(name)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let mut overrides = Self {
+            query: read(ENV_DEFAULT_QUERY),
+            host: read(ENV_HOST),
+            ..Self::default()
+        };
+        if let Some(port) = read(ENV_PORT) {
+            match port.parse::<u16>() {
+                Ok(port) if port > 0 => overrides.port = Some(port),
+                _ => overrides.warnings.push(format!(
+                    "环境变量 {} 的值 \"{}\" 不是有效的端口，使用配置中的端口",
+                    ENV_PORT, port
+                )),
+            }
+        }
+        overrides
+    }
+}
+
 /// 用户配置结构体
 /// 
-/// 存储应用程序的所有用户自定义设置
+/// 存储应用程序的所有用户自定义设置。缺失的字段使用默认值，
+/// 以兼容旧版本的配置文件
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct UserConfig {
     /// 保存的 WAQL 语句列表
-    pub saved_queries: Vec<String>,
+    pub saved_queries: Vec<SavedQuery>,
+    /// 用户添加的查询模板，显示在内置模板之后
+    pub query_templates: Vec<QueryTemplate>,
     /// 选择的主题名称
     pub theme_name: String,
     /// 字体大小
     pub fontsize: f32,
     /// 自定义关键词列表
     pub custom_keywords: Vec<String>,
+    /// 是否使用内置示例数据代替 WAAPI 连接
+    pub offline_mode: bool,
+    /// 是否使用多行编辑器
+    pub multiline_editor: bool,
+    /// 回车键的行为，未设置时见 [`UserConfig::enter_behavior`]
+    pub enter_behavior: Option<EnterBehavior>,
+    /// 是否自动为缺少 `$` 的查询补全前缀
+    pub auto_fix_dollar: bool,
+    /// 执行前是否规范化查询中的空白和弯引号
+    pub normalize_input: bool,
+    /// 执行前是否去除查询首尾的空白
+    pub trim_input: bool,
+    /// 执行查询后是否将焦点保持在编辑器中
+    pub keep_editor_focus: bool,
+    /// 执行没有 `where`/`take` 并且查询 `broad_types` 中类型的查询前是否先确认
+    pub large_query_guard: bool,
+    /// 对象数量很多的类型名称
+    pub broad_types: Vec<String>,
+    /// 确认大查询时建议追加的 `take` 数量
+    pub large_query_limit: usize,
+    /// 是否定时重新执行最近一次查询并替换结果
+    pub auto_refresh: bool,
+    /// 自动刷新的间隔（秒）
+    pub auto_refresh_secs: u32,
+    /// 是否在后台线程中执行查询，关闭时在界面线程中等待响应
+    pub background_queries: bool,
+    /// 请求超时秒数，包括读取完整响应的时间
+    pub request_timeout_secs: u64,
+    /// 查询没有 `|` 选项部分时默认返回的字段
+    pub default_return: Vec<String>,
+    /// 查询没有 `select` 子句和 `|` 选项部分时是否在查询末尾追加 `select` 子句
+    pub auto_select: bool,
+    /// 自动追加的 `select` 子句选择的字段
+    pub auto_select_fields: Vec<String>,
+    /// 光标处的单词至少有多少个字符时才显示补全弹窗
+    pub min_completion_chars: usize,
+    /// 补全弹窗最多显示的候选数
+    pub max_suggestions: usize,
+    /// 导出 CSV 时是否转义可能被当作公式执行的单元格
+    pub safe_csv_export: bool,
+    /// CSV 导出的分隔符
+    pub csv_delimiter: CsvDelimiter,
+    /// CSV 导出的引号风格
+    pub csv_quoting: CsvQuoting,
+    /// CSV 导出时是否写入 UTF-8 BOM
+    pub csv_bom: bool,
+    /// 结果表格最多显示的行数，0 表示不限制
+    pub max_rows: usize,
+    /// 原始 JSON 视图中展开的最大嵌套层数，0 表示不限制
+    pub json_max_depth: usize,
+    /// 查询返回空列表时显示的消息，为空时使用默认消息
+    pub empty_result_message: String,
+    /// 结果表格中空单元格和 null 值的显示方式
+    pub null_display: NullDisplay,
+    /// 是否在表头显示推断出的列类型
+    pub show_type_badges: bool,
+    /// 是否使用色盲友好的状态颜色（蓝色/橙色）并在颜色旁显示图标
+    pub colorblind_palette: bool,
+    /// 结果表格横向滚动时保持可见的最左侧列数
+    pub frozen_columns: usize,
+    /// 结果表格中按列名保存的列宽，由"Fit columns"生成
+    pub column_widths: BTreeMap<String, f32>,
+    /// 发送修改项目的 WAAPI 调用前是否先预览并确认
+    pub preview_mutations: bool,
+    /// 是否定期检测连接并在断开后自动重连
+    pub health_check: bool,
+    /// 连接正常时的检测间隔（秒）
+    pub health_check_secs: u32,
+    /// 每次重连失败后等待时间的倍数
+    pub reconnect_backoff: f32,
+    /// 重连等待时间上限（秒）
+    pub max_reconnect_secs: u32,
+    /// 没有请求多久（秒）后断开连接，0 表示不断开
+    pub idle_timeout_secs: u64,
+    /// 执行过的查询，最近的在前
+    pub query_history: Vec<String>,
+    /// 是否启用单元格着色规则
+    pub cell_rules_enabled: bool,
+    /// 单元格着色规则，按顺序匹配，第一条匹配的规则生效
+    pub cell_rules: Vec<CellRule>,
+    /// 查询结果显示前按顺序应用的后处理规则
+    pub post_rules: Vec<PostRule>,
+    /// 各导出格式（文件扩展名）最近使用的目录
+    pub export_dirs: BTreeMap<String, PathBuf>,
+    /// 一键运行并导出时写入的 CSV 文件路径
+    pub report_path: Option<PathBuf>,
+    /// 命名的 WAAPI 连接列表
+    pub connections: Vec<Connection>,
+    /// 当前使用的连接名称
+    pub active_connection: String,
+    /// 最近使用的连接名称，最近的在前
+    pub recent_connections: Vec<String>,
+    /// WAAPI 认证令牌，为空时不发送认证请求头
+    #[serde(skip)]
+    pub auth_token: String,
+    /// 每个 WAAPI 请求附加的自定义请求头（名称和值）
+    pub headers: Vec<(String, String)>,
+    /// 最近一次加载或保存时配置文件的修改时间
+    #[serde(skip)]
+    saved_mtime: Cell<Option<SystemTime>>,
+    /// 启动时由环境变量指定的主机和端口，覆盖当前连接，切换连接后不再使用
+    #[serde(skip)]
+    address_override: (Option<String>, Option<u16>),
 }
 
 impl Default for UserConfig {
     fn default() -> Self {
         Self {
             saved_queries: Vec::new(),
+            query_templates: Vec::new(),
             theme_name: "GRUVBOX".to_string(),
             fontsize: DEFAULT_FONT_SIZE,
             custom_keywords: Vec::new(),
+            offline_mode: false,
+            multiline_editor: false,
+            enter_behavior: None,
+            auto_fix_dollar: false,
+            normalize_input: true,
+            trim_input: true,
+            keep_editor_focus: true,
+            large_query_guard: true,
+            broad_types: DEFAULT_BROAD_TYPES.iter().map(|t| t.to_string()).collect(),
+            large_query_limit: DEFAULT_LARGE_QUERY_LIMIT,
+            auto_refresh: false,
+            auto_refresh_secs: 30,
+            background_queries: true,
+            request_timeout_secs: DEFAULT_REQUEST_TIMEOUT_SECS,
+            default_return: Vec::new(),
+            auto_select: false,
+            auto_select_fields: DEFAULT_AUTO_SELECT_FIELDS.iter().map(|f| f.to_string()).collect(),
+            min_completion_chars: 1,
+            max_suggestions: DEFAULT_MAX_SUGGESTIONS,
+            safe_csv_export: true,
+            csv_delimiter: CsvDelimiter::default(),
+            csv_quoting: CsvQuoting::default(),
+            // Windows 上的 Excel 需要 BOM 才能正确识别 UTF-8
+            csv_bom: cfg!(windows),
+            max_rows: 0,
+            json_max_depth: 0,
+            empty_result_message: DEFAULT_EMPTY_RESULT_MESSAGE.to_string(),
+            null_display: NullDisplay::default(),
+            show_type_badges: true,
+            colorblind_palette: false,
+            frozen_columns: 0,
+            column_widths: BTreeMap::new(),
+            preview_mutations: true,
+            health_check: true,
+            health_check_secs: 10,
+            reconnect_backoff: 2.0,
+            max_reconnect_secs: 60,
+            idle_timeout_secs: 0,
+            query_history: Vec::new(),
+            cell_rules_enabled: false,
+            cell_rules: Vec::new(),
+            post_rules: Vec::new(),
+            export_dirs: BTreeMap::new(),
+            report_path: None,
+            connections: vec![Connection::default()],
+            active_connection: DEFAULT_CONNECTION_NAME.to_string(),
+            recent_connections: Vec::new(),
+            auth_token: String::new(),
+            headers: Vec::new(),
+            saved_mtime: Cell::new(None),
+            address_override: (None, None),
         }
     }
 }
@@ -48,12 +745,15 @@ impl UserConfig {
     /// 如果文件不存在或读取失败，返回默认配置
     pub fn load() -> Self {
         let config_path = Self::get_config_path();
-        if let Ok(content) = fs::read_to_string(&config_path) {
-            if let Ok(config) = serde_json::from_str::<UserConfig>(&content) {
-                return config;
-            }
-        }
-        Self::default()
+        let mut config = fs::read_to_string(&config_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<UserConfig>(&content).ok())
+            .unwrap_or_default();
+        config.auth_token = fs::read_to_string(Self::get_data_path(TOKEN_FILE_NAME))
+            .map(|token| token.trim().to_string())
+            .unwrap_or_default();
+        config.saved_mtime.set(Self::config_mtime());
+        config
     }
 
     /// 保存配置到文件
@@ -65,33 +765,281 @@ impl UserConfig {
         let config_path = Self::get_config_path();
         let json = serde_json::to_string_pretty(self)?;
         fs::write(&config_path, json)?;
+
+        let token_path = Self::get_data_path(TOKEN_FILE_NAME);
+        if self.auth_token.is_empty() {
+            if token_path.exists() {
+                fs::remove_file(&token_path)?;
+            }
+        } else {
+            fs::write(&token_path, &self.auth_token)?;
+        }
+        self.saved_mtime.set(Self::config_mtime());
         Ok(())
     }
 
+    /// 配置文件是否在最近一次加载或保存之后被外部修改
+    pub fn changed_on_disk(&self) -> bool {
+        match (Self::config_mtime(), self.saved_mtime.get()) {
+            (Some(current), Some(saved)) => current > saved,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// 配置文件的修改时间，文件不存在时返回 `None`
+    fn config_mtime() -> Option<SystemTime> {
+        fs::metadata(Self::get_config_path()).and_then(|meta| meta.modified()).ok()
+    }
+
     /// 获取配置文件路径
+    pub fn get_config_path() -> PathBuf {
+        Self::get_data_path(CONFIG_FILE_NAME)
+    }
+
+    /// 获取数据文件路径
     /// 
-    /// 配置文件存储在可执行文件同目录下
-    fn get_config_path() -> PathBuf {
+    /// 数据文件存储在可执行文件同目录下
+    fn get_data_path(file_name: &str) -> PathBuf {
         let mut path = std::env::current_exe().unwrap_or_else(|_| PathBuf::from("."));
         path.pop(); // 移除可执行文件名
-        path.push(CONFIG_FILE_NAME);
+        path.push(file_name);
         path
     }
 
+    /// 生成 WAAPI 客户端连接配置
+    ///
+    /// 使用当前连接的主机和端口，找不到当前连接时使用默认地址；
+    /// 设置了 [`UserConfig::set_address_override`] 时优先使用其中的主机和端口。
+    /// 名称为空的自定义请求头被忽略
+    pub fn client_config(&self) -> ClientConfig {
+        let token = self.auth_token.trim();
+        let connection = self.active_connection().cloned().unwrap_or_default();
+        let (host, port) = &self.address_override;
+        ClientConfig {
+            host: host.clone().unwrap_or(connection.host),
+            port: port.unwrap_or(connection.port),
+            auth_token: (!token.is_empty()).then(|| token.to_string()),
+            headers: self
+                .headers
+                .iter()
+                .filter(|(name, _)| !name.trim().is_empty())
+                .map(|(name, value)| (name.trim().to_string(), value.clone()))
+                .collect(),
+            timeout: self.request_timeout(),
+        }
+    }
+
+    /// 覆盖当前连接的主机和端口，不保存到配置文件，切换连接后失效
+    pub fn set_address_override(&mut self, host: Option<String>, port: Option<u16>) {
+        self.address_override = (host, port);
+    }
+
+    /// 当前覆盖连接的主机和端口
+    pub fn address_override(&self) -> (Option<String>, Option<u16>) {
+        self.address_override.clone()
+    }
+
+    /// 请求超时时间，至少 1 秒
+    pub fn request_timeout(&self) -> Duration {
+        Duration::from_secs(self.request_timeout_secs.max(1))
+    }
+
+    /// 空闲断开的超时时间，未启用时返回 `None`
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        (self.idle_timeout_secs > 0).then(|| Duration::from_secs(self.idle_timeout_secs))
+    }
+
+    /// CSV 导出格式
+    pub fn csv_format(&self) -> CsvFormat {
+        CsvFormat {
+            delimiter: self.csv_delimiter,
+            quoting: self.csv_quoting,
+            bom: self.csv_bom,
+        }
+    }
+
+    /// 回车键的行为
+    ///
+    /// 未设置时沿用编辑器模式的习惯：单行编辑器回车执行，多行编辑器回车换行
+    pub fn enter_behavior(&self) -> EnterBehavior {
+        self.enter_behavior.unwrap_or(if self.multiline_editor {
+            EnterBehavior::Newline
+        } else {
+            EnterBehavior::Run
+        })
+    }
+
+    /// 当前生效的单元格着色规则，未启用时为空
+    pub fn active_cell_rules(&self) -> &[CellRule] {
+        if self.cell_rules_enabled {
+            &self.cell_rules
+        } else {
+            &[]
+        }
+    }
+
+    /// 结果表格最多显示的行数，未设置限制时返回 `None`
+    pub fn max_rows(&self) -> Option<usize> {
+        (self.max_rows > 0).then_some(self.max_rows)
+    }
+
+    /// 原始 JSON 视图中展开的最大嵌套层数，未设置限制时返回 `None`
+    pub fn json_max_depth(&self) -> Option<usize> {
+        (self.json_max_depth > 0).then_some(self.json_max_depth)
+    }
+
+    /// 按配置创建连接健康检查
+    pub fn health_check(&self) -> HealthCheck {
+        let (interval, backoff, max_delay) = self.health_policy();
+        HealthCheck::new(interval, backoff, max_delay)
+    }
+
+    /// 健康检查的间隔、退避倍数和重连等待上限（秒）
+    pub fn health_policy(&self) -> (f64, f64, f64) {
+        (
+            f64::from(self.health_check_secs.max(1)),
+            f64::from(self.reconnect_backoff.max(1.0)),
+            f64::from(self.max_reconnect_secs.max(1)),
+        )
+    }
+
+    /// 查询返回空列表时显示的消息
+    pub fn empty_result_message(&self) -> &str {
+        let message = self.empty_result_message.trim();
+        if message.is_empty() {
+            DEFAULT_EMPTY_RESULT_MESSAGE
+        } else {
+            message
+        }
+    }
+
+    /// 获取当前使用的连接
+    pub fn active_connection(&self) -> Option<&Connection> {
+        self.connections
+            .iter()
+            .find(|c| c.name == self.active_connection)
+    }
+
+    /// 添加连接
+    ///
+    /// 名称为空或与已有连接重名时不会添加
+    pub fn add_connection(&mut self, connection: Connection) -> bool {
+        let name = connection.name.trim();
+        if name.is_empty() || self.connections.iter().any(|c| c.name == name) {
+            return false;
+        }
+        self.connections.push(Connection {
+            name: name.to_string(),
+            ..connection
+        });
+        true
+    }
+
+    /// 删除连接，同时从最近使用列表中移除
+    pub fn remove_connection(&mut self, index: usize) -> Option<Connection> {
+        if index >= self.connections.len() {
+            return None;
+        }
+        let removed = self.connections.remove(index);
+        self.recent_connections.retain(|name| *name != removed.name);
+        Some(removed)
+    }
+
+    /// 切换到指定连接，并记录到最近使用列表
+    ///
+    /// 连接不存在时返回 `false`
+    pub fn activate_connection(&mut self, name: &str) -> bool {
+        if !self.connections.iter().any(|c| c.name == name) {
+            return false;
+        }
+        self.address_override = (None, None);
+        self.active_connection = name.to_string();
+        self.recent_connections.retain(|recent| recent != name);
+        self.recent_connections.insert(0, name.to_string());
+        self.recent_connections.truncate(MAX_RECENT_CONNECTIONS);
+        true
+    }
+
+    /// 记录执行过的查询，重复的查询移到最前面
+    pub fn push_history(&mut self, query: &str) {
+        let query = query.trim();
+        if query.is_empty() {
+            return;
+        }
+        self.query_history.retain(|q| q != query);
+        self.query_history.insert(0, query.to_string());
+        self.query_history.truncate(MAX_QUERY_HISTORY);
+    }
+
+    /// 最近使用且仍然存在的连接名称
+    pub fn recent_connections(&self) -> Vec<&str> {
+        self.recent_connections
+            .iter()
+            .filter(|name| self.connections.iter().any(|c| &c.name == *name))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// 获取指定导出格式最近使用的目录
+    ///
+    /// 目录已不存在时返回 `None`
+    pub fn export_dir(&self, format: &str) -> Option<&Path> {
+        self.export_dirs
+            .get(format)
+            .map(PathBuf::as_path)
+            .filter(|dir| dir.is_dir())
+    }
+
+    /// 记录导出文件所在目录，作为该格式下次导出的起始目录
+    pub fn remember_export_dir(&mut self, format: &str, file: &Path) {
+        if let Some(dir) = file.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            self.export_dirs.insert(format.to_string(), dir.to_path_buf());
+        }
+    }
+
     /// 添加保存的查询语句
     /// 
     /// 如果查询已存在，不会重复添加
     pub fn add_saved_query(&mut self, query: String) -> bool {
-        if !self.saved_queries.contains(&query) {
-            self.saved_queries.push(query);
+        if !self.saved_queries.iter().any(|q| q.query == query) {
+            self.saved_queries.push(SavedQuery::new(query));
             true
         } else {
             false
         }
     }
 
+    /// 在当前连接显示的保存的查询中查找与 `query` 相似但不完全相同的查询，返回其索引
+    ///
+    /// 忽略空白和大小写后相同，或编辑距离不超过较长查询的 [`SIMILAR_QUERY_MAX_PERCENT`]% 时
+    /// 视为相似
+    pub fn similar_saved_query(&self, query: &str) -> Option<usize> {
+        let key = query_key(query);
+        self.saved_queries.iter().position(|saved| {
+            if saved.query == query || !saved.is_visible_in(&self.active_connection) {
+                return false;
+            }
+            let saved_key = query_key(&saved.query);
+            let max_distance = key.len().max(saved_key.len()) * SIMILAR_QUERY_MAX_PERCENT / 100;
+            key.len().abs_diff(saved_key.len()) <= max_distance
+                && edit_distance(&saved_key, &key) <= max_distance
+        })
+    }
+
+    /// 用新的查询语句替换保存的查询，保留名称、标签和所属连接
+    pub fn update_saved_query(&mut self, index: usize, query: String) -> bool {
+        match self.saved_queries.get_mut(index) {
+            Some(saved) => {
+                saved.query = query;
+                true
+            }
+            None => false,
+        }
+    }
+
     /// 删除保存的查询语句
-    pub fn remove_saved_query(&mut self, index: usize) -> Option<String> {
+    pub fn remove_saved_query(&mut self, index: usize) -> Option<SavedQuery> {
         if index < self.saved_queries.len() {
             Some(self.saved_queries.remove(index))
         } else {
@@ -99,6 +1047,71 @@ impl UserConfig {
         }
     }
 
+    /// 导出保存的查询语句为查询集文件
+    ///
+    /// 查询集文件是包含名称、查询语句和标签的 JSON 数组
+    ///
+    /// # Errors
+    ///
+    /// 如果序列化或写入文件失败，返回错误
+    pub fn export_query_set(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(&self.saved_queries)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    /// 从查询集文件导入查询语句
+    ///
+    /// # Errors
+    ///
+    /// 如果读取文件失败或文件内容不是合法的查询集，返回错误
+    pub fn import_query_set(&mut self, path: &Path) -> Result<usize, Box<dyn std::error::Error>> {
+        let content = fs::read_to_string(path)?;
+        self.merge_query_set(&content)
+    }
+
+    /// 合并查询集内容
+    ///
+    /// 查询语句相同的条目不会重复添加：已有条目没有名称时使用导入的名称，
+    /// 标签取并集
+    ///
+    /// # Returns
+    ///
+    /// 返回新增的查询数量
+    ///
+    /// # Errors
+    ///
+    /// 如果内容不是查询数组或包含空查询，返回错误
+    pub fn merge_query_set(&mut self, content: &str) -> Result<usize, Box<dyn std::error::Error>> {
+        let imported: Vec<SavedQuery> = serde_json::from_str(content)?;
+
+        if let Some(index) = imported.iter().position(|q| q.query.trim().is_empty()) {
+            return Err(format!("查询集第 {} 条查询为空", index + 1).into());
+        }
+
+        let mut added = 0;
+        for mut query in imported {
+            query.query = query.query.trim().to_string();
+            match self.saved_queries.iter_mut().find(|q| q.query == query.query) {
+                Some(existing) => {
+                    if existing.name.is_empty() {
+                        existing.name = query.name;
+                    }
+                    for tag in query.tags {
+                        if !existing.tags.contains(&tag) {
+                            existing.tags.push(tag);
+                        }
+                    }
+                }
+                None => {
+                    self.saved_queries.push(query);
+                    added += 1;
+                }
+            }
+        }
+        Ok(added)
+    }
+
     /// 添加自定义关键词
     /// 
     /// 如果关键词已存在，不会重复添加
@@ -132,6 +1145,96 @@ mod tests {
         assert_eq!(config.fontsize, DEFAULT_FONT_SIZE);
         assert!(config.saved_queries.is_empty());
         assert!(config.custom_keywords.is_empty());
+        assert!(!config.offline_mode);
+        assert!(config.safe_csv_export);
+    }
+
+    #[test]
+    fn test_auth_token_not_serialized() {
+        let config = UserConfig {
+            auth_token: "secret".to_string(),
+            ..UserConfig::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(!json.contains("secret"));
+        assert_eq!(config.client_config().auth_token.as_deref(), Some("secret"));
+        assert!(UserConfig::default().client_config().auth_token.is_none());
+    }
+
+    #[test]
+    fn test_load_config_missing_fields() {
+        let config: UserConfig =
+            serde_json::from_str(r#"{"saved_queries": ["$ from type Sound"]}"#).unwrap();
+        assert_eq!(
+            config.saved_queries,
+            vec![SavedQuery::new("$ from type Sound".to_string())]
+        );
+        assert_eq!(config.fontsize, DEFAULT_FONT_SIZE);
+        assert!(!config.offline_mode);
+    }
+
+    #[test]
+    fn test_null_display() {
+        assert_eq!(UserConfig::default().null_display, NullDisplay::Blank);
+        assert_eq!(NullDisplay::Blank.placeholder(), "");
+        assert_eq!(NullDisplay::Dash.placeholder(), "—");
+        let config: UserConfig = serde_json::from_str(r#"{"null_display": "Null"}"#).unwrap();
+        assert_eq!(config.null_display.placeholder(), "NULL");
+    }
+
+    #[test]
+    fn test_enter_behavior() {
+        let mut config = UserConfig::default();
+        assert_eq!(config.enter_behavior(), EnterBehavior::Run);
+        config.multiline_editor = true;
+        assert_eq!(config.enter_behavior(), EnterBehavior::Newline);
+
+        let config: UserConfig =
+            serde_json::from_str(r#"{"multiline_editor": true, "enter_behavior": "Run"}"#)
+                .unwrap();
+        assert_eq!(config.enter_behavior(), EnterBehavior::Run);
+    }
+
+    #[test]
+    fn test_saved_query_connection_scope() {
+        let config: UserConfig = serde_json::from_str(
+            r#"{"saved_queries": [
+                "$ from type Sound",
+                {"query": "$ from type Event"},
+                {"query": "$ from type Bus", "connection": "Build machine"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(config.saved_queries[0].connection, None);
+        assert_eq!(config.saved_queries[1].connection, None);
+
+        let visible = |connection: &str| {
+            config
+                .saved_queries
+                .iter()
+                .filter(|q| q.is_visible_in(connection))
+                .count()
+        };
+        assert_eq!(visible("Build machine"), 3);
+        assert_eq!(visible(DEFAULT_CONNECTION_NAME), 2);
+
+        // 全局查询序列化时不写出连接字段
+        let json = serde_json::to_string(&config.saved_queries[0]).unwrap();
+        assert!(!json.contains("connection"));
+    }
+
+    #[test]
+    fn test_audit_entry_line() {
+        let entry = AuditEntry::new(
+            "ak.wwise.core.object.setProperty",
+            serde_json::json!({"object": "{1}", "property": "Volume", "value": -6}),
+        );
+        assert!(entry.timestamp > 0);
+
+        let line = serde_json::to_string(&entry).unwrap();
+        assert!(!line.contains('\n'));
+        let parsed: AuditEntry = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed, entry);
     }
 
     #[test]
@@ -143,6 +1246,279 @@ mod tests {
         assert_eq!(config.saved_queries.len(), 1);
     }
 
+    #[test]
+    fn test_similar_saved_query() {
+        let mut config = UserConfig::default();
+        config.add_saved_query("$ from type Sound where name : \"foot\"".to_string());
+        config.add_saved_query("$ from type Event".to_string());
+        config.add_saved_query("$ from type Bus".to_string());
+        config.saved_queries[2].connection = Some("Build machine".to_string());
+
+        assert_eq!(
+            config.similar_saved_query("$ FROM type Sound  where name:\"Foot\""),
+            Some(0)
+        );
+        // 长查询允许少量差异，短查询只忽略空白和大小写
+        assert_eq!(config.similar_saved_query("$ from type Sound where name : \"foots\""), Some(0));
+        assert_eq!(config.similar_saved_query("$ from type Sound where name : \"step\""), None);
+        assert_eq!(config.similar_saved_query("$ from type Events"), None);
+        assert_eq!(config.similar_saved_query("$  FROM TYPE event"), Some(1));
+        assert_eq!(config.similar_saved_query("$ from type Event"), None);
+        // 只在当前连接显示的查询中查找
+        assert_eq!(config.similar_saved_query("$ from type bus"), None);
+        config.active_connection = "Build machine".to_string();
+        assert_eq!(config.similar_saved_query("$ from type bus"), Some(2));
+
+        assert!(config.update_saved_query(1, "$ from type Events".to_string()));
+        assert_eq!(config.saved_queries[1].query, "$ from type Events");
+        assert!(!config.update_saved_query(5, String::new()));
+    }
+
+    #[test]
+    fn test_merge_query_set() {
+        let mut config = UserConfig::default();
+        config.add_saved_query("$ from type Sound".to_string());
+
+        let content = r#"[
+            {"name": "All sounds", "query": "$ from type Sound", "tags": ["audio"]},
+            {"name": "All events", "query": "$ from type Event"},
+            "$ from type Bus"
+        ]"#;
+        assert_eq!(config.merge_query_set(content).unwrap(), 2);
+        assert_eq!(config.saved_queries.len(), 3);
+        assert_eq!(config.saved_queries[0].name, "All sounds");
+        assert_eq!(config.saved_queries[0].tags, vec!["audio".to_string()]);
+
+        // 再次导入不会产生重复
+        assert_eq!(config.merge_query_set(content).unwrap(), 0);
+        assert_eq!(config.saved_queries[0].tags, vec!["audio".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_query_set_rejects_invalid() {
+        let mut config = UserConfig::default();
+        assert!(config.merge_query_set(r#"{"query": "$ from type Sound"}"#).is_err());
+        assert!(config.merge_query_set(r#"[{"name": "missing query"}]"#).is_err());
+        assert!(config.merge_query_set(r#"[{"query": "  "}]"#).is_err());
+        assert!(config.saved_queries.is_empty());
+    }
+
+    #[test]
+    fn test_client_config_uses_active_connection() {
+        let mut config = UserConfig::default();
+        assert!(config.add_connection(Connection {
+            name: "Build machine".to_string(),
+            host: "10.0.0.5".to_string(),
+            port: 8095,
+        }));
+        assert_eq!(config.client_config().host, DEFAULT_HOST);
+
+        assert!(config.activate_connection("Build machine"));
+        let client_config = config.client_config();
+        assert_eq!(client_config.host, "10.0.0.5");
+        assert_eq!(client_config.port, 8095);
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                pairs.iter().find(|(key, _)| *key == name).map(|(_, value)| value.to_string())
+            }
+        };
+        let overrides = EnvOverrides::from_lookup(vars(&[
+            (ENV_DEFAULT_QUERY, "$ from type Event"),
+            (ENV_HOST, " 10.0.0.5 "),
+            (ENV_PORT, "8095"),
+        ]));
+        assert_eq!(overrides.query.as_deref(), Some("$ from type Event"));
+        assert_eq!(overrides.host.as_deref(), Some("10.0.0.5"));
+        assert_eq!(overrides.port, Some(8095));
+        assert!(overrides.warnings.is_empty());
+
+        let overrides = EnvOverrides::from_lookup(vars(&[(ENV_HOST, ""), (ENV_PORT, "80a")]));
+        assert_eq!(overrides.host, None);
+        assert_eq!(overrides.port, None);
+        assert_eq!(overrides.warnings.len(), 1);
+        assert_eq!(EnvOverrides::from_lookup(vars(&[])), EnvOverrides::default());
+    }
+
+    #[test]
+    fn test_address_override() {
+        let mut config = UserConfig::default();
+        config.set_address_override(Some("10.0.0.5".to_string()), None);
+        assert_eq!(config.client_config().host, "10.0.0.5");
+        assert_eq!(config.client_config().port, DEFAULT_PORT);
+        assert!(config.activate_connection(DEFAULT_CONNECTION_NAME));
+        assert_eq!(config.client_config().host, DEFAULT_HOST);
+    }
+
+    #[test]
+    fn test_client_config_headers() {
+        let config = UserConfig {
+            headers: vec![
+                (" X-Forwarded-User ".to_string(), "sound".to_string()),
+                ("  ".to_string(), "ignored".to_string()),
+            ],
+            ..UserConfig::default()
+        };
+        assert_eq!(
+            config.client_config().headers,
+            vec![("X-Forwarded-User".to_string(), "sound".to_string())]
+        );
+        assert!(UserConfig::default().client_config().headers.is_empty());
+    }
+
+    #[test]
+    fn test_request_timeout() {
+        let mut config = UserConfig::default();
+        assert_eq!(config.client_config().timeout, Duration::from_secs(10));
+        config.request_timeout_secs = 0;
+        assert_eq!(config.request_timeout(), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let mut config = UserConfig::default();
+        assert_eq!(config.idle_timeout(), None);
+        config.idle_timeout_secs = 300;
+        assert_eq!(config.idle_timeout(), Some(Duration::from_secs(300)));
+    }
+
+    #[test]
+    fn test_cell_rule_matches() {
+        let rule = CellRule {
+            column: "@Volume".to_string(),
+            op: RuleOp::Lt,
+            value: "-12".to_string(),
+            ..CellRule::default()
+        };
+        assert!(rule.matches(&serde_json::json!(-15.5)));
+        assert!(rule.matches(&serde_json::json!("-20")));
+        assert!(!rule.matches(&serde_json::json!(-3)));
+        assert!(!rule.matches(&serde_json::json!("loud")));
+
+        let rule = CellRule {
+            column: "name".to_string(),
+            op: RuleOp::Contains,
+            value: "FOOT".to_string(),
+            ..CellRule::default()
+        };
+        assert!(rule.matches(&serde_json::json!("Footstep")));
+
+        let rule = CellRule {
+            column: "type".to_string(),
+            op: RuleOp::Eq,
+            value: "Sound".to_string(),
+            ..CellRule::default()
+        };
+        assert!(rule.matches(&serde_json::json!("Sound")));
+        assert!(!rule.matches(&serde_json::json!("Event")));
+    }
+
+    #[test]
+    fn test_invalid_cell_rule() {
+        let rule = CellRule {
+            column: "@Volume".to_string(),
+            op: RuleOp::Gt,
+            value: "loud".to_string(),
+            ..CellRule::default()
+        };
+        assert!(rule.validate().is_err());
+        assert!(!rule.matches(&serde_json::json!(3)));
+
+        let rule = CellRule {
+            op: RuleOp::Contains,
+            value: "a".to_string(),
+            ..CellRule::default()
+        };
+        assert!(rule.validate().is_err());
+
+        let config = UserConfig {
+            cell_rules: vec![CellRule::default()],
+            ..UserConfig::default()
+        };
+        assert!(config.active_cell_rules().is_empty());
+    }
+
+    #[test]
+    fn test_push_history() {
+        let mut config = UserConfig::default();
+        config.push_history("$ from type Sound");
+        config.push_history("  $ from type Event ");
+        config.push_history("");
+        config.push_history("$ from type Sound");
+        assert_eq!(config.query_history, vec!["$ from type Sound", "$ from type Event"]);
+
+        for i in 0..MAX_QUERY_HISTORY + 1 {
+            config.push_history(&format!("$ from type Bus where name : \"{}\"", i));
+        }
+        assert_eq!(config.query_history.len(), MAX_QUERY_HISTORY);
+    }
+
+    #[test]
+    fn test_recent_connections() {
+        let mut config = UserConfig::default();
+        for i in 0..MAX_RECENT_CONNECTIONS + 1 {
+            config.add_connection(Connection {
+                name: format!("conn{}", i),
+                ..Connection::default()
+            });
+        }
+        assert!(!config.add_connection(Connection::default()));
+        assert!(!config.activate_connection("missing"));
+
+        config.activate_connection("conn0");
+        config.activate_connection("conn1");
+        config.activate_connection("conn0");
+        assert_eq!(config.recent_connections(), vec!["conn0", "conn1"]);
+
+        for i in 0..MAX_RECENT_CONNECTIONS + 1 {
+            config.activate_connection(&format!("conn{}", i));
+        }
+        assert_eq!(config.recent_connections().len(), MAX_RECENT_CONNECTIONS);
+        assert_eq!(config.recent_connections()[0], "conn5");
+
+        let index = config.connections.iter().position(|c| c.name == "conn5").unwrap();
+        config.remove_connection(index);
+        assert_eq!(config.recent_connections()[0], "conn4");
+    }
+
+    #[test]
+    fn test_export_dirs() {
+        let mut config = UserConfig::default();
+        assert!(config.export_dir("csv").is_none());
+
+        let dir = std::env::temp_dir();
+        config.remember_export_dir("csv", &dir.join("results.csv"));
+        assert_eq!(config.export_dir("csv"), Some(dir.as_path()));
+        assert!(config.export_dir("json").is_none());
+
+        config.remember_export_dir("json", Path::new("/nonexistent/waql/results.json"));
+        assert!(config.export_dir("json").is_none());
+
+        config.remember_export_dir("json", Path::new("results.json"));
+        assert!(config.export_dirs["json"].ends_with("waql"));
+    }
+
+    #[test]
+    fn test_recovery_data_is_empty() {
+        assert!(RecoveryData::default().is_empty());
+        assert!(RecoveryData { code: "  \n".to_string(), ..Default::default() }.is_empty());
+        assert!(!RecoveryData { code: "$ from type Sound".to_string(), ..Default::default() }
+            .is_empty());
+
+        let data: RecoveryData = serde_json::from_str(r#"{"code": "$ from type Bus"}"#).unwrap();
+        assert_eq!(data.code, "$ from type Bus");
+        assert!(data.result.is_empty());
+    }
+
+    #[test]
+    fn test_parse_tags() {
+        assert_eq!(parse_tags(" a, b ,,a "), vec!["a".to_string(), "b".to_string()]);
+        assert!(parse_tags("").is_empty());
+    }
+
     #[test]
     fn test_add_custom_keyword() {
         let mut config = UserConfig::default();
@@ -4,8 +4,21 @@
 
 pub mod config;
 pub mod query_executor;
+mod report;
+pub mod transport;
+pub mod waapi;
 mod waql;
 
 pub use waql::waql_syntax;
+pub use waql::{EXAMPLE_QUERIES, QUERY_TEMPLATES};
 pub use waql::WAAPI_ACCESSORS;
-pub use waql::WAAPI_PROPERTIES;
\ No newline at end of file
+pub use waql::WAAPI_PROPERTIES;
+pub use waql::{append_condition, WhereCondition, WhereOp};
+pub use waql::{append_take, broad_query_type};
+pub use waql::{lint, LintKind, LintWarning};
+pub use waql::{quote_string, quote_value};
+pub use waql::{append_select, in_select_clause, select_fields};
+pub use waql::{split_statements, statement_at};
+pub use waql::{tokenize, tokenize_json, tokenize_with, TokenKind};
+pub use waql::{token_at_caret, CaretToken, WordCompleter};
+pub use waql::{fuzzy_match, palette_entries, search_palette, FuzzyMatch, PaletteEntry, PaletteKind};
\ No newline at end of file
@@ -16,16 +16,48 @@
 
 mod config;
 mod query_executor;
+mod report;
+mod transport;
 mod ui;
+mod waapi;
 
-use config::UserConfig;
+use config::{
+    AuditEntry, EnterBehavior, EnvOverrides, QueryTemplate, RecoveryData, UserConfig,
+    QUERY_SET_EXTENSION,
+};
 use eframe::{self, CreationContext, egui};
-use egui_code_editor::{ColorTheme, Completer, Syntax};
-use query_executor::{QueryExecutor, TableData};
+use egui_code_editor::{ColorTheme, Syntax};
+use query_executor::{
+    sanitize_file_name, ConnectionState, ExportFormat, HealthCheck, PendingQuery, PendingTable,
+    PingOutcome, QueryExecutor, QueryReport, QueryResult, ReportFormat, ResultKind, RpcRequest,
+    TableData,
+};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant, SystemTime};
 use ui::{
-    render_code_editor, render_config_panel, render_control_buttons, render_results, THEMES,
+    apply_theme, caret_index, editor_id, focus_editor, handle_undo_redo, insert_at_cursor,
+    multiline_paste, navigate_history, next_theme, render_about_window, render_auto_refresh_status,
+    render_batch_export_window, render_code_editor, render_config_panel, render_control_buttons,
+    render_diff_window, render_export_columns_window, render_help_window, render_inspector_window,
+    render_large_query_window, render_lint_warnings, render_option_selectors, render_palette,
+    render_reload_config_window, render_results, render_rpc_window, render_similar_query_window,
+    render_snapshot_bar, render_status_bar, render_templates_menu, render_toast,
+    render_validate_window, render_watch_bar, render_where_builder, selected_text, AutoRefreshState,
+    BatchExportState, CodeCompleter, ControlButtonState, Diagnostics, EditHistory,
+    ExportColumnsState, HistoryCursor, LargeQueryChoice, PaletteState, RpcState, SimilarQueryChoice,
+    StatusInfo, StatusPalette, TableViewState, TemplateActions, Toast, ValidateEntry, ValidateState,
+    WatchState, WhereBuilderState, THEMES,
+};
+use waapi::is_mutating_uri;
+use waql_tool::{
+    append_condition, append_select, append_take, broad_query_type, lint, palette_entries,
+    quote_string, select_fields, split_statements, statement_at, tokenize, waql_syntax,
+    LintWarning, PaletteEntry, TokenKind, WAAPI_ACCESSORS, WAAPI_PROPERTIES,
 };
-use waql_tool::{waql_syntax, WAAPI_ACCESSORS, WAAPI_PROPERTIES};
 
 // UI 常量
 const APP_TITLE: &str = "Waql Tool";
@@ -33,6 +65,48 @@ const DEFAULT_WINDOW_WIDTH: f32 = 900.0;
 const DEFAULT_WINDOW_HEIGHT: f32 = 600.0;
 const MIN_WINDOW_SIZE: f32 = 280.0;
 
+/// 拖放时作为查询语句加载的文件扩展名
+const QUERY_FILE_EXTENSIONS: [&str; 2] = ["waql", "txt"];
+
+/// 崩溃恢复文件的写入间隔（秒）
+const RECOVERY_INTERVAL: f64 = 5.0;
+
+/// 写入崩溃恢复文件的查询结果大小上限（字节）
+const RECOVERY_RESULT_LIMIT: usize = 1024 * 1024;
+
+/// 查看子对象时返回的字段，包含 `id` 以便继续展开
+const CHILDREN_RETURN: &str = "id name type childrenCount";
+
+/// 结果不是对象列表时显示的消息
+const NON_TABULAR_MESSAGE: &str = "Result is not a WAQL object list (showing raw JSON)";
+
+/// 启用离线演示模式的命令行参数
+const OFFLINE_FLAG: &str = "--offline";
+
+/// 打开插入面板的快捷键
+const OPEN_PALETTE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::P);
+
+/// 回车执行时多行编辑器中换行的快捷键
+const NEWLINE_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::SHIFT, egui::Key::Enter);
+
+/// 运行选中内容或光标所在语句的快捷键，单行编辑器中运行查询
+const RUN_SELECTION_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Enter);
+
+/// 打开快捷键帮助的快捷键
+const HELP_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::F1);
+
+/// 重新执行上一次执行的查询的快捷键
+const RERUN_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::F5);
+
+/// 切换到下一个主题的快捷键
+const CYCLE_THEME_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::T);
+
 /// 设置自定义字体
 fn setup_custom_fonts(ctx: &egui::Context, fontsize: f32) {
     // 从默认字体开始
@@ -89,6 +163,38 @@ fn update_font_size(ctx: &egui::Context, fontsize: f32) {
     ctx.set_style(style);
 }
 
+/// 在系统文件管理器中显示文件
+///
+/// Windows 和 macOS 上会选中该文件，其他平台打开文件所在目录
+fn reveal_in_file_manager(path: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("explorer");
+        command.arg(format!("/select,{}", path.display()));
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg("-R").arg(path);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(path.parent().unwrap_or(Path::new(".")));
+        command
+    };
+    command.spawn().map(|_| ())
+}
+
+/// 用系统默认程序打开文件或目录
+fn open_with_default_app(path: &Path) -> std::io::Result<()> {
+    let mut command = if cfg!(windows) {
+        Command::new("explorer")
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    command.arg(path).spawn().map(|_| ())
+}
+
 fn main() -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
@@ -109,6 +215,59 @@ fn main() -> Result<(), eframe::Error> {
     )
 }
 
+/// 子对象结果视图
+///
+/// 在单独的窗口中显示某个对象的子对象，不影响主结果
+struct ChildrenView {
+    /// 父对象 GUID
+    guid: String,
+    /// 查询结果或错误信息
+    result: String,
+    /// 解析后的表格数据
+    table_data: Option<TableData>,
+    /// 是否有错误
+    has_error: bool,
+    /// 表格视图状态
+    table_view: TableViewState,
+}
+
+/// 在单独窗口中显示的结果副本
+struct PoppedResult {
+    /// 窗口标识
+    id: u64,
+    /// 生成该结果的查询语句
+    query: String,
+    /// 查询结果
+    result: String,
+    /// 表格数据
+    table_data: Option<TableData>,
+    /// 表格视图状态
+    table_view: TableViewState,
+}
+
+/// 用于比较的结果快照，只在本次运行中保留
+struct Snapshot {
+    /// 快照的表格数据
+    table: TableData,
+    /// 生成该结果的查询语句
+    query: String,
+    /// 保存快照的时间
+    taken_at: Instant,
+}
+
+impl Snapshot {
+    /// 在按钮栏中显示的说明
+    fn description(&self) -> String {
+        let elapsed = self.taken_at.elapsed().as_secs();
+        let age = if elapsed < 60 {
+            format!("{} s ago", elapsed)
+        } else {
+            format!("{} min ago", elapsed / 60)
+        };
+        format!("Snapshot of `{}` · {}", self.query, age)
+    }
+}
+
 /// WAQL 工具应用程序主结构
 struct WaqlApp {
     /// 查询执行器
@@ -126,7 +285,7 @@ struct WaqlApp {
     /// WAQL 语法定义
     syntax: Syntax,
     /// 代码自动补全器
-    completer: Completer,
+    completer: CodeCompleter,
     /// 用户配置
     config: UserConfig,
     /// 自定义关键词输入框
@@ -135,34 +294,165 @@ struct WaqlApp {
     show_config_panel: bool,
     /// 状态消息
     status_message: String,
+    /// 插入面板状态
+    palette: PaletteState,
+    /// 插入面板条目
+    palette_entries: Vec<PaletteEntry>,
+    /// 当前显示的提示消息
+    toast: Option<Toast>,
+    /// 结果表格视图状态
+    table_view: TableViewState,
+    /// 状态栏信息
+    status: StatusInfo,
+    /// 正在后台解析的表格数据和解析完成后表格列的顺序（select 子句中的字段）
+    pending_table: Option<(Vec<String>, PendingTable)>,
+    /// 正在后台执行的查询和查询语句
+    pending_query: Option<(String, PendingQuery)>,
+    /// 等待用户确认是否恢复的上次会话内容
+    recovery_prompt: Option<RecoveryData>,
+    /// 最近一次写入恢复文件的内容
+    recovery_saved: RecoveryData,
+    /// 下次写入恢复文件的时间（egui 输入时间，秒）
+    next_recovery_save: f64,
+    /// 子对象结果视图
+    children_view: Option<ChildrenView>,
+    /// 在单独窗口中显示的结果
+    popouts: Vec<PoppedResult>,
+    /// 下一个结果窗口的标识
+    next_popout_id: u64,
+    /// 编辑器中浏览查询历史的位置
+    history_cursor: HistoryCursor,
+    /// 编辑器的撤销和重做记录
+    edit_history: EditHistory,
+    /// 是否显示关于面板
+    show_about: bool,
+    /// 是否显示快捷键帮助
+    show_help: bool,
+    /// 上一次执行的查询，与编辑器内容分开保存
+    last_query: Option<String>,
+    /// `where` 条件构建窗口的状态
+    where_builder: WhereBuilderState,
+    /// 模板菜单中输入的新模板名称
+    template_name: String,
+    /// 等待确认的大查询和其中对象数量很多的类型
+    large_query: Option<(String, String)>,
+    /// 是否显示重新加载配置的确认窗口
+    show_reload_config: bool,
+    /// 是否显示检查器窗口
+    show_inspector: bool,
+    /// 连接健康检查的计时
+    health: HealthCheck,
+    /// 正在后台进行的连接检测
+    pending_ping: Option<Receiver<PingOutcome>>,
+    /// 批量导出窗口的状态
+    batch_export: BatchExportState,
+    /// 按自定义列导出窗口的状态
+    export_columns: ExportColumnsState,
+    /// 等待确认的相似查询和与之相似的保存的查询索引
+    similar_query: Option<(String, usize)>,
+    /// 保存的查询检查窗口状态
+    validate: ValidateState,
+    /// 是否显示 WAAPI 调用窗口
+    show_rpc: bool,
+    /// WAAPI 调用窗口的状态
+    rpc: RpcState,
+    /// 缓存的 Wwise 版本，切换连接时清除
+    wwise_version: Option<Result<String, String>>,
+    /// 项目支持的对象类型名称，连接后获取，用于查询检查
+    project_types: Option<Vec<String>>,
+    /// 本次运行中关闭的查询检查提示
+    dismissed_lints: Vec<LintWarning>,
+    /// 用于比较的结果快照
+    snapshot: Option<Snapshot>,
+    /// 是否显示与快照的比较结果
+    show_diff: bool,
+    /// 定时重新执行查询的状态
+    watch: WatchState,
+    /// 按键合并时等待后台解析完成的原表格
+    watch_base: Option<TableData>,
+    /// 定时自动刷新结果的状态
+    auto_refresh: AutoRefreshState,
+}
+
+/// 创建补全器，包含 WAAPI 属性、访问器和配置中的自定义关键词
+fn build_completer(syntax: &Syntax, config: &UserConfig) -> CodeCompleter {
+    let mut completer = CodeCompleter::new(syntax);
+    for word in WAAPI_PROPERTIES.iter().chain(WAAPI_ACCESSORS.iter()) {
+        completer.push_word(word);
+    }
+    for keyword in &config.custom_keywords {
+        completer.push_word(keyword);
+    }
+    completer.set_limits(config.min_completion_chars, config.max_suggestions);
+    completer
+}
+
+/// 根据配置中的主题名称选择主题
+fn config_theme(config: &UserConfig) -> ColorTheme {
+    THEMES
+        .iter()
+        .find(|t| t.name() == config.theme_name)
+        .copied()
+        .unwrap_or(ColorTheme::GRUVBOX)
+}
+
+/// 将配置中的查询设置应用到执行器，不包括连接和离线模式
+fn apply_executor_settings(executor: &mut QueryExecutor, config: &UserConfig) {
+    executor.set_auto_fix_dollar(config.auto_fix_dollar);
+    executor.set_normalize_input(config.normalize_input);
+    executor.set_trim_input(config.trim_input);
+    executor.set_default_return(config.default_return.clone());
+    executor.set_max_rows(config.max_rows());
+    executor.set_post_rules(config.post_rules.clone());
+    executor.set_idle_timeout(config.idle_timeout());
+}
+
+/// 剪贴板不可用时写入的临时文件名
+const CLIPBOARD_FALLBACK_FILE: &str = "waql-tool-clipboard.txt";
+
+/// 当前环境是否有可用的剪贴板
+///
+/// egui 不报告剪贴板写入失败，这里按显示环境判断：Linux 上没有 X11 或 Wayland 显示时不可用
+fn clipboard_available() -> bool {
+    if cfg!(target_os = "linux") {
+        std::env::var_os("DISPLAY").is_some() || std::env::var_os("WAYLAND_DISPLAY").is_some()
+    } else {
+        true
+    }
 }
 
 impl Default for WaqlApp {
     fn default() -> Self {
         let syntax = waql_syntax();
-        let mut completer = Completer::new_with_syntax(&syntax).with_user_words();
-        for word in WAAPI_PROPERTIES.iter().chain(WAAPI_ACCESSORS.iter()) {
-            completer.push_word(word);
-        }
-
-        // 加载用户配置
-        let config = UserConfig::load();
 
-        // 加载自定义关键词到补全器
-        for keyword in &config.custom_keywords {
-            completer.push_word(keyword);
+        // 加载用户配置，环境变量中的主机和端口覆盖当前连接
+        let env = EnvOverrides::from_env();
+        let code = env.query.unwrap_or_default();
+        let mut config = UserConfig::load();
+        config.set_address_override(env.host.clone(), env.port);
+        for warning in &env.warnings {
+            eprintln!("{}", warning);
         }
+        let completer = build_completer(&syntax, &config);
+        let theme = config_theme(&config);
+
+        // 命令行参数或配置启用离线演示模式
+        let offline = config.offline_mode || std::env::args().any(|arg| arg == OFFLINE_FLAG);
+        let mut executor = QueryExecutor::with_config(config.client_config());
+        executor.set_offline(offline);
+        apply_executor_settings(&mut executor, &config);
 
-        // 根据配置中的主题名称选择主题
-        let theme = THEMES
-            .iter()
-            .find(|t| t.name() == config.theme_name)
-            .copied()
-            .unwrap_or(ColorTheme::GRUVBOX);
+        let executor_state = executor.connection_state();
+        // 离线模式下项目信息来自内置数据，可以直接获取
+        let project = if offline { executor.project_info().ok() } else { None };
+        let health = config.health_check();
+        let table_view =
+            TableViewState::with_layout(config.frozen_columns, config.column_widths.clone());
 
         Self {
-            executor: QueryExecutor::new(),
-            code: String::new(),
+            executor,
+            edit_history: EditHistory::new(&code),
+            code,
             result: String::new(),
             table_data: None,
             has_error: false,
@@ -172,7 +462,49 @@ impl Default for WaqlApp {
             config,
             custom_keyword: String::new(),
             show_config_panel: false,
-            status_message: String::new(),
+            status_message: env.warnings.join("\n"),
+            palette: PaletteState::default(),
+            palette_entries: palette_entries(),
+            toast: None,
+            table_view,
+            status: StatusInfo {
+                connection: executor_state,
+                project,
+                ..Default::default()
+            },
+            pending_table: None,
+            pending_query: None,
+            recovery_prompt: RecoveryData::load(),
+            recovery_saved: RecoveryData::default(),
+            next_recovery_save: 0.0,
+            children_view: None,
+            popouts: Vec::new(),
+            next_popout_id: 0,
+            history_cursor: HistoryCursor::default(),
+            show_about: false,
+            show_help: false,
+            last_query: None,
+            where_builder: WhereBuilderState::default(),
+            template_name: String::new(),
+            large_query: None,
+            show_reload_config: false,
+            show_inspector: false,
+            health,
+            pending_ping: None,
+            batch_export: BatchExportState::default(),
+            export_columns: ExportColumnsState::default(),
+            similar_query: None,
+            validate: ValidateState::default(),
+            show_rpc: false,
+            rpc: RpcState::default(),
+            wwise_version: None,
+            project_types: None,
+            dismissed_lints: Vec::new(),
+            snapshot: None,
+            show_diff: false,
+            watch: WatchState::default(),
+            watch_base: None,
+            auto_refresh: AutoRefreshState::default(),
         }
     }
 }
@@ -184,49 +516,1572 @@ impl WaqlApp {
         let config = UserConfig::load();
         // 设置自定义字体和大小
         setup_custom_fonts(&cc.egui_ctx, config.fontsize);
-        Self::default()
+        let app = Self::default();
+        apply_theme(&cc.egui_ctx, &app.theme);
+        app
+    }
+
+    /// 切换到下一个主题并保存
+    fn cycle_theme(&mut self, ctx: &egui::Context) {
+        self.theme = next_theme(&self.theme);
+        apply_theme(ctx, &self.theme);
+        self.config.theme_name = self.theme.name().to_string();
+        let _ = self.config.save();
+        self.toast = Some(Toast::new(ctx, format!("Theme: {}", self.theme.name())));
+    }
+
+    /// 选择导出文件路径
+    ///
+    /// 对话框从该格式最近使用的目录开始，选择后记录新的目录
+    fn pick_export_path(
+        &mut self,
+        extension: &str,
+        file_name: &str,
+        filter_name: &str,
+    ) -> Option<PathBuf> {
+        let mut dialog = rfd::FileDialog::new()
+            .set_file_name(file_name)
+            .add_filter(filter_name, &[extension]);
+        if let Some(dir) = self.config.export_dir(extension) {
+            dialog = dialog.set_directory(dir);
+        }
+        let path = dialog.save_file()?;
+        self.config.remember_export_dir(extension, &path);
+        let _ = self.config.save();
+        Some(path)
     }
 
     /// 导出结果到 CSV 文件
-    fn export_to_csv(&self) {
-        if let Some(table_data) = &self.table_data {
-            if let Some(path) = rfd::FileDialog::new()
-                .set_file_name("waql_results.csv")
-                .add_filter("CSV Files", &["csv"])
-                .save_file()
-            {
-                if let Err(e) = table_data.export_to_csv(&path) {
-                    eprintln!("Failed to export CSV: {}", e);
+    fn export_to_csv(&mut self) {
+        if self.table_data.is_none() {
+            return;
+        }
+        let Some(path) = self.pick_export_path("csv", "waql_results.csv", "CSV Files") else {
+            return;
+        };
+        if let Some(table_data) = &self.table_data
+            && let Err(e) = table_data.export_to_csv(
+                &path,
+                self.config.csv_format(),
+                self.config.safe_csv_export,
+            )
+        {
+            eprintln!("Failed to export CSV: {}", e);
+        }
+    }
+
+    /// 打开结果表格中的文件路径，`reveal` 为 `true` 时在文件管理器中显示
+    ///
+    /// 路径不存在时只提示，不调用系统程序
+    fn open_cell_path(&mut self, ctx: &egui::Context, path: &str, reveal: bool) {
+        let path = Path::new(path);
+        if !path.exists() {
+            self.toast = Some(Toast::new(ctx, format!("⚠ 文件不存在: {}", path.display())));
+            return;
+        }
+        let result = if reveal {
+            reveal_in_file_manager(path)
+        } else {
+            open_with_default_app(path)
+        };
+        if let Err(e) = result {
+            self.toast = Some(Toast::new(ctx, format!("打开失败: {}", e)));
+        }
+    }
+
+    /// 选择一键导出使用的 CSV 文件路径并记住
+    fn choose_report_path(&mut self) -> Option<PathBuf> {
+        let path = self.pick_export_path("csv", "waql_report.csv", "CSV Files")?;
+        self.config.report_path = Some(path.clone());
+        let _ = self.config.save();
+        Some(path)
+    }
+
+    /// 运行查询、导出 CSV 并在文件管理器中显示
+    ///
+    /// 导出到记住的路径，没有时先选择路径。每一步的结果汇总显示在提示消息中
+    fn run_export_reveal(&mut self, ctx: &egui::Context) {
+        let code = self.code.clone();
+        self.execute_code(&code);
+        // 导出需要查询结果，等待后台查询完成
+        if let Some((code, pending)) = self.pending_query.take() {
+            let result = self.executor.finish_query(pending.wait());
+            self.show_query_result(&code, result);
+        }
+        let mut steps = Vec::new();
+        let message = self.export_reveal_steps(&mut steps).err();
+        steps.extend(message);
+        self.toast = Some(Toast::new(ctx, steps.join("\n")));
+    }
+
+    /// 依次执行一键导出的各个步骤，成功的步骤记录到 `steps`
+    ///
+    /// # Errors
+    ///
+    /// 返回第一个失败步骤的描述
+    fn export_reveal_steps(&mut self, steps: &mut Vec<String>) -> Result<(), String> {
+        if self.has_error {
+            return Err("✖ 查询失败".to_string());
+        }
+        steps.push("✔ 查询成功".to_string());
+
+        // 后台解析尚未完成时直接在当前线程解析
+        let table = self
+            .table_data
+            .clone()
+            .or_else(|| {
+                QueryExecutor::table_from_json(
+                    &self.result,
+                    &self.table_view.table_key,
+                    self.executor.max_rows(),
+                )
+            })
+            .ok_or("✖ 结果不是表格，无法导出 CSV")?;
+
+        let path = match self.config.report_path.clone() {
+            Some(path) => path,
+            None => self.choose_report_path().ok_or("✖ 已取消导出")?,
+        };
+        table
+            .export_to_csv(&path, self.config.csv_format(), self.config.safe_csv_export)
+            .map_err(|e| format!("✖ 导出 CSV 失败: {}", e))?;
+        steps.push(format!("✔ 已导出到 {}", path.display()));
+
+        reveal_in_file_manager(&path).map_err(|e| format!("✖ 打开文件管理器失败: {}", e))?;
+        steps.push("✔ 已在文件管理器中显示".to_string());
+        Ok(())
+    }
+
+    /// 导出保存的查询语句为查询集文件
+    fn export_query_set(&mut self, ctx: &egui::Context) {
+        let file_name = format!("queries.{}", QUERY_SET_EXTENSION);
+        let Some(path) =
+            self.pick_export_path(QUERY_SET_EXTENSION, &file_name, "WAQL Query Set")
+        else {
+            return;
+        };
+        let message = match self.config.export_query_set(&path) {
+            Ok(()) => format!("已导出 {} 条查询", self.config.saved_queries.len()),
+            Err(e) => format!("导出查询集失败: {}", e),
+        };
+        self.toast = Some(Toast::new(ctx, message));
+    }
+
+    /// 从查询集文件导入查询语句
+    fn import_query_set(&mut self, ctx: &egui::Context) {
+        let Some(path) = rfd::FileDialog::new()
+            .add_filter("WAQL Query Set", &[QUERY_SET_EXTENSION])
+            .pick_file()
+        else {
+            return;
+        };
+        let message = match self.config.import_query_set(&path) {
+            Ok(added) => {
+                let _ = self.config.save();
+                format!("已导入 {} 条新查询", added)
+            }
+            Err(e) => format!("导入查询集失败: {}", e),
+        };
+        self.toast = Some(Toast::new(ctx, message));
+    }
+
+    /// 导出完整的原始 JSON 结果到文件
+    fn export_to_json(&mut self) {
+        if self.result.is_empty() || self.has_error {
+            return;
+        }
+        if let Some(path) = self.pick_export_path("json", "waql_results.json", "JSON Files")
+            && let Err(e) = std::fs::write(&path, &self.result)
+        {
+            eprintln!("Failed to export JSON: {}", e);
+        }
+    }
+
+    /// 导出结果表格到 NDJSON 文件
+    fn export_to_ndjson(&mut self) {
+        if self.table_data.is_none() {
+            return;
+        }
+        let Some(path) = self.pick_export_path("ndjson", "waql_results.ndjson", "NDJSON Files")
+        else {
+            return;
+        };
+        if let Some(table_data) = &self.table_data
+            && let Err(e) = table_data.export_to_ndjson(&path)
+        {
+            eprintln!("Failed to export NDJSON: {}", e);
+        }
+    }
+
+    /// 导出结果表格到 Parquet 文件
+    #[cfg(feature = "parquet")]
+    fn export_to_parquet(&mut self) {
+        if self.table_data.is_none() {
+            return;
+        }
+        let Some(path) = self.pick_export_path("parquet", "waql_results.parquet", "Parquet Files")
+        else {
+            return;
+        };
+        if let Some(table_data) = &self.table_data
+            && let Err(e) = table_data.export_to_parquet(&path)
+        {
+            eprintln!("Failed to export Parquet: {}", e);
+        }
+    }
+
+    /// 导出当前表格的列名和推断出的类型
+    fn export_schema(&mut self) {
+        let Some(schema) = self.table_data.as_ref().map(TableData::schema_json) else {
+            return;
+        };
+        if let Some(path) = self.pick_export_path("json", "waql_schema.json", "JSON Files")
+            && let Err(e) = std::fs::write(&path, schema)
+        {
+            eprintln!("Failed to export schema: {}", e);
+        }
+    }
+
+    /// 导出当前表格每一列的统计信息到 CSV 文件
+    fn export_stats(&mut self) {
+        if self.table_data.is_none() {
+            return;
+        }
+        let Some(path) = self.pick_export_path("csv", "waql_stats.csv", "CSV Files") else {
+            return;
+        };
+        if let Some(table_data) = &self.table_data
+            && let Err(e) = table_data.export_stats(&path, self.config.csv_format())
+        {
+            eprintln!("Failed to export stats: {}", e);
+        }
+    }
+
+    /// 导出最近执行的查询、连接信息和结果表格组成的报告
+    fn export_report(&mut self, format: ReportFormat) {
+        if self.table_data.is_none() {
+            return;
+        }
+        let file_name = format!("waql_report.{}", format.extension());
+        let filter = format!("{} Files", format.label());
+        let Some(path) = self.pick_export_path(format.extension(), &file_name, &filter) else {
+            return;
+        };
+        let Some(table) = &self.table_data else {
+            return;
+        };
+        let query = self.last_query.as_deref().unwrap_or_default();
+        let query_spans = tokenize(query)
+            .into_iter()
+            .filter_map(|(kind, range)| {
+                let class = match kind {
+                    TokenKind::Keyword => "keyword",
+                    TokenKind::Type => "type",
+                    TokenKind::Special => "special",
+                    TokenKind::Str => "string",
+                    TokenKind::Numeric => "number",
+                    TokenKind::Comment => "comment",
+                    _ => return None,
+                };
+                Some((range, class))
+            })
+            .collect();
+        let connection = if self.executor.is_offline() {
+            "Offline demo".to_string()
+        } else {
+            self.config.active_connection.clone()
+        };
+        let report = QueryReport {
+            query,
+            query_spans,
+            generated_at: SystemTime::now(),
+            connection,
+            project: self.status.project.as_ref().map(|p| p.name.clone()),
+            table,
+        };
+        if let Err(e) = std::fs::write(&path, report.render(format)) {
+            eprintln!("Failed to export report: {}", e);
+        }
+    }
+
+    /// 处理拖放到窗口中的文件
+    ///
+    /// 查询文件（.waql/.txt）加载到编辑器，JSON 文件作为查询结果显示
+    fn load_dropped_file(&mut self, ctx: &egui::Context, file: &egui::DroppedFile) {
+        let name = file
+            .path
+            .as_ref()
+            .and_then(|p| p.file_name())
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| file.name.clone());
+        let extension = std::path::Path::new(&name)
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        let is_query_file = QUERY_FILE_EXTENSIONS.contains(&extension.as_str());
+        if !is_query_file && extension != "json" {
+            self.toast = Some(Toast::new(
+                ctx,
+                format!("不支持的文件类型: {}（支持 .waql、.txt、.json）", name),
+            ));
+            return;
+        }
+
+        let content = match (&file.bytes, &file.path) {
+            (Some(bytes), _) => Ok(String::from_utf8_lossy(bytes).into_owned()),
+            (None, Some(path)) => std::fs::read_to_string(path).map_err(|e| e.to_string()),
+            (None, None) => Err("无法读取文件内容".to_string()),
+        };
+        let content = match content {
+            Ok(content) => content,
+            Err(e) => {
+                self.toast = Some(Toast::new(ctx, format!("读取 {} 失败: {}", name, e)));
+                return;
+            }
+        };
+
+        if is_query_file {
+            self.code = content.trim().to_string();
+            self.toast = Some(Toast::new(ctx, format!("已加载查询: {}", name)));
+            return;
+        }
+
+        if let Err(e) = self.load_result_json(&name, &content) {
+            self.toast = Some(Toast::new(ctx, format!("{}: {}", name, e)));
+        }
+    }
+
+    /// 将 JSON 文本作为查询结果显示
+    ///
+    /// # Errors
+    ///
+    /// 如果文本不是合法的 JSON，返回错误信息
+    fn load_result_json(&mut self, name: &str, content: &str) -> Result<(), String> {
+        let result = QueryExecutor::result_from_json(content, self.executor.max_rows())?;
+        self.show_loaded_result(&format!("已加载 {}", name), result);
+        Ok(())
+    }
+
+    /// 在结果面板中显示不是由查询产生的结果，状态消息以 `label` 开头
+    fn show_loaded_result(&mut self, label: &str, result: QueryResult) {
+        self.pending_query = None;
+        self.has_error = false;
+        self.result = result.raw_json;
+        self.table_data = result.table_data;
+        self.table_view.array_keys = result.array_keys;
+        self.table_view.table_key = result.table_key;
+        self.status_message = if let Some(pending) = &result.pending_table {
+            format!("{} - 正在解析 {} 行...", label, pending.rows)
+        } else {
+            match result.kind {
+                ResultKind::Table => format!("{} - {} 条结果", label, result.count),
+                ResultKind::EmptyTable => {
+                    format!("{} - {}", label, self.config.empty_result_message())
                 }
+                ResultKind::NonTabular => format!("{} - {}", label, NON_TABULAR_MESSAGE),
             }
+        };
+        self.pending_table = result.pending_table.map(|pending| (Vec::new(), pending));
+    }
+
+    /// 定期将编辑内容写入崩溃恢复文件，内容未变化时跳过
+    fn autosave_recovery(&mut self, ctx: &egui::Context) {
+        // 等待用户决定是否恢复上次的内容，避免覆盖恢复文件
+        if self.recovery_prompt.is_some() {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        if now < self.next_recovery_save {
+            return;
+        }
+        self.next_recovery_save = now + RECOVERY_INTERVAL;
+
+        let data = RecoveryData {
+            code: self.code.clone(),
+            result: if self.has_error || self.result.len() > RECOVERY_RESULT_LIMIT {
+                String::new()
+            } else {
+                self.result.clone()
+            },
+        };
+        if data != self.recovery_saved {
+            if data.is_empty() {
+                RecoveryData::clear();
+            } else if let Err(e) = data.save() {
+                eprintln!("Failed to write recovery file: {}", e);
+            }
+            self.recovery_saved = data;
+        }
+    }
+
+    /// 显示恢复上次未保存内容的提示
+    fn render_recovery_prompt(&mut self, ctx: &egui::Context) {
+        let Some(data) = &self.recovery_prompt else {
+            return;
+        };
+        let mut restore = false;
+        let mut discard = false;
+        egui::Window::new("Restore unsaved work?")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label("The previous session did not exit cleanly.");
+                if let Some(first_line) = data.code.lines().find(|l| !l.trim().is_empty()) {
+                    ui.monospace(first_line);
+                }
+                if !data.result.is_empty() {
+                    ui.weak("The last query result can also be restored.");
+                }
+                ui.horizontal(|ui| {
+                    restore = ui.button("Restore").clicked();
+                    discard = ui.button("Discard").clicked();
+                });
+            });
+
+        if restore {
+            if let Some(data) = self.recovery_prompt.take() {
+                self.code = data.code;
+                self.edit_history.reset(&self.code);
+                if !data.result.is_empty() {
+                    let _ = self.load_result_json("recovery", &data.result);
+                }
+                self.toast = Some(Toast::new(ctx, "已恢复上次未保存的内容"));
+            }
+        } else if discard {
+            self.recovery_prompt = None;
+            RecoveryData::clear();
+        }
+    }
+
+    /// 执行编辑器中的全部内容
+    fn execute_query(&mut self, ctx: &egui::Context) {
+        let code = self.code.clone();
+        self.execute_code(&code);
+        self.restore_editor_focus(ctx);
+    }
+
+    /// 执行选中的内容
+    ///
+    /// 没有选中内容时执行光标所在的语句（以空行分隔），
+    /// 找不到明确的语句时执行全部内容
+    fn execute_selection(&mut self, ctx: &egui::Context) {
+        let code = selected_text(ctx, &self.code)
+            .or_else(|| {
+                let caret = caret_index(ctx)?;
+                statement_at(&self.code, caret).map(|range| self.code[range].to_string())
+            })
+            .unwrap_or_else(|| self.code.clone());
+        self.execute_code(&code);
+        self.restore_editor_focus(ctx);
+    }
+
+    /// 按设置在执行查询后将焦点移回编辑器
+    fn restore_editor_focus(&self, ctx: &egui::Context) {
+        if self.config.keep_editor_focus {
+            focus_editor(ctx);
         }
     }
 
     /// 执行 WAQL 查询并更新结果
-    fn execute_query(&mut self) {
-        match self.executor.execute(&self.code) {
-            Ok(result) => {
+    ///
+    /// 开启大查询检查时，没有筛选条件的大类型查询先等待确认
+    fn execute_code(&mut self, code: &str) {
+        if self.config.large_query_guard
+            && let Some(kind) = broad_query_type(code, &self.config.broad_types)
+        {
+            self.status_message = format!("查询可能返回所有 {} 对象，等待确认", kind);
+            self.large_query = Some((code.to_string(), kind.to_string()));
+            return;
+        }
+        self.execute_confirmed(code);
+    }
+
+    /// 执行 WAQL 查询并更新结果，不做大查询检查
+    fn execute_confirmed(&mut self, code: &str) {
+        self.config.push_history(code);
+        let _ = self.config.save();
+        self.history_cursor = HistoryCursor::default();
+        self.table_view.flashed.clear();
+        self.last_query = Some(code.to_string());
+        // 没有投影时按设置追加 select 子句，只影响发送的查询，不修改编辑器内容
+        let selected = self
+            .config
+            .auto_select
+            .then(|| append_select(code, &self.config.auto_select_fields))
+            .flatten();
+        let code = selected.as_deref().unwrap_or(code);
+        if self.config.background_queries {
+            self.pending_query = Some((code.to_string(), self.executor.spawn_execute(code)));
+            self.pending_table = None;
+            self.status_message = "正在查询...".to_string();
+        } else {
+            self.run_code(code);
+        }
+    }
+
+    /// 检查后台查询是否完成，完成后显示结果
+    fn poll_pending_query(&mut self, ctx: &egui::Context) {
+        let Some((_, pending)) = &self.pending_query else {
+            return;
+        };
+        let Some(outcome) = pending.poll() else {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        };
+        let Some((code, _)) = self.pending_query.take() else {
+            return;
+        };
+        let result = self.executor.finish_query(outcome);
+        self.show_query_result(&code, result);
+    }
+
+    /// 重新执行上一次执行的查询，不使用编辑器中的内容
+    fn rerun_last_query(&mut self) {
+        let Some(query) = self.last_query.clone() else {
+            self.status_message = "还没有执行过查询".to_string();
+            return;
+        };
+        self.execute_code(&query);
+        self.status_message = format!("已重新执行: {} - {}", query, self.status_message);
+    }
+
+    /// 执行查询并更新结果，不记录历史
+    fn run_code(&mut self, code: &str) {
+        let result = self.executor.execute(code);
+        self.show_query_result(code, result);
+    }
+
+    /// 显示查询结果或错误信息
+    fn show_query_result(&mut self, code: &str, result: Result<QueryResult, String>) {
+        self.status.connection = self.executor.connection_state();
+        match result {
+            Ok(mut result) => {
+                // 表格列按 select 子句中字段的顺序排列，后台解析的表格在解析完成后排列
+                let fields = select_fields(code);
+                if let Some(table) = &mut result.table_data {
+                    table.order_columns(&fields);
+                }
+                self.status.query_latency = Some(result.elapsed);
+                self.status.row_count = Some(result.count);
+                self.status.result_bytes = result.raw_json.len();
                 self.has_error = false;
                 self.result = result.raw_json;
                 self.table_data = result.table_data;
-                self.status_message = if result.count > 0 {
-                    format!("查询成功 - {} 条结果", result.count)
+                self.table_view.array_keys = result.array_keys;
+                self.table_view.table_key = result.table_key;
+                self.status_message = if let Some(pending) = &result.pending_table {
+                    format!("正在解析 {} 行...", pending.rows)
                 } else {
-                    String::new()
+                    match result.kind {
+                        ResultKind::Table => format!("查询成功 - {} 条结果", result.count),
+                        ResultKind::EmptyTable => self.config.empty_result_message().to_string(),
+                        ResultKind::NonTabular => NON_TABULAR_MESSAGE.to_string(),
+                    }
                 };
+                if let Some(fixed) = &result.fixed_query {
+                    self.status_message = format!("已自动补全 $: {} {}", fixed, self.status_message);
+                }
+                if !result.duplicate_keys.is_empty() {
+                    self.status_message = format!(
+                        "{}（响应中有重复的键 {}，只显示最后一个值）",
+                        self.status_message,
+                        result.duplicate_keys.join(", ")
+                    );
+                }
+                self.pending_table = result.pending_table.map(|pending| (fields, pending));
             }
             Err(e) => {
+                self.status.query_latency = None;
+                self.status.row_count = None;
+                self.status.result_bytes = 0;
                 self.result = e;
                 self.has_error = true;
                 self.table_data = None;
+                self.pending_table = None;
                 self.status_message = "查询失败".to_string();
             }
         }
     }
+
+    /// 查询对象的子对象，结果显示在子对象视图中
+    fn show_children(&mut self, guid: String) {
+        let code = format!(
+            "$ from object {} select children | {}",
+            quote_string(&guid),
+            CHILDREN_RETURN
+        );
+        let result = self.executor.execute(&code);
+        self.status.connection = self.executor.connection_state();
+        let mut view = ChildrenView {
+            guid,
+            result: String::new(),
+            table_data: None,
+            has_error: false,
+            table_view: TableViewState::default(),
+        };
+        match result {
+            Ok(result) => {
+                // 子对象数量通常不大，需要后台解析时直接在当前线程解析
+                view.table_data = match result.pending_table {
+                    Some(_) => QueryExecutor::table_from_json(
+                        &result.raw_json,
+                        &result.table_key,
+                        self.executor.max_rows(),
+                    ),
+                    None => result.table_data,
+                };
+                view.result = result.raw_json;
+                view.table_view.array_keys = result.array_keys;
+                view.table_view.table_key = result.table_key;
+            }
+            Err(e) => {
+                view.result = e;
+                view.has_error = true;
+            }
+        }
+        self.children_view = Some(view);
+    }
+
+    /// 在单独的窗口中打开表格的副本，之后的查询不会改变其内容
+    fn pop_out(&mut self, query: String, result: String, table: TableData) {
+        let table_view = TableViewState::with_layout(
+            self.config.frozen_columns,
+            self.config.column_widths.clone(),
+        );
+        self.next_popout_id += 1;
+        self.popouts.push(PoppedResult {
+            id: self.next_popout_id,
+            query,
+            result,
+            table_data: Some(table),
+            table_view,
+        });
+    }
+
+    /// 渲染弹出的结果窗口
+    ///
+    /// 支持多视口时每个结果使用独立的系统窗口，否则显示为应用内的窗口
+    fn render_popouts(&mut self, ctx: &egui::Context) {
+        let theme = self.theme;
+        let rules = self.config.active_cell_rules();
+        let mut all_actions = Vec::new();
+        let mut copies = Vec::new();
+        self.popouts.retain_mut(|popout| {
+            popout.table_view.null_display = self.config.null_display;
+            popout.table_view.show_type_badges = self.config.show_type_badges;
+            popout.table_view.json_max_depth = self.config.json_max_depth();
+            let title = format!("Result: {}", popout.query);
+            let id = egui::ViewportId::from_hash_of(("popout", popout.id));
+            let builder = egui::ViewportBuilder::default()
+                .with_title(&title)
+                .with_inner_size([700.0, 450.0]);
+            let mut open = true;
+            let mut actions = None;
+            ctx.show_viewport_immediate(id, builder, |ctx, class| {
+                let mut render = |ui: &mut egui::Ui| {
+                    actions = Some(render_results(
+                        ui,
+                        &popout.result,
+                        &popout.table_data,
+                        false,
+                        &mut popout.table_view,
+                        &theme,
+                        rules,
+                    ));
+                };
+                if class == egui::ViewportClass::Embedded {
+                    egui::Window::new(&title)
+                        .id(egui::Id::new(id))
+                        .open(&mut open)
+                        .default_size([700.0, 450.0])
+                        .show(ctx, |ui| render(ui));
+                } else {
+                    egui::CentralPanel::default().show(ctx, |ui| render(ui));
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        open = false;
+                    }
+                }
+            });
+            if let Some(actions) = actions {
+                if let (true, Some(table)) = (actions.pop_out, &popout.table_data) {
+                    copies.push((popout.query.clone(), popout.result.clone(), table.clone()));
+                }
+                all_actions.push(actions);
+            }
+            open
+        });
+
+        for (query, result, table) in copies {
+            self.pop_out(query, result, table);
+        }
+        for actions in all_actions {
+            if let Some(value) = actions.copied_value {
+                self.copy_to_clipboard(ctx, value, "Copied!");
+            }
+            if let Some((list, count)) = actions.copied_list {
+                self.copy_to_clipboard(ctx, list, format!("Copied {} references", count));
+            }
+            if let Some(path) = actions.open_path {
+                self.open_cell_path(ctx, &path, false);
+            }
+            if let Some(path) = actions.reveal_path {
+                self.open_cell_path(ctx, &path, true);
+            }
+            if let Some(guid) = actions.show_children {
+                self.show_children(guid);
+            }
+        }
+    }
+
+    /// 渲染子对象结果窗口
+    ///
+    /// 在窗口中继续查看子对象时替换当前内容，可以逐级浏览层级
+    fn render_children_view(&mut self, ctx: &egui::Context) {
+        let Some(view) = &mut self.children_view else {
+            return;
+        };
+        view.table_view.null_display = self.config.null_display;
+        view.table_view.show_type_badges = self.config.show_type_badges;
+        view.table_view.json_max_depth = self.config.json_max_depth();
+        let mut open = true;
+        let mut actions = None;
+        egui::Window::new(format!("Children of {}", view.guid))
+            .id(egui::Id::new("children_view"))
+            .open(&mut open)
+            .default_size([600.0, 400.0])
+            .show(ctx, |ui| {
+                actions = Some(render_results(
+                    ui,
+                    &view.result,
+                    &view.table_data,
+                    view.has_error,
+                    &mut view.table_view,
+                    &self.theme,
+                    self.config.active_cell_rules(),
+                ));
+            });
+
+        if let Some(actions) = actions {
+            if actions.table_key_changed {
+                view.table_data = QueryExecutor::table_from_json(
+                    &view.result,
+                    &view.table_view.table_key,
+                    self.executor.max_rows(),
+                );
+            }
+            if let (true, Some(table)) = (actions.pop_out, view.table_data.clone()) {
+                let query = format!("Children of {}", view.guid);
+                let result = view.result.clone();
+                self.pop_out(query, result, table);
+            }
+            if let Some(value) = actions.copied_value {
+                self.copy_to_clipboard(ctx, value, "Copied!");
+            }
+            if let Some((list, count)) = actions.copied_list {
+                self.copy_to_clipboard(ctx, list, format!("Copied {} references", count));
+            }
+            if let Some(path) = actions.open_path {
+                self.open_cell_path(ctx, &path, false);
+            }
+            if let Some(path) = actions.reveal_path {
+                self.open_cell_path(ctx, &path, true);
+            }
+            if let Some(guid) = actions.show_children {
+                self.show_children(guid);
+                return;
+            }
+        }
+        if !open {
+            self.children_view = None;
+        }
+    }
+
+    /// 定期在后台检测连接，断开后按退避策略自动重连
+    ///
+    /// 离线模式或关闭健康检查时不检测
+    fn poll_health(&mut self, ctx: &egui::Context) {
+        self.status.reconnect_in = None;
+        if !self.config.health_check
+            || self.executor.is_offline()
+            || self.status.connection == ConnectionState::Idle
+        {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+
+        if let Some(receiver) = &self.pending_ping {
+            let outcome = match receiver.try_recv() {
+                Ok(outcome) => outcome,
+                Err(TryRecvError::Empty) => {
+                    ctx.request_repaint_after(Duration::from_millis(200));
+                    return;
+                }
+                Err(TryRecvError::Disconnected) => PingOutcome {
+                    latency: Err("连接检测中断".to_string()),
+                    project: None,
+                    object_types: None,
+                },
+            };
+            self.pending_ping = None;
+            self.health.record(outcome.latency.is_ok(), now);
+            match outcome.latency {
+                Ok(latency) => {
+                    self.status.ping_latency = Some(latency);
+                    self.status.connection = ConnectionState::Connected;
+                    // 项目信息和对象类型已在检测线程中获取
+                    if self.status.project.is_none() {
+                        self.status.project = outcome.project;
+                    }
+                    if self.project_types.is_none() {
+                        self.project_types = outcome.object_types;
+                    }
+                }
+                Err(_) => {
+                    self.status.ping_latency = None;
+                    self.status.connection = ConnectionState::Failed;
+                }
+            }
+        } else if self.health.is_due(now) {
+            self.pending_ping = Some(
+                self.executor
+                    .spawn_ping(self.status.project.is_none(), self.project_types.is_none()),
+            );
+            ctx.request_repaint_after(Duration::from_millis(200));
+            return;
+        }
+
+        // 断开时每秒刷新一次重连倒计时
+        let remaining = self.health.remaining(now);
+        if self.status.connection == ConnectionState::Failed {
+            self.status.reconnect_in = Some(Duration::from_secs_f64(remaining));
+            ctx.request_repaint_after(Duration::from_secs_f64(remaining.min(1.0)));
+        } else {
+            ctx.request_repaint_after(Duration::from_secs_f64(remaining));
+        }
+    }
+
+    /// 空闲超时后断开连接，下次请求时再重新连接
+    fn poll_idle(&mut self, ctx: &egui::Context) {
+        let Some(remaining) = self.executor.idle_remaining() else {
+            return;
+        };
+        if !remaining.is_zero() {
+            ctx.request_repaint_after(remaining);
+            return;
+        }
+        if self.pending_query.is_some() || self.pending_ping.is_some() {
+            return;
+        }
+        self.executor.disconnect_idle();
+        self.status.connection = self.executor.connection_state();
+        self.status.ping_latency = None;
+        self.status.reconnect_in = None;
+    }
+
+    /// 检查后台表格解析是否完成
+    fn poll_pending_table(&mut self, ctx: &egui::Context) {
+        let Some((fields, pending)) = &self.pending_table else {
+            return;
+        };
+        match pending.poll() {
+            Some(mut table) => {
+                if let Some(table) = &mut table {
+                    table.order_columns(fields);
+                    self.status_message = format!("查询成功 - {} 条结果", table.total_rows);
+                }
+                self.table_data = table;
+                self.pending_table = None;
+                if let Some(base) = self.watch_base.take() {
+                    self.merge_keyed(ctx, base);
+                }
+            }
+            None => ctx.request_repaint_after(Duration::from_millis(100)),
+        }
+    }
+
+    /// 到时间后重新执行正在监视的查询
+    fn poll_watch(&mut self, ctx: &egui::Context) {
+        if !self.watch.running {
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        // 上一次的结果仍在后台解析或手动执行的查询未完成时推迟
+        if now < self.watch.next_at
+            || self.pending_table.is_some()
+            || self.pending_query.is_some()
+        {
+            ctx.request_repaint_after(Duration::from_secs_f64((self.watch.next_at - now).max(0.1)));
+            return;
+        }
+        self.watch.next_at = now + self.watch.interval_secs as f64;
+        ctx.request_repaint_after(Duration::from_secs(self.watch.interval_secs as u64));
+
+        let query = self.watch.query.clone();
+        let previous = if self.watch.keyed {
+            self.table_data.take()
+        } else {
+            None
+        };
+        self.run_code(&query);
+        if let Some(previous) = previous {
+            if self.pending_table.is_some() {
+                self.watch_base = Some(previous);
+            } else {
+                self.merge_keyed(ctx, previous);
+            }
+        }
+    }
+
+    /// 定时重新执行最近一次查询并替换结果
+    ///
+    /// 编辑查询或操作表格后暂停，空闲一段时间后继续；监视查询时不自动刷新
+    fn poll_auto_refresh(&mut self, ctx: &egui::Context) {
+        if !self.config.auto_refresh || self.watch.running || self.last_query.is_none() {
+            self.auto_refresh.next_at = 0.0;
+            return;
+        }
+        let now = ctx.input(|i| i.time);
+        let interval = f64::from(self.config.auto_refresh_secs.max(1));
+        if self.auto_refresh.next_at == 0.0 {
+            self.auto_refresh.next_at = now + interval;
+        }
+        // 每秒刷新一次倒计时
+        ctx.request_repaint_after(Duration::from_secs(1));
+        if now < self.auto_refresh.next_at
+            || self.auto_refresh.is_paused(now)
+            || self.pending_table.is_some()
+            || self.pending_query.is_some()
+        {
+            return;
+        }
+        self.auto_refresh.next_at = now + interval;
+        if let Some(query) = self.last_query.clone() {
+            self.run_code(&query);
+        }
+    }
+
+    /// 将新结果按键列合并到原表格，并高亮发生变化的单元格
+    ///
+    /// 查询失败或没有表格结果时保留原表格
+    fn merge_keyed(&mut self, ctx: &egui::Context, mut base: TableData) {
+        if let Some(update) = self.table_data.take() {
+            let changed = base.upsert(update, &self.watch.key_column);
+            self.status_message = format!(
+                "查询成功 - {} 条结果，{} 个单元格变化",
+                base.rows.len(),
+                changed.len()
+            );
+            self.table_view.flash(ctx, changed);
+        }
+        self.table_data = Some(base);
+    }
+
+    /// 切换到指定连接并重建客户端
+    fn switch_connection(&mut self, name: &str) {
+        if !self.config.activate_connection(name) {
+            return;
+        }
+        let _ = self.config.save();
+        self.executor.set_client_config(self.config.client_config());
+        self.reset_connection_status();
+        self.status_message = format!("已切换到连接 {}", name);
+    }
+
+    /// 连接改变后重置状态栏并重新获取项目信息
+    fn reset_connection_status(&mut self) {
+        self.wwise_version = None;
+        self.health = self.config.health_check();
+        self.pending_ping = None;
+        let project = self.executor.refresh_project_info().ok();
+        self.project_types = None;
+        self.status = StatusInfo {
+            connection: self.executor.connection_state(),
+            project,
+            ..Default::default()
+        };
+    }
+
+    /// 渲染与快照的比较窗口
+    fn render_diff(&mut self, ctx: &egui::Context) {
+        if !self.show_diff {
+            return;
+        }
+        match (&self.table_data, &self.snapshot) {
+            (Some(current), Some(snapshot)) => {
+                render_diff_window(ctx, &mut self.show_diff, current, &snapshot.table);
+            }
+            _ => self.show_diff = false,
+        }
+    }
+
+    /// 渲染批量导出窗口并处理操作
+    fn render_batch_export(&mut self, ctx: &egui::Context) {
+        if !self.batch_export.open {
+            return;
+        }
+        let actions = render_batch_export_window(
+            ctx,
+            &mut self.batch_export,
+            &self.config.saved_queries,
+            &self.config.active_connection,
+        );
+        if actions.choose_folder {
+            let extension = self.batch_export.format.extension();
+            let mut dialog = rfd::FileDialog::new();
+            if let Some(dir) = self
+                .batch_export
+                .folder
+                .as_deref()
+                .or_else(|| self.config.export_dir(extension))
+            {
+                dialog = dialog.set_directory(dir);
+            }
+            if let Some(folder) = dialog.pick_folder() {
+                self.batch_export.folder = Some(folder);
+            }
+        }
+        if actions.run {
+            self.run_batch_export(ctx);
+        }
+    }
+
+    /// 渲染按自定义列导出的窗口，没有表格结果时不显示
+    fn render_export_columns(&mut self, ctx: &egui::Context) {
+        if !self.export_columns.open {
+            return;
+        }
+        let Some(columns) = self.table_data.as_ref().map(|t| t.columns.clone()) else {
+            return;
+        };
+        if render_export_columns_window(ctx, &mut self.export_columns, &columns) {
+            self.export_selected_columns(ctx);
+        }
+    }
+
+    /// 只导出选中的列，按窗口中的顺序排列
+    fn export_selected_columns(&mut self, ctx: &egui::Context) {
+        let format = self.export_columns.format;
+        let file_name = format!("waql_results.{}", format.extension());
+        let filter = format!("{} Files", format.label());
+        let Some(path) = self.pick_export_path(format.extension(), &file_name, &filter) else {
+            return;
+        };
+        let Some(table) = &self.table_data else {
+            return;
+        };
+        let table = table.select_columns(&self.export_columns.selected());
+        let message = match table.export(
+            &path,
+            format,
+            self.config.csv_format(),
+            self.config.safe_csv_export,
+        ) {
+            Ok(()) => format!("已导出 {} 列到 {}", table.columns.len(), path.display()),
+            Err(e) => format!("导出失败: {}", e),
+        };
+        self.toast = Some(Toast::new(ctx, message));
+    }
+
+    /// 依次执行选中的保存的查询，每个查询的结果导出到以查询名称命名的文件
+    ///
+    /// 单个查询失败不会中断整个批量导出，结果汇总显示在窗口中
+    fn run_batch_export(&mut self, ctx: &egui::Context) {
+        let Some(folder) = self.batch_export.folder.clone() else {
+            return;
+        };
+        let format = self.batch_export.format;
+        let indices: Vec<usize> = self.batch_export.selected.iter().copied().collect();
+        let mut file_names = HashSet::new();
+        let mut report = Vec::new();
+
+        for index in indices {
+            let Some(query) = self.config.saved_queries.get(index).cloned() else {
+                continue;
+            };
+            // 同名查询的文件名加上序号
+            let base = sanitize_file_name(query.title());
+            let mut file_name = format!("{}.{}", base, format.extension());
+            let mut n = 2;
+            while !file_names.insert(file_name.clone()) {
+                file_name = format!("{} ({}).{}", base, n, format.extension());
+                n += 1;
+            }
+            let path = folder.join(file_name);
+            let result = self.export_query(&query.query, &path, format);
+            report.push((query.title().to_string(), result.map(|()| path)));
+        }
+        self.status.connection = self.executor.connection_state();
+
+        let failed = report.iter().filter(|(_, result)| result.is_err()).count();
+        let message = format!("批量导出完成: 成功 {}，失败 {}", report.len() - failed, failed);
+        self.toast = Some(Toast::new(ctx, message));
+        self.batch_export.report = report;
+    }
+
+    /// 渲染保存的查询检查窗口，检查进行中时每帧执行一个查询
+    fn render_validate(&mut self, ctx: &egui::Context) {
+        if !self.validate.open {
+            return;
+        }
+        let actions = render_validate_window(ctx, &mut self.validate);
+        if actions.run {
+            let active = &self.config.active_connection;
+            self.validate.pending = self
+                .config
+                .saved_queries
+                .iter()
+                .enumerate()
+                .filter(|(_, query)| query.is_visible_in(active))
+                .map(|(index, _)| index)
+                .collect();
+            self.validate.total = self.validate.pending.len();
+            self.validate.report.clear();
+        }
+        if actions.cancel {
+            self.validate.pending.clear();
+            self.status.connection = self.executor.connection_state();
+        }
+        if actions.export {
+            self.export_validation_report();
+        }
+
+        let Some(index) = self.validate.pending.pop_front() else {
+            return;
+        };
+        if let Some(query) = self.config.saved_queries.get(index) {
+            let result = self.executor.execute(&query.query).map(|result| result.count);
+            self.validate.report.push(ValidateEntry {
+                title: query.title().to_string(),
+                query: query.query.clone(),
+                result,
+            });
+        }
+        if self.validate.is_running() {
+            ctx.request_repaint();
+        } else {
+            self.status.connection = self.executor.connection_state();
+            let failed = self.validate.failed();
+            let message = format!(
+                "查询检查完成: 成功 {}，失败 {}",
+                self.validate.report.len() - failed,
+                failed
+            );
+            self.toast = Some(Toast::new(ctx, message));
+        }
+    }
+
+    /// 导出保存的查询检查结果到 CSV 文件
+    fn export_validation_report(&mut self) {
+        let Some(path) = self.pick_export_path("csv", "waql_validation.csv", "CSV Files") else {
+            return;
+        };
+        let table = self.validate.to_table();
+        let safe = self.config.safe_csv_export;
+        if let Err(e) = table.export_to_csv(&path, self.config.csv_format(), safe) {
+            eprintln!("Failed to export validation report: {}", e);
+        }
+    }
+
+    /// 执行查询并将结果表格导出到文件，不影响主结果区域
+    fn export_query(&mut self, code: &str, path: &Path, format: ExportFormat) -> Result<(), String> {
+        let result = self.executor.execute(code)?;
+        let table = match result.table_data {
+            Some(table) => Some(table),
+            None => QueryExecutor::table_from_json(
+                &result.raw_json,
+                &result.table_key,
+                self.executor.max_rows(),
+            ),
+        };
+        let Some(mut table) = table else {
+            return Err(match result.kind {
+                ResultKind::EmptyTable => self.config.empty_result_message().to_string(),
+                _ => NON_TABULAR_MESSAGE.to_string(),
+            });
+        };
+        table.order_columns(&select_fields(code));
+        table
+            .export(path, format, self.config.csv_format(), self.config.safe_csv_export)
+            .map_err(|e| format!("导出失败: {}", e))
+    }
+
+    /// 渲染大查询确认窗口并处理选择
+    ///
+    /// 追加 `take` 时，如果等待的查询就是编辑器内容，同时更新编辑器
+    fn render_large_query(&mut self, ctx: &egui::Context) {
+        let Some((query, kind)) = &self.large_query else {
+            return;
+        };
+        let limit = self.config.large_query_limit;
+        let Some(choice) = render_large_query_window(ctx, query, kind, limit) else {
+            return;
+        };
+        let Some((query, _)) = self.large_query.take() else {
+            return;
+        };
+        match choice {
+            LargeQueryChoice::Run => self.execute_confirmed(&query),
+            LargeQueryChoice::AddTake => {
+                let limited = append_take(&query, limit);
+                if self.code == query {
+                    self.code = limited.clone();
+                }
+                self.execute_confirmed(&limited);
+            }
+            LargeQueryChoice::Cancel => self.status_message = "已取消执行".to_string(),
+        }
+    }
+
+    /// 保存查询，与已有的查询相似时先确认
+    fn save_query(&mut self, query: String) {
+        let exists = self.config.saved_queries.iter().any(|q| q.query == query);
+        if !exists && let Some(index) = self.config.similar_saved_query(&query) {
+            self.similar_query = Some((query, index));
+            return;
+        }
+        if self.config.add_saved_query(query) {
+            self.save_config();
+        }
+    }
+
+    /// 保存配置，失败时在结果区域显示错误
+    fn save_config(&mut self) {
+        if let Err(e) = self.config.save() {
+            self.result = format!("保存配置失败: {}", e);
+        }
+    }
+
+    /// 渲染保存相似查询的确认窗口并处理选择
+    fn render_similar_query(&mut self, ctx: &egui::Context) {
+        let Some((query, index)) = &self.similar_query else {
+            return;
+        };
+        let Some(existing) = self.config.saved_queries.get(*index) else {
+            self.similar_query = None;
+            return;
+        };
+        let Some(choice) = render_similar_query_window(ctx, query, existing) else {
+            return;
+        };
+        let Some((query, index)) = self.similar_query.take() else {
+            return;
+        };
+        let saved = match choice {
+            SimilarQueryChoice::Update => self.config.update_saved_query(index, query),
+            SimilarQueryChoice::SaveNew => self.config.add_saved_query(query),
+            SimilarQueryChoice::Cancel => false,
+        };
+        if saved {
+            self.save_config();
+        }
+    }
+
+    /// 渲染重新加载配置的确认窗口
+    fn render_reload_config(&mut self, ctx: &egui::Context) {
+        if !self.show_reload_config {
+            return;
+        }
+        let changed_on_disk = self.config.changed_on_disk();
+        let Some(reload) = render_reload_config_window(ctx, changed_on_disk) else {
+            return;
+        };
+        self.show_reload_config = false;
+        if reload {
+            self.reload_config(ctx);
+        }
+    }
+
+    /// 从磁盘重新读取配置，丢弃内存中未保存的修改
+    ///
+    /// 重新应用主题、字体、表格设置、连接和查询设置，并重建补全器
+    fn reload_config(&mut self, ctx: &egui::Context) {
+        let (host, port) = self.config.address_override();
+        self.config = UserConfig::load();
+        self.config.set_address_override(host, port);
+        self.completer = build_completer(&self.syntax, &self.config);
+        self.theme = config_theme(&self.config);
+        apply_theme(ctx, &self.theme);
+        update_font_size(ctx, self.config.fontsize);
+        self.table_view.frozen_columns = self.config.frozen_columns;
+        self.table_view.column_widths = self.config.column_widths.clone();
+        self.table_view.reset_widths = true;
+
+        let offline =
+            self.config.offline_mode || std::env::args().any(|arg| arg == OFFLINE_FLAG);
+        self.executor.set_client_config(self.config.client_config());
+        self.executor.set_offline(offline);
+        apply_executor_settings(&mut self.executor, &self.config);
+        self.reset_connection_status();
+        self.status_message = "已从磁盘重新加载配置".to_string();
+    }
+
+    /// 处理模板菜单操作
+    ///
+    /// 追加只在多行编辑器中有效，以空行与已有内容分隔为新语句
+    fn handle_template_actions(&mut self, actions: TemplateActions) {
+        if let Some((query, append)) = actions.insert {
+            if append && self.config.multiline_editor && !self.code.trim().is_empty() {
+                self.code = format!("{}\n\n{}", self.code.trim_end(), query);
+            } else {
+                self.code = query;
+            }
+        }
+        if let Some(name) = actions.save_current {
+            self.config.query_templates.push(QueryTemplate {
+                name,
+                query: self.code.trim().to_string(),
+            });
+            let _ = self.config.save();
+        }
+        if let Some(index) = actions.remove {
+            self.config.query_templates.remove(index);
+            let _ = self.config.save();
+        }
+    }
+
+    /// 渲染检查器窗口
+    fn render_inspector(&mut self, ctx: &egui::Context) {
+        if !self.show_inspector {
+            return;
+        }
+        let actions =
+            render_inspector_window(ctx, &mut self.show_inspector, self.executor.exchanges());
+        if actions.clear {
+            self.executor.clear_exchanges();
+        }
+        if let Some(text) = actions.copied {
+            self.copy_to_clipboard(ctx, text, "Copied!");
+        }
+    }
+
+    /// 渲染 WAAPI 调用窗口并处理调用
+    fn render_rpc(&mut self, ctx: &egui::Context) {
+        if !self.show_rpc {
+            return;
+        }
+        let actions = render_rpc_window(ctx, &mut self.show_rpc, &mut self.rpc);
+        if actions.send {
+            match self.rpc_request() {
+                // 修改操作先显示预览，确认后再发送
+                Ok(request) if self.config.preview_mutations && is_mutating_uri(&request.uri) => {
+                    self.rpc.pending = Some(request);
+                }
+                Ok(request) => self.send_rpc(request),
+                Err(e) => {
+                    self.rpc.response = e;
+                    self.rpc.has_error = true;
+                }
+            }
+        }
+        if actions.confirm && let Some(request) = self.rpc.pending.take() {
+            self.send_rpc(request);
+        }
+    }
+
+    /// 根据 WAAPI 调用窗口的输入生成请求
+    ///
+    /// # Errors
+    ///
+    /// 参数或完整请求不是合法的 JSON 时返回错误信息
+    fn rpc_request(&self) -> Result<RpcRequest, String> {
+        if self.rpc.raw {
+            return RpcRequest::parse(&self.rpc.raw_request);
+        }
+        let args = if self.rpc.args.trim().is_empty() {
+            json!({})
+        } else {
+            serde_json::from_str::<Value>(&self.rpc.args)
+                .map_err(|e| format!("参数不是合法的 JSON: {}", e))?
+        };
+        Ok(RpcRequest {
+            uri: self.rpc.uri.trim().to_string(),
+            args,
+            options: None,
+        })
+    }
+
+    /// 发送 WAAPI 调用，修改操作先写入审计日志
+    ///
+    /// 审计日志写入失败时不发送调用。直接编辑 JSON-RPC 请求时，响应显示在结果面板中
+    fn send_rpc(&mut self, request: RpcRequest) {
+        if is_mutating_uri(&request.uri)
+            && let Err(e) = AuditEntry::new(&request.uri, request.args.clone()).append()
+        {
+            self.rpc.response = format!("写入审计日志失败，未发送调用: {}", e);
+            self.rpc.has_error = true;
+            return;
+        }
+        let result = self.executor.call_request(&request);
+        self.status.connection = self.executor.connection_state();
+        match result {
+            Ok(value) if self.rpc.raw => {
+                self.rpc.response.clear();
+                self.rpc.has_error = false;
+                let result = QueryExecutor::result_from_value(value, self.executor.max_rows());
+                self.show_loaded_result(&request.uri, result);
+            }
+            Ok(value) => {
+                self.rpc.response = serde_json::to_string_pretty(&value).unwrap_or_default();
+                self.rpc.has_error = false;
+            }
+            Err(e) => {
+                self.rpc.response = e.to_string();
+                self.rpc.has_error = true;
+            }
+        }
+    }
+
+    /// 渲染关于面板，首次打开时获取 Wwise 版本
+    fn render_about(&mut self, ctx: &egui::Context) {
+        if !self.show_about {
+            return;
+        }
+        let wwise_version = match &self.wwise_version {
+            Some(version) => version.clone(),
+            None => {
+                let version = self.executor.wwise_version().map_err(|e| e.to_string());
+                self.status.connection = self.executor.connection_state();
+                self.wwise_version = Some(version.clone());
+                version
+            }
+        };
+        let diagnostics = Diagnostics {
+            version: env!("CARGO_PKG_VERSION"),
+            wwise_version,
+            config_path: UserConfig::get_config_path(),
+            connection: self
+                .config
+                .active_connection()
+                .map(|c| format!("{} ({}:{})", c.name, c.host, c.port))
+                .unwrap_or_default(),
+            offline: self.executor.is_offline(),
+            theme: self.theme.name().to_string(),
+            fontsize: self.config.fontsize,
+        };
+        if let Some(text) = render_about_window(ctx, &mut self.show_about, &diagnostics) {
+            self.copy_to_clipboard(ctx, text, "Copied!");
+        }
+    }
+
+    /// 复制文本到剪贴板并显示提示消息 `message`
+    ///
+    /// 剪贴板不可用时改为写入临时文件，并在提示中给出文件路径
+    fn copy_to_clipboard(
+        &mut self,
+        ctx: &egui::Context,
+        text: String,
+        message: impl Into<String>,
+    ) {
+        if clipboard_available() {
+            ctx.copy_text(text);
+            self.toast = Some(Toast::new(ctx, message));
+            return;
+        }
+        let path = std::env::temp_dir().join(CLIPBOARD_FALLBACK_FILE);
+        let message = match std::fs::write(&path, text) {
+            Ok(()) => format!("剪贴板不可用，内容已写入 {}", path.display()),
+            Err(e) => format!("剪贴板不可用，写入 {} 失败: {}", path.display(), e),
+        };
+        self.toast = Some(Toast::new(ctx, message));
+    }
+
+    /// 检测连接并更新状态栏
+    fn ping(&mut self, ctx: &egui::Context) {
+        match self.executor.ping() {
+            Ok(latency) => {
+                self.status.ping_latency = Some(latency);
+                if self.status.project.is_none() {
+                    self.status.project = self.executor.project_info().ok();
+                }
+                if self.project_types.is_none() {
+                    self.project_types = self.executor.object_types().ok();
+                }
+            }
+            Err(e) => {
+                self.status.ping_latency = None;
+                self.toast = Some(Toast::new(ctx, e));
+            }
+        }
+        self.status.connection = self.executor.connection_state();
+    }
 }
 
 impl eframe::App for WaqlApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        StatusPalette::new(self.config.colorblind_palette).store(ctx);
+
+        // 插入面板（需在编辑器之前处理按键）
+        if ctx.input_mut(|i| i.consume_shortcut(&OPEN_PALETTE_SHORTCUT)) {
+            self.palette.open = true;
+        }
+        if let Some(text) = render_palette(ctx, &mut self.palette, &self.palette_entries) {
+            insert_at_cursor(ctx, &mut self.code, text);
+        }
+
+        if ctx.input_mut(|i| i.consume_shortcut(&CYCLE_THEME_SHORTCUT)) {
+            self.cycle_theme(ctx);
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&HELP_SHORTCUT)) {
+            self.show_help = !self.show_help;
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&RERUN_SHORTCUT)) {
+            self.rerun_last_query();
+        }
+        self.poll_pending_query(ctx);
+        self.poll_pending_table(ctx);
+        self.poll_watch(ctx);
+        self.poll_auto_refresh(ctx);
+        self.poll_idle(ctx);
+        self.poll_health(ctx);
+        self.render_recovery_prompt(ctx);
+        self.render_children_view(ctx);
+        self.render_popouts(ctx);
+        self.render_about(ctx);
+        render_help_window(ctx, &mut self.show_help);
+        let has_code = !self.code.trim().is_empty();
+        if let Some(condition) = render_where_builder(ctx, &mut self.where_builder, has_code) {
+            self.code = append_condition(&self.code, &condition);
+        }
+        self.render_large_query(ctx);
+        self.render_reload_config(ctx);
+        self.render_rpc(ctx);
+        self.render_inspector(ctx);
+        self.render_batch_export(ctx);
+        self.render_export_columns(ctx);
+        self.render_similar_query(ctx);
+        self.render_validate(ctx);
+        self.render_diff(ctx);
+        self.autosave_recovery(ctx);
+
+        // 拖放文件
+        let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
+        for file in &dropped_files {
+            self.load_dropped_file(ctx, file);
+        }
+
+        render_toast(ctx, &mut self.toast);
+
+        // 底部状态栏
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
+            let actions = render_status_bar(
+                ui,
+                &self.status,
+                &self.config.active_connection,
+                &self.config.recent_connections(),
+            );
+            if actions.ping {
+                self.ping(ctx);
+            }
+            if actions.reconnect {
+                self.health.trigger();
+            }
+            if let Some(name) = actions.switch_connection {
+                self.switch_connection(&name);
+            }
+        });
+
         // 底部配置面板
         if self.show_config_panel {
             egui::TopBottomPanel::bottom("config_panel")
@@ -250,10 +2105,90 @@ impl eframe::App for WaqlApp {
                             update_font_size(ctx, self.config.fontsize);
                         }
 
+                        if actions.client_config_changed {
+                            self.executor.set_client_config(self.config.client_config());
+                        }
+
+                        if let Some(name) = actions.activate_connection {
+                            self.switch_connection(&name);
+                        }
+
+                        if let Some(index) = actions.remove_connection_index {
+                            self.config.remove_connection(index);
+                            self.executor.set_client_config(self.config.client_config());
+                            let _ = self.config.save();
+                        }
+
+                        if actions.auto_fix_changed {
+                            self.executor.set_auto_fix_dollar(self.config.auto_fix_dollar);
+                        }
+
+                        if actions.batch_export {
+                            self.batch_export.open = true;
+                        }
+
+                        if actions.validate_library {
+                            self.validate.open = true;
+                        }
+
+                        if actions.health_changed {
+                            let (interval, backoff, max_delay) = self.config.health_policy();
+                            self.health.set_policy(interval, backoff, max_delay);
+                        }
+
+                        if actions.normalize_changed {
+                            self.executor.set_normalize_input(self.config.normalize_input);
+                        }
+
+                        if actions.trim_changed {
+                            self.executor.set_trim_input(self.config.trim_input);
+                        }
+
+                        if actions.idle_timeout_changed {
+                            self.executor.set_idle_timeout(self.config.idle_timeout());
+                        }
+
+                        if actions.default_return_changed {
+                            self.executor
+                                .set_default_return(self.config.default_return.clone());
+                        }
+
+                        if actions.completion_changed {
+                            self.completer.set_limits(
+                                self.config.min_completion_chars,
+                                self.config.max_suggestions,
+                            );
+                        }
+
+                        if actions.max_rows_changed {
+                            self.executor.set_max_rows(self.config.max_rows());
+                        }
+
+                        if actions.post_rules_changed {
+                            self.executor.set_post_rules(self.config.post_rules.clone());
+                        }
+
+                        if actions.offline_changed {
+                            self.executor.set_offline(self.config.offline_mode);
+                            self.reset_connection_status();
+                        }
+
                         if actions.save_config {
                             let _ = self.config.save();
                         }
 
+                        if actions.reload_config {
+                            self.show_reload_config = true;
+                        }
+
+                        if actions.export_query_set {
+                            self.export_query_set(ctx);
+                        }
+
+                        if actions.import_query_set {
+                            self.import_query_set(ctx);
+                        }
+
                         if let Some(index) = actions.remove_query_index {
                             self.config.remove_saved_query(index);
                             let _ = self.config.save();
@@ -270,6 +2205,43 @@ impl eframe::App for WaqlApp {
         // 中央主面板
         egui::CentralPanel::default().show(ctx, |ui| {
             // 代码输入编辑器
+            // 单行编辑器会丢弃粘贴内容中的换行，粘贴多行文本时先切换到多行编辑器
+            if !self.config.multiline_editor && multiline_paste(ctx) {
+                self.config.multiline_editor = true;
+                let _ = self.config.save();
+                self.toast = Some(Toast::new(ctx, "已切换到多行编辑器，保留粘贴内容中的换行"));
+            }
+            // Ctrl+Enter 需在编辑器之前处理，避免多行编辑器插入换行
+            let multiline = self.config.multiline_editor;
+            let enter_runs = self.config.enter_behavior() == EnterBehavior::Run;
+            let run_shortcut = ctx.input_mut(|i| i.consume_shortcut(&RUN_SELECTION_SHORTCUT));
+            let newline_key = multiline.then_some(if enter_runs {
+                NEWLINE_SHORTCUT
+            } else {
+                egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::Enter)
+            });
+            handle_undo_redo(ctx, &mut self.code, &mut self.edit_history);
+            if !multiline {
+                navigate_history(
+                    ctx,
+                    &mut self.code,
+                    &self.config.query_history,
+                    &mut self.history_cursor,
+                    &self.completer,
+                );
+            }
+
+            // 编辑查询时暂停自动刷新
+            let editing = ui.memory(|m| m.has_focus(editor_id()))
+                && ui.input(|i| {
+                    i.events
+                        .iter()
+                        .any(|e| matches!(e, egui::Event::Text(_) | egui::Event::Key { .. }))
+                });
+            if editing {
+                self.auto_refresh.touch(ui.input(|i| i.time));
+            }
+
             render_code_editor(
                 ui,
                 &mut self.code,
@@ -277,57 +2249,247 @@ impl eframe::App for WaqlApp {
                 &self.syntax,
                 &self.theme,
                 self.config.fontsize,
+                newline_key,
             );
 
-            // 检测回车键执行查询
-            if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-                self.execute_query();
+            // 查询检查提示，只对非空语句检查
+            let warnings: Vec<LintWarning> = split_statements(&self.code)
+                .into_iter()
+                .flat_map(|range| lint(&self.code[range], self.project_types.as_deref()))
+                .filter(|warning| !self.dismissed_lints.contains(warning))
+                .collect();
+            if let Some(index) = render_lint_warnings(ui, &warnings) {
+                self.dismissed_lints.push(warnings[index].clone());
             }
 
+            // 回车执行时检测回车键执行查询，Shift+Enter 用于换行
+            if run_shortcut && multiline {
+                self.execute_selection(ctx);
+            } else if run_shortcut
+                || (enter_runs
+                    && ui.input(|i| i.key_pressed(egui::Key::Enter) && !i.modifiers.shift))
+            {
+                self.execute_query(ctx);
+            }
+
+            // 模板菜单，以及平台和语言选项（连接后可用）
+            let template_actions = ui
+                .horizontal(|ui| {
+                    let actions = render_templates_menu(
+                        ui,
+                        &self.config.query_templates,
+                        !self.code.trim().is_empty(),
+                        &mut self.template_name,
+                    );
+                    if let Some(project) = &self.status.project {
+                        render_option_selectors(ui, &mut self.code, project);
+                    }
+                    actions
+                })
+                .inner;
+            self.handle_template_actions(template_actions);
+
             ui.separator();
 
             // 控制按钮栏
-            let has_code = !self.code.trim().is_empty();
-            let has_results = !self.result.is_empty() || self.table_data.is_some();
+            let state = ControlButtonState {
+                has_code: !self.code.trim().is_empty(),
+                has_results: !self.result.is_empty() || self.table_data.is_some(),
+                has_table_data: self.table_data.is_some(),
+                has_error: self.has_error,
+                offline: self.executor.is_offline(),
+                multiline,
+            };
             let actions = render_control_buttons(
                 ui,
-                has_code,
-                has_results,
-                self.table_data.is_some(),
+                state,
                 &mut self.show_config_panel,
                 &self.status_message,
-                self.has_error,
             );
 
             // 处理控制按钮操作
             if actions.run_query {
-                self.execute_query();
+                self.execute_query(ctx);
+            }
+
+            if actions.run_selection {
+                self.execute_selection(ctx);
             }
 
             if actions.save_query {
-                let query = self.code.trim().to_string();
-                if self.config.add_saved_query(query) {
-                    if let Err(e) = self.config.save() {
-                        self.result = format!("保存配置失败: {}", e);
-                    }
-                }
+                self.save_query(self.code.trim().to_string());
             }
 
             if actions.export_csv {
                 self.export_to_csv();
             }
 
+            if actions.run_export_reveal {
+                self.run_export_reveal(ctx);
+            }
+
+            if actions.choose_report_path {
+                self.choose_report_path();
+            }
+
+            if actions.export_json {
+                self.export_to_json();
+            }
+
+            if actions.export_ndjson {
+                self.export_to_ndjson();
+            }
+
+            #[cfg(feature = "parquet")]
+            if actions.export_parquet {
+                self.export_to_parquet();
+            }
+
+            if actions.export_columns {
+                self.export_columns.open = true;
+            }
+
+            if actions.export_schema {
+                self.export_schema();
+            }
+
+            if actions.export_stats {
+                self.export_stats();
+            }
+
+            if let Some(format) = actions.export_report {
+                self.export_report(format);
+            }
+
+            if actions.show_about {
+                self.show_about = true;
+            }
+
+            if actions.show_help {
+                self.show_help = true;
+            }
+
+            if actions.show_where_builder {
+                self.where_builder.open = true;
+            }
+
+            if actions.toggle_inspector {
+                self.show_inspector = !self.show_inspector;
+            }
+
+            if actions.show_rpc {
+                self.show_rpc = true;
+            }
+
             if actions.clear_results {
                 self.result.clear();
                 self.table_data = None;
+                self.pending_table = None;
+                self.table_view.array_keys.clear();
                 self.has_error = false;
                 self.status_message.clear();
             }
 
+            // 快照按钮栏
+            let description = self.snapshot.as_ref().map(Snapshot::description);
+            let actions =
+                render_snapshot_bar(ui, self.table_data.is_some(), description.as_deref());
+            if actions.take && let Some(table) = &self.table_data {
+                self.snapshot = Some(Snapshot {
+                    table: table.clone(),
+                    query: self.config.query_history.first().cloned().unwrap_or_default(),
+                    taken_at: Instant::now(),
+                });
+                self.toast = Some(Toast::new(ctx, "已保存快照"));
+            }
+            if actions.show_diff {
+                self.show_diff = true;
+            }
+            if actions.clear {
+                self.snapshot = None;
+                self.show_diff = false;
+            }
+
+            // 监视按钮栏
+            let columns = self.table_data.as_ref().map(|t| t.columns.clone()).unwrap_or_default();
+            let actions = render_watch_bar(ui, &mut self.watch, &columns);
+            if actions.start && !self.code.trim().is_empty() {
+                self.watch.query = self.code.trim().to_string();
+                self.watch.running = true;
+                self.watch.next_at = 0.0;
+            }
+            if actions.stop {
+                self.watch.running = false;
+                self.watch_base = None;
+            }
+            if let (true, Some(query)) = (self.auto_refresh.next_at > 0.0, &self.last_query) {
+                render_auto_refresh_status(ui, &self.auto_refresh, query);
+            }
+
             ui.separator();
 
             // 结果显示区域
-            render_results(ui, &self.result, &self.table_data, self.has_error);
+            self.table_view.null_display = self.config.null_display;
+            self.table_view.show_type_badges = self.config.show_type_badges;
+            self.table_view.json_max_depth = self.config.json_max_depth();
+            let results = ui.scope(|ui| {
+                render_results(
+                    ui,
+                    &self.result,
+                    &self.table_data,
+                    self.has_error,
+                    &mut self.table_view,
+                    &self.theme,
+                    self.config.active_cell_rules(),
+                )
+            });
+            // 在结果区域点击、拖动或滚动时暂停自动刷新
+            if results.response.contains_pointer()
+                && ui.input(|i| i.pointer.any_down() || i.raw_scroll_delta != egui::Vec2::ZERO)
+            {
+                self.auto_refresh.touch(ui.input(|i| i.time));
+            }
+            let actions = results.inner;
+            if actions.table_key_changed {
+                self.table_data = QueryExecutor::table_from_json(
+                    &self.result,
+                    &self.table_view.table_key,
+                    self.executor.max_rows(),
+                );
+                self.pending_table = None;
+            }
+            if let Some(value) = actions.copied_value {
+                self.copy_to_clipboard(ctx, value, "Copied!");
+            }
+            if let Some((list, count)) = actions.copied_list {
+                self.copy_to_clipboard(ctx, list, format!("Copied {} references", count));
+            }
+            if let (true, Some(table)) = (actions.pop_out, self.table_data.clone()) {
+                let query = self.last_query.clone().unwrap_or_default();
+                self.pop_out(query, self.result.clone(), table);
+            }
+            if actions.frozen_columns_changed {
+                self.config.frozen_columns = self.table_view.frozen_columns;
+                let _ = self.config.save();
+            }
+            if actions.column_widths_changed {
+                self.config.column_widths = self.table_view.column_widths.clone();
+                let _ = self.config.save();
+            }
+            if let Some(path) = actions.open_path {
+                self.open_cell_path(ctx, &path, false);
+            }
+            if let Some(path) = actions.reveal_path {
+                self.open_cell_path(ctx, &path, true);
+            }
+            if let Some(guid) = actions.show_children {
+                self.show_children(guid);
+            }
         });
     }
+
+    /// 正常退出时删除崩溃恢复文件
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        RecoveryData::clear();
+    }
 }
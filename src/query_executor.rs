@@ -2,19 +2,318 @@
 //! 
 //! 负责执行 WAQL 查询并处理结果
 
-use serde_json::{json, to_string_pretty, Value};
-use std::collections::HashMap;
-use waapi_rs::WaapiClient;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, to_string, to_string_pretty, Map, Value};
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::ops::Range;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// WAQL 查询执行结果
+pub use crate::report::{QueryReport, ReportFormat};
+use crate::report::escape_html;
+use crate::transport::{SampleTransport, TopicHandler, Transport, WaapiTransport};
+use crate::waapi::{
+    duplicate_keys, object_type_names, wwise_version, ClientConfig, ProjectInfo, WaapiError,
+    OBJECT_TYPES_URI, PROJECT_INFO_URI, WAQL_URI, WWISE_INFO_URI,
+};
+
+/// 超过该大小（字节）的结果不再格式化，避免大结果卡顿
+const PRETTY_PRINT_LIMIT: usize = 1024 * 1024;
+
+/// UTF-8 字节顺序标记，Windows 上的 Excel 依靠它识别 UTF-8 编码
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 超过该行数的结果在后台线程中解析表格，避免界面卡顿
+const BACKGROUND_PARSE_ROWS: usize = 5000;
+
+/// 用于检测连接的轻量查询
+const PING_QUERY: &str = "$ from type Project";
+
+/// 检查器最多保留的请求记录数
+const MAX_EXCHANGES: usize = 20;
+
+/// 请求记录的响应中每个顶层数组最多保留的元素数
+const EXCHANGE_MAX_ITEMS: usize = 100;
+
+/// 一次 WAAPI 请求和响应的记录，用于检查器
+#[derive(Debug, Clone)]
+pub struct Exchange {
+    /// 请求内容（`uri`、`args` 和 `options`）
+    pub request: Value,
+    /// 响应内容或错误信息，顶层数组只保留前 [`EXCHANGE_MAX_ITEMS`] 个元素
+    pub response: Result<Value, String>,
+    /// 请求耗时
+    pub elapsed: Duration,
+}
+
+/// 一次 WAAPI 调用的接口、参数和选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcRequest {
+    /// WAAPI 接口
+    pub uri: String,
+    /// 调用参数
+    pub args: Value,
+    /// 调用选项
+    pub options: Option<Value>,
+}
+
+impl RpcRequest {
+    /// 解析完整的 JSON-RPC 请求文本，如 `{"uri": "...", "args": {...}, "options": {...}}`
+    ///
+    /// `uri` 必须是非空字符串，`args` 省略时为空对象，`args` 和 `options` 必须是对象
+    ///
+    /// # Errors
+    ///
+    /// 文本不是合法的 JSON 或结构不符合要求时返回错误信息
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|e| format!("不是合法的 JSON: {}", e))?;
+        let Value::Object(mut object) = value else {
+            return Err("请求必须是 JSON 对象".to_string());
+        };
+        if let Some(key) = object
+            .keys()
+            .find(|key| !matches!(key.as_str(), "uri" | "args" | "options"))
+        {
+            return Err(format!("未知字段 {}，只支持 uri、args 和 options", key));
+        }
+        let uri = match object.remove("uri") {
+            Some(Value::String(uri)) if !uri.trim().is_empty() => uri.trim().to_string(),
+            _ => return Err("缺少 uri 字段，或 uri 不是字符串".to_string()),
+        };
+        let args = match object.remove("args") {
+            None => json!({}),
+            Some(args) if args.is_object() => args,
+            Some(_) => return Err("args 必须是 JSON 对象".to_string()),
+        };
+        let options = match object.remove("options") {
+            None => None,
+            Some(options) if options.is_object() => Some(options),
+            Some(_) => return Err("options 必须是 JSON 对象".to_string()),
+        };
+        Ok(Self { uri, args, options })
+    }
+
+    /// 转换为 JSON 对象，没有选项时不包含 `options`
+    pub fn to_json(&self) -> Value {
+        let mut value = json!({ "uri": self.uri, "args": self.args });
+        if let Some(options) = &self.options {
+            value["options"] = options.clone();
+        }
+        value
+    }
+}
+
+/// 连接状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ConnectionState {
+    /// 尚未发送过请求
+    #[default]
+    Unknown,
+    /// 最近一次请求成功
+    Connected,
+    /// 最近一次请求失败
+    Failed,
+    /// 离线演示模式
+    Offline,
+    /// 空闲超时后已断开，下次请求时重新连接
+    Idle,
+}
+
+/// 连接健康检查的计时
+///
+/// 连接正常时按固定间隔检测；失败后按倍数延长下一次重连的等待时间，直到上限。
+/// 时间使用调用方提供的秒数（如 egui 输入时间），便于测试
 #[derive(Debug, Clone)]
+pub struct HealthCheck {
+    /// 连接正常时的检测间隔（秒）
+    interval: f64,
+    /// 每次失败后等待时间的倍数
+    backoff: f64,
+    /// 重连等待时间上限（秒）
+    max_delay: f64,
+    /// 连续失败的次数
+    failures: u32,
+    /// 下一次检测的时间
+    next_at: f64,
+}
+
+impl HealthCheck {
+    /// 创建健康检查，第一次检测立即进行
+    pub fn new(interval: f64, backoff: f64, max_delay: f64) -> Self {
+        Self {
+            interval,
+            backoff,
+            max_delay,
+            failures: 0,
+            next_at: 0.0,
+        }
+    }
+
+    /// 更新检测间隔和退避策略，已安排的下一次检测时间不变
+    pub fn set_policy(&mut self, interval: f64, backoff: f64, max_delay: f64) {
+        self.interval = interval;
+        self.backoff = backoff;
+        self.max_delay = max_delay;
+    }
+
+    /// 按连续失败次数计算的下一次检测等待时间（秒）
+    pub fn delay(&self) -> f64 {
+        let delay = self.interval * self.backoff.max(1.0).powi(self.failures as i32);
+        delay.min(self.max_delay.max(self.interval))
+    }
+
+    /// 记录一次检测结果并安排下一次检测
+    pub fn record(&mut self, ok: bool, now: f64) {
+        self.failures = if ok { 0 } else { self.failures.saturating_add(1) };
+        self.next_at = now + self.delay();
+    }
+
+    /// 是否到了下一次检测的时间
+    pub fn is_due(&self, now: f64) -> bool {
+        now >= self.next_at
+    }
+
+    /// 距离下一次检测的时间（秒）
+    pub fn remaining(&self, now: f64) -> f64 {
+        (self.next_at - now).max(0.0)
+    }
+
+    /// 立即进行下一次检测
+    pub fn trigger(&mut self) {
+        self.next_at = 0.0;
+    }
+}
+
+/// WAQL 查询执行结果
+#[derive(Debug)]
 pub struct QueryResult {
     /// 原始 JSON 结果
     pub raw_json: String,
     /// 解析后的表格数据（列名和行数据）
     pub table_data: Option<TableData>,
+    /// 正在后台解析的表格数据（结果较大时）
+    pub pending_table: Option<PendingTable>,
     /// 结果数量
     pub count: usize,
+    /// 查询耗时
+    pub elapsed: Duration,
+    /// 自动补全 `$` 后实际发送的查询语句
+    pub fixed_query: Option<String>,
+    /// 结果中值为数组的顶层键
+    pub array_keys: Vec<String>,
+    /// 用于生成表格的顶层键
+    pub table_key: String,
+    /// 结果形态，用于区分空结果和非表格结果
+    pub kind: ResultKind,
+    /// 响应中在同一个对象内重复出现的键，表格中只保留最后一个值
+    pub duplicate_keys: Vec<String>,
+}
+
+/// 查询结果的形态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultKind {
+    /// 对象列表，可以显示为表格
+    Table,
+    /// 对象列表为空
+    EmptyTable,
+    /// 结果不是对象列表，只能显示原始 JSON
+    NonTabular,
+}
+
+impl ResultKind {
+    /// 根据用于生成表格的顶层键判断结果形态
+    ///
+    /// 键不存在或不是数组时视为非表格结果，数组中不是对象的元素显示在 [`VALUE_COLUMN`] 列
+    fn of(result: &Value, key: &str) -> Self {
+        match result.get(key).and_then(Value::as_array) {
+            Some(array) if array.is_empty() => ResultKind::EmptyTable,
+            Some(_) => ResultKind::Table,
+            None => ResultKind::NonTabular,
+        }
+    }
+}
+
+/// 正在后台线程中解析的表格数据
+#[derive(Debug)]
+pub struct PendingTable {
+    /// 待解析的行数
+    pub rows: usize,
+    receiver: Receiver<Option<TableData>>,
+}
+
+impl PendingTable {
+    /// 检查后台解析是否完成
+    ///
+    /// # Returns
+    ///
+    /// 仍在解析时返回 `None`，完成后返回解析结果
+    pub fn poll(&self) -> Option<Option<TableData>> {
+        match self.receiver.try_recv() {
+            Ok(table) => Some(table),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(None),
+        }
+    }
+}
+
+/// 正在后台线程中执行的查询，见 [`QueryExecutor::spawn_execute`]
+#[derive(Debug)]
+pub struct PendingQuery {
+    receiver: Receiver<QueryOutcome>,
+}
+
+impl PendingQuery {
+    /// 检查后台查询是否完成
+    ///
+    /// # Returns
+    ///
+    /// 仍在执行时返回 `None`，完成后返回结果，交给 [`QueryExecutor::finish_query`] 处理
+    pub fn poll(&self) -> Option<QueryOutcome> {
+        match self.receiver.try_recv() {
+            Ok(outcome) => Some(outcome),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(QueryOutcome::terminated()),
+        }
+    }
+
+    /// 等待后台查询完成
+    pub fn wait(self) -> QueryOutcome {
+        self.receiver.recv().unwrap_or_else(|_| QueryOutcome::terminated())
+    }
+}
+
+/// 后台连接检测的结果
+#[derive(Debug)]
+pub struct PingOutcome {
+    /// 检测的延迟或错误信息
+    pub latency: Result<Duration, String>,
+    /// 连接正常且请求了项目信息时获取到的项目信息
+    pub project: Option<ProjectInfo>,
+    /// 连接正常且请求了对象类型时获取到的类型名称
+    pub object_types: Option<Vec<String>>,
+}
+
+/// 后台查询的结果，以及执行期间的请求记录和连接状态
+#[derive(Debug)]
+pub struct QueryOutcome {
+    result: Result<QueryResult, String>,
+    exchanges: VecDeque<Exchange>,
+    connection_state: ConnectionState,
+}
+
+impl QueryOutcome {
+    /// 后台线程没有返回结果就退出时的结果
+    fn terminated() -> Self {
+        Self {
+            result: Err("后台查询意外终止".to_string()),
+            exchanges: VecDeque::new(),
+            connection_state: ConnectionState::Unknown,
+        }
+    }
 }
 
 /// 表格数据结构
@@ -24,195 +323,3452 @@ pub struct TableData {
     pub columns: Vec<String>,
     /// 行数据列表
     pub rows: Vec<HashMap<String, String>>,
+    /// 保留原始 JSON 类型的行数据，与 `rows` 一一对应（缺失的键不包含在内）
+    pub typed_rows: Vec<HashMap<String, Value>>,
+    /// 截断前的总行数，超过最大行数限制时大于 `rows.len()`
+    pub total_rows: usize,
 }
 
-impl TableData {
-    /// 导出为 CSV 格式
-    /// 
-    /// # Errors
-    /// 
-    /// 如果写入 CSV 失败，返回错误
-    pub fn export_to_csv(&self, path: &std::path::Path) -> Result<(), Box<dyn std::error::Error>> {
-        let mut writer = csv::Writer::from_path(path)?;
+/// CSV 分隔符
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CsvDelimiter {
+    /// 逗号
+    #[default]
+    Comma,
+    /// 分号（部分欧洲地区的 Excel 默认使用）
+    Semicolon,
+    /// 制表符
+    Tab,
+}
 
-        // 写入表头
-        writer.write_record(&self.columns)?;
+impl CsvDelimiter {
+    /// 所有分隔符
+    pub const ALL: [CsvDelimiter; 3] = [
+        CsvDelimiter::Comma,
+        CsvDelimiter::Semicolon,
+        CsvDelimiter::Tab,
+    ];
 
-        // 写入数据行
-        for row in &self.rows {
-            let record: Vec<&str> = self
-                .columns
-                .iter()
-                .map(|col| row.get(col).map(|s| s.as_str()).unwrap_or(""))
-                .collect();
-            writer.write_record(&record)?;
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            CsvDelimiter::Comma => "Comma (,)",
+            CsvDelimiter::Semicolon => "Semicolon (;)",
+            CsvDelimiter::Tab => "Tab",
         }
+    }
 
-        writer.flush()?;
-        Ok(())
+    /// 分隔符字节
+    fn byte(&self) -> u8 {
+        match self {
+            CsvDelimiter::Comma => b',',
+            CsvDelimiter::Semicolon => b';',
+            CsvDelimiter::Tab => b'\t',
+        }
     }
 }
 
-/// WAQL 查询执行器
-pub struct QueryExecutor {
-    client: WaapiClient,
+/// CSV 引号风格
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CsvQuoting {
+    /// 只在需要时（包含分隔符、引号或换行）加引号
+    #[default]
+    Necessary,
+    /// 所有单元格都加引号
+    Always,
+    /// 不是数字的单元格加引号
+    NonNumeric,
 }
 
-impl Default for QueryExecutor {
-    fn default() -> Self {
-        Self::new()
+impl CsvQuoting {
+    /// 所有引号风格
+    pub const ALL: [CsvQuoting; 3] = [
+        CsvQuoting::Necessary,
+        CsvQuoting::Always,
+        CsvQuoting::NonNumeric,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            CsvQuoting::Necessary => "When needed",
+            CsvQuoting::Always => "Always",
+            CsvQuoting::NonNumeric => "Non-numeric",
+        }
+    }
+
+    /// 对应的 `csv` 引号风格
+    fn quote_style(&self) -> csv::QuoteStyle {
+        match self {
+            CsvQuoting::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoting::Always => csv::QuoteStyle::Always,
+            CsvQuoting::NonNumeric => csv::QuoteStyle::NonNumeric,
+        }
     }
 }
 
-impl QueryExecutor {
-    /// 创建新的查询执行器
-    pub fn new() -> Self {
-        Self {
-            client: WaapiClient::default(),
+/// CSV 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CsvFormat {
+    /// 分隔符
+    pub delimiter: CsvDelimiter,
+    /// 引号风格
+    pub quoting: CsvQuoting,
+    /// 是否在开头写入 UTF-8 BOM
+    pub bom: bool,
+}
+
+/// 批量导出的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExportFormat {
+    /// CSV，使用设置中的分隔符和引号风格
+    #[default]
+    Csv,
+    /// 制表符分隔
+    Tsv,
+    /// JSON 对象数组
+    Json,
+    /// 每行一个 JSON 对象（NDJSON）
+    Ndjson,
+    /// Apache Parquet，需要启用 `parquet` 特性
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    /// 所有格式
+    pub const ALL: &[ExportFormat] = &[
+        ExportFormat::Csv,
+        ExportFormat::Tsv,
+        ExportFormat::Json,
+        ExportFormat::Ndjson,
+        #[cfg(feature = "parquet")]
+        ExportFormat::Parquet,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Tsv => "TSV",
+            ExportFormat::Json => "JSON",
+            ExportFormat::Ndjson => "NDJSON",
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => "Parquet",
         }
     }
 
-    /// 执行 WAQL 查询
-    /// 
-    /// # Arguments
-    /// 
-    /// * `code` - WAQL 查询语句，可以包含 options（用 | 分隔）
-    /// 
-    /// # Returns
-    /// 
-    /// 返回查询结果或错误信息
-    pub fn execute(&mut self, code: &str) -> Result<QueryResult, String> {
-        let code = code.trim();
-        
-        if code.is_empty() {
-            return Err("请输入 WAQL 查询语句".to_string());
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => "parquet",
+        }
+    }
+}
+
+/// 将查询名称转换为可用的文件名（不含扩展名）
+///
+/// 文件名中不允许的字符替换为 `_`，去掉首尾的空白和末尾的 `.`，结果为空时使用 `query`
+pub fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            let reserved = matches!(c, '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*');
+            if c.is_control() || reserved { '_' } else { c }
+        })
+        .collect();
+    let name = name.trim().trim_end_matches('.').trim_end();
+    if name.is_empty() {
+        "query".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// 推断出的列类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnType {
+    /// 文本，类型不一致或没有值时也使用该类型
+    String,
+    /// 数值
+    Number,
+    /// 布尔值
+    Bool,
+    /// Wwise 对象 GUID
+    Guid,
+}
+
+impl ColumnType {
+    /// 表头中显示的简短类型标记
+    pub fn badge(&self) -> &'static str {
+        match self {
+            ColumnType::String => "str",
+            ColumnType::Number => "num",
+            ColumnType::Bool => "bool",
+            ColumnType::Guid => "guid",
         }
+    }
+}
 
-        let (query, options) = self.parse_query(code);
+/// 描述一列的名称和推断出的类型
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ColumnSchema {
+    /// 列名
+    pub name: String,
+    /// 推断出的类型
+    #[serde(rename = "type")]
+    pub kind: ColumnType,
+}
 
-        match self.client.waql_query(query, options) {
-            Ok(result) => {
-                // 将 Map 转换为 Value
-                let result_value = Value::Object(result);
-                
-                let raw_json = to_string_pretty(&result_value)
-                    .unwrap_or_else(|_| "格式化结果失败".to_string());
+/// 结果后处理规则的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PostOp {
+    /// 将 `column` 重命名为 `target`
+    #[default]
+    Rename,
+    /// 删除 `column`
+    Drop,
+    /// 将 `column` 和 `other` 以 `separator` 连接，写入 `target` 列
+    Concat,
+}
 
-                let table_data = Self::parse_table_data(&result_value);
-                let count = table_data.as_ref().map(|t| t.rows.len()).unwrap_or(0);
+impl PostOp {
+    /// 所有操作
+    pub const ALL: [PostOp; 3] = [PostOp::Rename, PostOp::Drop, PostOp::Concat];
 
-                Ok(QueryResult {
-                    raw_json,
-                    table_data,
-                    count,
-                })
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            PostOp::Rename => "rename",
+            PostOp::Drop => "drop",
+            PostOp::Concat => "concat",
+        }
+    }
+}
+
+/// 结果后处理规则，在结果显示前按顺序对表格生效
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostRule {
+    /// 操作
+    pub op: PostOp,
+    /// 操作的列
+    pub column: String,
+    /// 连接时的第二列
+    pub other: String,
+    /// 连接时的分隔符
+    pub separator: String,
+    /// 重命名后的列名或连接结果写入的列
+    pub target: String,
+}
+
+impl PostRule {
+    /// 检查规则是否有效
+    ///
+    /// # Errors
+    ///
+    /// 缺少操作需要的列名时返回原因
+    pub fn validate(&self) -> Result<(), String> {
+        if self.column.trim().is_empty() {
+            return Err("column is empty".to_string());
+        }
+        if self.op == PostOp::Concat && self.other.trim().is_empty() {
+            return Err("second column is empty".to_string());
+        }
+        if self.op != PostOp::Drop && self.target.trim().is_empty() {
+            return Err("target column is empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// 对表格应用规则，表格中没有 `column` 时不做修改
+    fn apply(&self, data: &mut TableData) {
+        let column = self.column.trim();
+        let target = self.target.trim();
+        if !data.columns.iter().any(|c| c == column) {
+            return;
+        }
+        match self.op {
+            PostOp::Rename if column == target => {}
+            PostOp::Rename => {
+                data.columns.retain(|c| c != target);
+                for name in &mut data.columns {
+                    if name == column {
+                        *name = target.to_string();
+                    }
+                }
+                for row in &mut data.rows {
+                    if let Some(value) = row.remove(column) {
+                        row.insert(target.to_string(), value);
+                    }
+                }
+                for row in &mut data.typed_rows {
+                    if let Some(value) = row.remove(column) {
+                        row.insert(target.to_string(), value);
+                    }
+                }
+            }
+            PostOp::Drop => {
+                data.columns.retain(|c| c != column);
+                data.rows.iter_mut().for_each(|row| {
+                    row.remove(column);
+                });
+                data.typed_rows.iter_mut().for_each(|row| {
+                    row.remove(column);
+                });
+            }
+            PostOp::Concat => {
+                let other = self.other.trim();
+                for (i, row) in data.rows.iter_mut().enumerate() {
+                    let cell = |name: &str| row.get(name).map(String::as_str).unwrap_or("");
+                    let value = format!("{}{}{}", cell(column), self.separator, cell(other));
+                    if let Some(typed) = data.typed_rows.get_mut(i) {
+                        typed.insert(target.to_string(), Value::String(value.clone()));
+                    }
+                    row.insert(target.to_string(), value);
+                }
+                if !data.columns.iter().any(|c| c == target) {
+                    data.columns.push(target.to_string());
+                }
             }
-            Err(e) => Err(format!("查询失败: {}", e)),
         }
     }
+}
 
-    /// 解析 WAQL 查询语句和选项
-    /// 
-    /// 如果查询语句包含 `|`，则分割为查询部分和选项部分
-    fn parse_query<'a>(&self, code: &'a str) -> (&'a str, Option<Value>) {
-        if let Some((query_part, options_part)) = code.split_once('|') {
-            let query = query_part.trim();
-            let options_str = options_part.trim();
-            
-            let options = if options_str.is_empty() {
-                None
-            } else {
-                Some(json!({
-                    "return": options_str
-                        .split_whitespace()
-                        .collect::<Vec<&str>>()
-                }))
-            };
-            
-            (query, options)
-        } else {
-            (code, None)
+/// 按顺序对表格应用后处理规则，无效的规则被忽略
+pub fn postprocess(mut data: TableData, rules: &[PostRule]) -> TableData {
+    for rule in rules.iter().filter(|rule| rule.validate().is_ok()) {
+        rule.apply(&mut data);
+    }
+    data
+}
+
+/// 当前结果与快照相比发生变化的行
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RowDiff {
+    /// 新增的行（当前结果中的索引）
+    Added(usize),
+    /// 删除的行（快照中的索引）
+    Removed(usize),
+    /// 值发生变化的行
+    Changed {
+        /// 当前结果中的索引
+        index: usize,
+        /// 快照中的索引
+        snapshot_index: usize,
+        /// 值不同的列
+        columns: Vec<String>,
+    },
+}
+
+/// 列聚合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Aggregate {
+    /// 求和
+    #[default]
+    Sum,
+    /// 平均值
+    Avg,
+    /// 最小值
+    Min,
+    /// 最大值
+    Max,
+    /// 数值个数
+    Count,
+}
+
+impl Aggregate {
+    /// 所有聚合方式
+    pub const ALL: [Aggregate; 5] = [
+        Aggregate::Sum,
+        Aggregate::Avg,
+        Aggregate::Min,
+        Aggregate::Max,
+        Aggregate::Count,
+    ];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            Aggregate::Sum => "sum",
+            Aggregate::Avg => "avg",
+            Aggregate::Min => "min",
+            Aggregate::Max => "max",
+            Aggregate::Count => "count",
         }
     }
+}
 
-    /// 从 JSON 结果中解析表格数据
-    fn parse_table_data(result: &Value) -> Option<TableData> {
-        let return_array = result.get("return")?.as_array()?;
+/// 单列的统计信息，见 [`TableData::compute_stats`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    /// 列名
+    pub column: String,
+    /// 有值的单元格数量（不包括空值和 null）
+    pub count: usize,
+    /// 不重复的值的数量
+    pub distinct: usize,
+    /// 最小值，只用于数值列
+    pub min: Option<f64>,
+    /// 最大值，只用于数值列
+    pub max: Option<f64>,
+    /// 平均值，只用于数值列
+    pub avg: Option<f64>,
+}
 
-        if return_array.is_empty() {
+impl TableData {
+    /// 获取匹配筛选文本的行索引
+    ///
+    /// 任意列包含筛选文本（忽略大小写）即视为匹配，筛选文本为空时返回所有行
+    pub fn filtered_indices(&self, filter: &str) -> Vec<usize> {
+        let filter = filter.trim().to_lowercase();
+        if filter.is_empty() {
+            return (0..self.rows.len()).collect();
+        }
+        self.rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| row.values().any(|v| v.to_lowercase().contains(&filter)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// 判断列是否为数值列
+    ///
+    /// 列中至少有一个数值，且除 null 外的所有值都是数值
+    pub fn is_numeric_column(&self, column: &str) -> bool {
+        let mut has_number = false;
+        for row in &self.typed_rows {
+            match row.get(column) {
+                Some(Value::Number(_)) => has_number = true,
+                Some(Value::Null) | None => {}
+                Some(_) => return false,
+            }
+        }
+        has_number
+    }
+
+    /// 计算指定行中某一列的数值聚合结果
+    ///
+    /// 只统计数值类型的单元格，没有数值时返回 `None`（`Count` 返回 0）
+    pub fn aggregate(&self, column: &str, indices: &[usize], aggregate: Aggregate) -> Option<f64> {
+        let values: Vec<f64> = indices
+            .iter()
+            .filter_map(|&i| self.typed_rows.get(i)?.get(column)?.as_f64())
+            .collect();
+
+        if aggregate == Aggregate::Count {
+            return Some(values.len() as f64);
+        }
+        if values.is_empty() {
             return None;
         }
 
-        // 提取所有可能的列名（从所有对象的键中收集）
-        let mut columns = Vec::new();
-        let mut columns_set = std::collections::HashSet::new();
+        let sum: f64 = values.iter().sum();
+        Some(match aggregate {
+            Aggregate::Sum => sum,
+            Aggregate::Avg => sum / values.len() as f64,
+            Aggregate::Min => values.iter().copied().fold(f64::INFINITY, f64::min),
+            Aggregate::Max => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            Aggregate::Count => unreachable!(),
+        })
+    }
 
-        for item in return_array {
-            if let Some(obj) = item.as_object() {
-                for key in obj.keys() {
-                    if columns_set.insert(key.clone()) {
-                        columns.push(key.clone());
-                    }
+    /// 按某一列的值将指定行分组
+    ///
+    /// 组按值首次出现的顺序排列，组内保持 `indices` 中的顺序
+    pub fn group_indices(&self, column: &str, indices: &[usize]) -> Vec<(String, Vec<usize>)> {
+        let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+        let mut positions: HashMap<&str, usize> = HashMap::new();
+        for &i in indices {
+            let value = self
+                .rows
+                .get(i)
+                .and_then(|row| row.get(column))
+                .map_or("", String::as_str);
+            match positions.get(value) {
+                Some(&position) => groups[position].1.push(i),
+                None => {
+                    positions.insert(value, groups.len());
+                    groups.push((value.to_string(), vec![i]));
                 }
             }
         }
+        groups
+    }
 
-        // 转换数据行
-        let mut rows = Vec::new();
-        for item in return_array {
-            if let Some(obj) = item.as_object() {
-                let mut row = HashMap::new();
-                for col in &columns {
-                    let value = obj
-                        .get(col)
-                        .map(|v| Self::value_to_string(v))
-                        .unwrap_or_default();
-                    row.insert(col.clone(), value);
-                }
-                rows.push(row);
+    /// 计算每一列的统计信息，按列的顺序排列，见 [`TableData::column_stats`]
+    pub fn compute_stats(&self) -> Vec<ColumnStats> {
+        self.columns.iter().map(|column| self.column_stats(column)).collect()
+    }
+
+    /// 计算某一列的统计信息
+    ///
+    /// 统计数量和不重复值数量，只有数值列（见 [`TableData::is_numeric_column`]）
+    /// 计算最小值、最大值和平均值
+    pub fn column_stats(&self, column: &str) -> ColumnStats {
+        let values = self.present_values(column);
+        let distinct = values.iter().collect::<HashSet<_>>().len();
+        let numeric = self.is_numeric_column(column);
+        let all: Vec<usize> = (0..self.rows.len()).collect();
+        let stat = |aggregate| {
+            if numeric {
+                self.aggregate(column, &all, aggregate)
+            } else {
+                None
             }
+        };
+        ColumnStats {
+            column: column.to_string(),
+            count: values.len(),
+            distinct,
+            min: stat(Aggregate::Min),
+            max: stat(Aggregate::Max),
+            avg: stat(Aggregate::Avg),
         }
+    }
 
-        Some(TableData { columns, rows })
+    /// 某一列中出现次数最多的 `limit` 个值和出现次数
+    ///
+    /// 按次数从多到少排列，次数相同时按值排序。空值和 null 不参与统计
+    pub fn top_values(&self, column: &str, limit: usize) -> Vec<(String, usize)> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for value in self.present_values(column) {
+            *counts.entry(value).or_default() += 1;
+        }
+        let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        counts
+            .into_iter()
+            .take(limit)
+            .map(|(value, count)| (value.to_string(), count))
+            .collect()
     }
 
-    /// 将 JSON Value 转换为字符串
-    fn value_to_string(value: &Value) -> String {
-        match value {
-            Value::String(s) => s.clone(),
-            Value::Number(n) => n.to_string(),
-            Value::Bool(b) => b.to_string(),
-            Value::Null => "null".to_string(),
-            _ => serde_json::to_string(value).unwrap_or_default(),
+    /// 某一列中有值的单元格（不包括空值和 null）的显示文本
+    fn present_values(&self, column: &str) -> Vec<&str> {
+        self.rows
+            .iter()
+            .zip(&self.typed_rows)
+            .filter(|(_, typed)| !matches!(typed.get(column), None | Some(Value::Null)))
+            .filter_map(|(row, _)| row.get(column).map(String::as_str))
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    /// 将列统计信息以 CSV 格式写入，每列一行，非数值列的数值统计留空
+    ///
+    /// # Errors
+    ///
+    /// 如果写入失败，返回错误
+    pub fn write_stats_csv<W: Write>(
+        &self,
+        mut output: W,
+        format: CsvFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if format.bom {
+            output.write_all(UTF8_BOM)?;
         }
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(format.delimiter.byte())
+            .quote_style(format.quoting.quote_style())
+            .from_writer(output);
+        writer.write_record(["column", "count", "min", "max", "avg", "distinct"])?;
+        let number = |value: Option<f64>| value.map(|v| v.to_string()).unwrap_or_default();
+        for stats in self.compute_stats() {
+            writer.write_record([
+                stats.column,
+                stats.count.to_string(),
+                number(stats.min),
+                number(stats.max),
+                number(stats.avg),
+                stats.distinct.to_string(),
+            ])?;
+        }
+        writer.flush()?;
+        Ok(())
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// 导出列统计信息到 CSV 文件，见 [`TableData::write_stats_csv`]
+    ///
+    /// # Errors
+    ///
+    /// 如果写入文件失败，返回错误
+    pub fn export_stats(
+        &self,
+        path: &std::path::Path,
+        format: CsvFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::File::create(path)?;
+        self.write_stats_csv(file, format)
+    }
 
-    #[test]
-    fn test_parse_query_without_options() {
-        let executor = QueryExecutor::new();
-        let (query, options) = executor.parse_query("$ from type Sound");
-        assert_eq!(query, "$ from type Sound");
-        assert!(options.is_none());
+    /// 统计指定行中某一列的非空单元格数量
+    pub fn non_empty_count(&self, column: &str, indices: &[usize]) -> usize {
+        indices
+            .iter()
+            .filter(|&&i| {
+                self.rows
+                    .get(i)
+                    .and_then(|row| row.get(column))
+                    .is_some_and(|v| !v.is_empty())
+            })
+            .count()
     }
 
-    #[test]
-    fn test_parse_query_with_options() {
-        let executor = QueryExecutor::new();
-        let (query, options) = executor.parse_query("$ from type Sound | name id");
-        assert_eq!(query, "$ from type Sound");
-        assert!(options.is_some());
+    /// 将指定的列移到最前面，按 `fields` 的顺序排列，其余列保持原有顺序
+    ///
+    /// 列名与字段相同，或忽略 `@` 前缀后相同时视为匹配
+    pub fn order_columns(&mut self, fields: &[String]) {
+        let matches = |column: &str, field: &str| {
+            column == field || column.trim_start_matches('@') == field.trim_start_matches('@')
+        };
+        let mut ordered = Vec::with_capacity(self.columns.len());
+        for field in fields {
+            if let Some(index) = self.columns.iter().position(|c| matches(c, field)) {
+                ordered.push(self.columns.remove(index));
+            }
+        }
+        ordered.append(&mut self.columns);
+        self.columns = ordered;
     }
 
-    #[test]
-    fn test_value_to_string() {
-        assert_eq!(QueryExecutor::value_to_string(&json!("test")), "test");
-        assert_eq!(QueryExecutor::value_to_string(&json!(42)), "42");
-        assert_eq!(QueryExecutor::value_to_string(&json!(true)), "true");
-        assert_eq!(QueryExecutor::value_to_string(&json!(null)), "null");
+    /// 导出为 CSV 格式
+    ///
+    /// `format` 和 `safe` 含义同 [`TableData::write_csv`]
+    /// 
+    /// # Errors
+    /// 
+    /// 如果写入 CSV 失败，返回错误
+    pub fn export_to_csv(
+        &self,
+        path: &std::path::Path,
+        format: CsvFormat,
+        safe: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let file = std::fs::File::create(path)?;
+        self.write_csv(file, format, safe)
+    }
+
+    /// 导出为 NDJSON 格式，每行一个 JSON 对象，空表格写入空文件
+    ///
+    /// # Errors
+    ///
+    /// 如果写入文件失败，返回错误
+    pub fn export_to_ndjson(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        Ok(std::fs::write(path, self.to_ndjson_string())?)
+    }
+
+    /// 按指定格式导出到文件
+    ///
+    /// `csv` 为 CSV 格式使用的分隔符和引号风格，TSV 格式只使用其中的引号风格和 BOM 设置
+    ///
+    /// # Errors
+    ///
+    /// 如果写入失败，返回错误
+    pub fn export(
+        &self,
+        path: &std::path::Path,
+        format: ExportFormat,
+        csv: CsvFormat,
+        safe: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match format {
+            ExportFormat::Csv => self.export_to_csv(path, csv, safe),
+            ExportFormat::Tsv => {
+                let tsv = CsvFormat {
+                    delimiter: CsvDelimiter::Tab,
+                    ..csv
+                };
+                self.export_to_csv(path, tsv, safe)
+            }
+            ExportFormat::Json => Ok(std::fs::write(path, self.to_json_string())?),
+            ExportFormat::Ndjson => self.export_to_ndjson(path),
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => self.export_to_parquet(path),
+        }
+    }
+
+    /// 导出为 Parquet 格式
+    ///
+    /// 列类型见 [`TableData::infer_schema`]：数值列写为 `Float64`，布尔列写为 `Boolean`，
+    /// 其他列（包括类型不一致的列）写为 UTF8 文本。缺失值和 null 写为 null
+    ///
+    /// # Errors
+    ///
+    /// 如果表格没有列或写入文件失败，返回错误
+    #[cfg(feature = "parquet")]
+    pub fn export_to_parquet(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use arrow::array::{ArrayRef, BooleanArray, Float64Array, StringArray};
+        use arrow::datatypes::{DataType, Field, Schema};
+        use arrow::record_batch::RecordBatch;
+        use parquet::arrow::ArrowWriter;
+        use std::sync::Arc;
+
+        let schema = self.infer_schema();
+        let typed = |index: usize, column: &str| {
+            self.typed_rows
+                .get(index)
+                .and_then(|row| row.get(column))
+                .filter(|value| !value.is_null())
+        };
+        let mut fields = Vec::with_capacity(schema.len());
+        let mut arrays: Vec<ArrayRef> = Vec::with_capacity(schema.len());
+        for column in &schema {
+            let name = column.name.as_str();
+            let rows = 0..self.rows.len();
+            let (data_type, array): (DataType, ArrayRef) = match column.kind {
+                ColumnType::Number => (
+                    DataType::Float64,
+                    Arc::new(
+                        rows.map(|i| typed(i, name).and_then(Value::as_f64))
+                            .collect::<Float64Array>(),
+                    ),
+                ),
+                ColumnType::Bool => (
+                    DataType::Boolean,
+                    Arc::new(
+                        rows.map(|i| typed(i, name).and_then(Value::as_bool))
+                            .collect::<BooleanArray>(),
+                    ),
+                ),
+                ColumnType::String | ColumnType::Guid => (
+                    DataType::Utf8,
+                    Arc::new(
+                        rows.map(|i| {
+                            let null = self.typed_rows.get(i).is_some() && typed(i, name).is_none();
+                            let cell = self.rows[i].get(name).map(String::as_str);
+                            if null { None } else { cell }
+                        })
+                        .collect::<StringArray>(),
+                    ),
+                ),
+            };
+            fields.push(Field::new(name, data_type, true));
+            arrays.push(array);
+        }
+
+        let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?;
+        let file = std::fs::File::create(path)?;
+        let mut writer = ArrowWriter::try_new(file, batch.schema(), None)?;
+        writer.write(&batch)?;
+        writer.close()?;
+        Ok(())
+    }
+
+    /// 只保留指定索引的行，用于导出筛选后的结果
+    pub fn filtered(&self, indices: &[usize]) -> TableData {
+        let rows: Vec<_> = indices.iter().filter_map(|&i| self.rows.get(i).cloned()).collect();
+        TableData {
+            columns: self.columns.clone(),
+            total_rows: rows.len(),
+            rows,
+            typed_rows: indices
+                .iter()
+                .filter_map(|&i| self.typed_rows.get(i).cloned())
+                .collect(),
+        }
+    }
+
+    /// 只保留指定的列并按给定的顺序排列，用于按自定义列导出，表格中没有的列被忽略
+    pub fn select_columns(&self, columns: &[String]) -> TableData {
+        let columns: Vec<String> =
+            columns.iter().filter(|c| self.columns.contains(c)).cloned().collect();
+        TableData {
+            rows: self
+                .rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .filter(|(k, _)| columns.contains(k))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect()
+                })
+                .collect(),
+            typed_rows: self
+                .typed_rows
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .filter(|(k, _)| columns.contains(k))
+                        .map(|(k, v)| (k.clone(), v.clone()))
+                        .collect()
+                })
+                .collect(),
+            columns,
+            total_rows: self.total_rows,
+        }
+    }
+
+    /// 获取指定行对应的 Wwise 对象 GUID
+    ///
+    /// 优先使用 `id` 列，没有时使用按列顺序第一个 GUID 格式的单元格
+    pub fn row_guid(&self, index: usize) -> Option<&str> {
+        let row = self.rows.get(index)?;
+        std::iter::once("id")
+            .chain(self.columns.iter().map(String::as_str))
+            .filter_map(|col| row.get(col))
+            .map(String::as_str)
+            .find(|value| is_guid(value))
+    }
+
+    /// 行的比较键：对象 GUID，没有 GUID 时使用行号
+    fn row_key(&self, index: usize) -> String {
+        self.row_guid(index)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("#{}", index))
+    }
+
+    /// 与快照比较，返回发生变化的行
+    ///
+    /// 按对象 GUID 对应两边的行，没有 GUID 时按行号对应。
+    /// 先按当前顺序列出新增和变化的行，再列出快照中被删除的行
+    pub fn diff(&self, snapshot: &TableData) -> Vec<RowDiff> {
+        let snapshot_keys: HashMap<String, usize> = (0..snapshot.rows.len())
+            .map(|i| (snapshot.row_key(i), i))
+            .collect();
+        let mut columns: Vec<&String> = self.columns.iter().collect();
+        columns.extend(snapshot.columns.iter().filter(|c| !self.columns.contains(c)));
+
+        fn cell<'a>(row: &'a HashMap<String, String>, column: &str) -> &'a str {
+            row.get(column).map(String::as_str).unwrap_or("")
+        }
+
+        let mut matched = vec![false; snapshot.rows.len()];
+        let mut diff = Vec::new();
+        for index in 0..self.rows.len() {
+            let Some(&snapshot_index) = snapshot_keys.get(&self.row_key(index)) else {
+                diff.push(RowDiff::Added(index));
+                continue;
+            };
+            matched[snapshot_index] = true;
+            let row = &self.rows[index];
+            let snapshot_row = &snapshot.rows[snapshot_index];
+            let changed: Vec<String> = columns
+                .iter()
+                .filter(|c| cell(row, c) != cell(snapshot_row, c))
+                .map(|c| c.to_string())
+                .collect();
+            if !changed.is_empty() {
+                diff.push(RowDiff::Changed {
+                    index,
+                    snapshot_index,
+                    columns: changed,
+                });
+            }
+        }
+        diff.extend(
+            matched
+                .iter()
+                .enumerate()
+                .filter(|(_, matched)| !**matched)
+                .map(|(i, _)| RowDiff::Removed(i)),
+        );
+        diff
+    }
+
+    /// 按键列将新结果合并到当前表格，返回值发生变化的单元格（行索引和列名）
+    ///
+    /// 键值相同的行就地更新，键值不存在或为空的行追加到末尾（所有单元格都视为变化）。
+    /// 新结果中没有出现的行保持不变，已有行的顺序和索引不会改变
+    pub fn upsert(&mut self, update: TableData, key: &str) -> Vec<(usize, String)> {
+        let mut positions: HashMap<String, usize> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter_map(|(i, row)| Some((row.get(key).filter(|v| !v.is_empty())?.clone(), i)))
+            .collect();
+        for column in &update.columns {
+            if !self.columns.contains(column) {
+                self.columns.push(column.clone());
+            }
+        }
+
+        let mut changed = Vec::new();
+        for (row, typed) in update.rows.into_iter().zip(update.typed_rows) {
+            let existing = row
+                .get(key)
+                .filter(|v| !v.is_empty())
+                .and_then(|v| positions.get(v).copied());
+            let Some(index) = existing else {
+                let index = self.rows.len();
+                if let Some(value) = row.get(key).filter(|v| !v.is_empty()) {
+                    positions.insert(value.clone(), index);
+                }
+                changed.extend(row.keys().map(|column| (index, column.clone())));
+                self.rows.push(row);
+                self.typed_rows.push(typed);
+                continue;
+            };
+            let old = &self.rows[index];
+            changed.extend(
+                update
+                    .columns
+                    .iter()
+                    .filter(|c| old.get(*c) != row.get(*c))
+                    .map(|c| (index, c.clone())),
+            );
+            self.rows[index] = row;
+            self.typed_rows[index] = typed;
+        }
+        self.total_rows = self.rows.len();
+        changed
+    }
+
+    /// 根据所有行的值推断每一列的类型
+    ///
+    /// 忽略缺失值和 null，所有值类型一致时使用该类型，否则使用 [`ColumnType::String`]。
+    /// 全部为 GUID 的文本列推断为 [`ColumnType::Guid`]
+    pub fn infer_schema(&self) -> Vec<ColumnSchema> {
+        let value_type = |value: &Value| match value {
+            Value::Number(_) => Some(ColumnType::Number),
+            Value::Bool(_) => Some(ColumnType::Bool),
+            Value::String(s) if is_guid(s) => Some(ColumnType::Guid),
+            Value::Null => None,
+            _ => Some(ColumnType::String),
+        };
+
+        self.columns
+            .iter()
+            .map(|column| {
+                let mut types = self
+                    .typed_rows
+                    .iter()
+                    .filter_map(|row| row.get(column))
+                    .filter_map(value_type);
+                let first = types.next();
+                let kind = match first {
+                    Some(kind) if types.all(|t| t == kind) => kind,
+                    _ => ColumnType::String,
+                };
+                ColumnSchema {
+                    name: column.clone(),
+                    kind,
+                }
+            })
+            .collect()
+    }
+
+    /// 是否因最大行数限制被截断
+    pub fn is_truncated(&self) -> bool {
+        self.total_rows > self.rows.len()
+    }
+
+    /// 将推断出的列类型转换为 JSON 文本（两个空格缩进），见 [`TableData::infer_schema`]
+    pub fn schema_json(&self) -> String {
+        to_string_pretty(&self.infer_schema()).unwrap_or_default()
+    }
+
+    /// 转换为 JSON 对象数组文本（两个空格缩进），保留原始类型的值
+    pub fn to_json_string(&self) -> String {
+        let objects: Vec<serde_json::Map<String, Value>> = self
+            .typed_rows
+            .iter()
+            .map(|row| row.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+            .collect();
+        to_string_pretty(&objects).unwrap_or_default()
+    }
+
+    /// 转换为 NDJSON 文本，每行一个紧凑的 JSON 对象，以换行结尾
+    pub fn to_ndjson_string(&self) -> String {
+        self.typed_rows
+            .iter()
+            .map(|row| {
+                let object: serde_json::Map<String, Value> =
+                    row.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                format!("{}\n", Value::Object(object))
+            })
+            .collect()
+    }
+
+    /// 转换为 CSV 文本（默认格式，单元格不做公式转义）
+    pub fn to_csv_string(&self) -> String {
+        self.to_delimited_string(CsvFormat::default())
+    }
+
+    /// 转换为制表符分隔的 TSV 文本（单元格不做公式转义）
+    pub fn to_tsv_string(&self) -> String {
+        self.to_delimited_string(CsvFormat {
+            delimiter: CsvDelimiter::Tab,
+            ..CsvFormat::default()
+        })
+    }
+
+    /// 转换为 Markdown 表格文本
+    ///
+    /// 单元格中的 `|` 转义为 `\|`，换行替换为 `<br>`；没有列时返回空字符串
+    pub fn to_markdown_string(&self) -> String {
+        if self.columns.is_empty() {
+            return String::new();
+        }
+        let escape = |text: &str| {
+            text.replace('|', "\\|")
+                .replace("\r\n", "<br>")
+                .replace('\n', "<br>")
+        };
+        let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+        let mut output = line(self.columns.iter().map(|c| escape(c)).collect());
+        output.push_str(&line(vec!["---".to_string(); self.columns.len()]));
+        for row in &self.rows {
+            let cells = self
+                .columns
+                .iter()
+                .map(|column| escape(row.get(column).map(String::as_str).unwrap_or("")))
+                .collect();
+            output.push_str(&line(cells));
+        }
+        output
+    }
+
+    /// 转换为 HTML `<table>` 文本，单元格内容按 [`escape_html`] 转义
+    pub fn to_html_table(&self) -> String {
+        let mut output = String::from("<table>\n<tr>");
+        for column in &self.columns {
+            output.push_str(&format!("<th>{}</th>", escape_html(column)));
+        }
+        output.push_str("</tr>\n");
+        for row in &self.rows {
+            output.push_str("<tr>");
+            for column in &self.columns {
+                let value = row.get(column).map(String::as_str).unwrap_or("");
+                output.push_str(&format!("<td>{}</td>", escape_html(value)));
+            }
+            output.push_str("</tr>\n");
+        }
+        output.push_str("</table>\n");
+        output
+    }
+
+    /// 指定列中不重复的非空值，按首次出现的顺序排列
+    pub fn distinct_values(&self, column: &str) -> Vec<&str> {
+        let mut seen = HashSet::new();
+        self.rows
+            .iter()
+            .filter_map(|row| row.get(column))
+            .map(String::as_str)
+            .filter(|value| !value.is_empty() && seen.insert(*value))
+            .collect()
+    }
+
+    /// 使用指定格式转换为文本
+    fn to_delimited_string(&self, format: CsvFormat) -> String {
+        let mut output = Vec::new();
+        // 写入内存缓冲区不会失败，表格内容均为合法 UTF-8
+        let _ = self.write_csv(&mut output, format, false);
+        String::from_utf8(output).unwrap_or_default()
+    }
+
+    /// 将表格数据按指定的分隔符和引号风格以 CSV 格式写入
+    ///
+    /// `format.bom` 为 `true` 时先写入 UTF-8 BOM，避免 Excel 将非 ASCII 字符显示为乱码。
+    /// `safe` 为 `true` 时对可能被电子表格当作公式执行的单元格进行转义，
+    /// 见 [`escape_csv_formula`]。表头保持原样
+    ///
+    /// # Errors
+    ///
+    /// 如果写入失败，返回错误
+    pub fn write_csv<W: Write>(
+        &self,
+        mut output: W,
+        format: CsvFormat,
+        safe: bool,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if format.bom {
+            output.write_all(UTF8_BOM)?;
+        }
+        let mut writer = csv::WriterBuilder::new()
+            .delimiter(format.delimiter.byte())
+            .quote_style(format.quoting.quote_style())
+            .from_writer(output);
+
+        // 写入表头
+        // 列名来自用户自己编写的 return 字段（如 `@Volume`），不做转义
+        writer.write_record(&self.columns)?;
+
+        // 写入数据行
+        for row in &self.rows {
+            let record: Vec<Cow<'_, str>> = self
+                .columns
+                .iter()
+                .map(|col| {
+                    let value = row.get(col).map(|s| s.as_str()).unwrap_or("");
+                    if safe {
+                        escape_csv_formula(value)
+                    } else {
+                        Cow::Borrowed(value)
+                    }
+                })
+                .collect();
+            writer.write_record(record.iter().map(|value| value.as_bytes()))?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// 转义可能被电子表格当作公式执行的 CSV 单元格（CSV 注入）
+///
+/// 按 OWASP 建议，以 `=`、`+`、`-`、`@`、制表符或回车开头的值前加单引号。
+/// 数值（如 `-6` 音量）不会被当作公式，保持原样以便在电子表格中计算
+pub fn escape_csv_formula(value: &str) -> Cow<'_, str> {
+    let dangerous = value
+        .chars()
+        .next()
+        .is_some_and(|ch| matches!(ch, '=' | '+' | '-' | '@' | '\t' | '\r'));
+    if dangerous && value.parse::<f64>().is_err() {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// 判断值是否为 Wwise 对象 GUID
+///
+/// 格式为 `{xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx}`（十六进制，不区分大小写），
+/// 花括号可以省略
+pub fn is_guid(value: &str) -> bool {
+    let inner = value
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+        .unwrap_or(value);
+    let groups: Vec<&str> = inner.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// 将 JSON 值格式化为两个空格缩进的文本，格式与 `serde_json::to_string_pretty` 相同
+///
+/// 嵌套超过 `max_depth` 层的非空对象和数组显示为 `{...}`/`[...]`，`max_depth` 为 0 时整个值都折叠
+pub fn to_string_pretty_depth(value: &Value, max_depth: usize) -> String {
+    let mut output = String::new();
+    write_pretty_depth(&mut output, value, 0, max_depth);
+    output
+}
+
+/// 将第 `depth` 层的值写入 `output`，见 [`to_string_pretty_depth`]
+fn write_pretty_depth(output: &mut String, value: &Value, depth: usize, max_depth: usize) {
+    let (brackets, entries): (&str, Vec<(Option<&String>, &Value)>) = match value {
+        Value::Object(map) => ("{}", map.iter().map(|(k, v)| (Some(k), v)).collect()),
+        Value::Array(items) => ("[]", items.iter().map(|v| (None, v)).collect()),
+        scalar => {
+            output.push_str(&scalar.to_string());
+            return;
+        }
+    };
+    let (open, close) = brackets.split_at(1);
+    if entries.is_empty() {
+        output.push_str(brackets);
+        return;
+    }
+    if depth >= max_depth {
+        output.push_str(open);
+        output.push_str("...");
+        output.push_str(close);
+        return;
+    }
+    output.push_str(open);
+    for (i, (key, item)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            output.push(',');
+        }
+        output.push('\n');
+        output.push_str(&"  ".repeat(depth + 1));
+        if let Some(key) = key {
+            output.push_str(&to_string(key).unwrap_or_default());
+            output.push_str(": ");
+        }
+        write_pretty_depth(output, item, depth + 1, max_depth);
+    }
+    output.push('\n');
+    output.push_str(&"  ".repeat(depth));
+    output.push_str(close);
+}
+
+/// 判断值是否为网址，以 `http://`、`https://` 或 `file://` 开头（不区分大小写）且不包含空白
+pub fn is_url(value: &str) -> bool {
+    ["http://", "https://", "file://"].iter().any(|scheme| {
+        value.len() > scheme.len()
+            && value.get(..scheme.len()).is_some_and(|p| p.eq_ignore_ascii_case(scheme))
+    }) && !value.contains(char::is_whitespace)
+}
+
+/// 判断值是否为文件系统的绝对路径
+///
+/// 支持 Windows 盘符路径（`C:\...`）、UNC 路径（`\\server\...`）和至少两级的 Unix 绝对路径。
+/// Wwise 对象路径（`\Actor-Mixer Hierarchy\...`）以单个反斜杠开头，不视为文件路径
+pub fn is_file_path(value: &str) -> bool {
+    if value.contains(['\n', '\r']) {
+        return false;
+    }
+    let bytes = value.as_bytes();
+    let drive = bytes.len() > 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && matches!(bytes[2], b'\\' | b'/');
+    let unc = value.len() > 2 && value.starts_with("\\\\");
+    let unix = value.starts_with('/') && !value.starts_with("//") && value[1..].contains('/');
+    drive || unc || unix
+}
+
+/// 默认用于生成表格的顶层键（WAAPI 查询结果数组）
+pub const TABLE_KEY: &str = "return";
+
+/// 结果数组中不是对象的元素（标量或数组）所在的列
+pub const VALUE_COLUMN: &str = "value";
+
+/// 生成请求记录中保存的响应摘要，顶层数组见 [`summarize_items`]
+///
+/// 大结果只在查询结果中保存一份，请求记录中不再复制完整的响应
+fn summarize_response(map: &Map<String, Value>) -> Value {
+    Value::Object(
+        map.iter()
+            .map(|(key, value)| (key.clone(), summarize_items(value)))
+            .collect(),
+    )
+}
+
+/// 数组只保留前 [`EXCHANGE_MAX_ITEMS`] 个元素，并在末尾追加说明省略数量的字符串
+fn summarize_items(value: &Value) -> Value {
+    match value {
+        Value::Array(items) if items.len() > EXCHANGE_MAX_ITEMS => {
+            let mut kept = items[..EXCHANGE_MAX_ITEMS].to_vec();
+            kept.push(Value::String(format!(
+                "… {} more items",
+                items.len() - EXCHANGE_MAX_ITEMS
+            )));
+            Value::Array(kept)
+        }
+        other => other.clone(),
+    }
+}
+
+/// 列出 JSON 对象中值为数组的顶层键
+pub fn array_keys(value: &Value) -> Vec<String> {
+    value
+        .as_object()
+        .map(|object| {
+            object
+                .iter()
+                .filter(|(_, v)| v.is_array())
+                .map(|(k, _)| k.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 选择默认用于生成表格的顶层键
+///
+/// 优先使用 `return`，没有时使用第一个数组键
+pub fn default_table_key(keys: &[String]) -> &str {
+    keys.iter()
+        .find(|k| *k == TABLE_KEY)
+        .or_else(|| keys.first())
+        .map(String::as_str)
+        .unwrap_or(TABLE_KEY)
+}
+
+/// 可以作为 WAQL 查询开头（`$` 之后）的关键字
+const QUERY_START_KEYWORDS: [&str; 3] = ["from", "where", "select"];
+
+/// 为以 `from`、`where` 等关键字开头但缺少 `$` 的查询补全前缀
+///
+/// 已以 `$` 开头或不以这些关键字开头的查询返回 `None`
+pub fn fix_leading_dollar(query: &str) -> Option<String> {
+    let query = query.trim_start();
+    let first_word = query.split(|c: char| !c.is_alphanumeric()).next()?;
+    QUERY_START_KEYWORDS
+        .iter()
+        .any(|keyword| keyword.eq_ignore_ascii_case(first_word))
+        .then(|| format!("$ {}", query))
+}
+
+/// 规范化从其他地方粘贴的查询语句
+///
+/// - 连续的空白（包括制表符、换行和不间断空格）合并为一个空格
+/// - 弯引号 `“”` 替换为 `"`，`‘’` 替换为 `'`
+///
+/// 双引号字符串中的内容（包括其中的弯引号和 `\"` 转义）保持不变，
+/// 字符串外的弯双引号替换后同样视为字符串边界，以弯引号开始的字符串由弯引号或 `"` 结束
+pub fn normalize_query(code: &str) -> String {
+    let mut normalized = String::with_capacity(code.len());
+    // 当前字符串的开始引号，不在字符串中时为 `None`
+    let mut open_quote = None;
+    let mut escaped = false;
+    let mut pending_space = false;
+
+    for ch in code.trim().chars() {
+        if let Some(open) = open_quote {
+            let ch = match ch {
+                _ if escaped => {
+                    escaped = false;
+                    ch
+                }
+                '\\' => {
+                    escaped = true;
+                    ch
+                }
+                '"' => {
+                    open_quote = None;
+                    ch
+                }
+                '\u{201C}' | '\u{201D}' if open != '"' => {
+                    open_quote = None;
+                    '"'
+                }
+                _ => ch,
+            };
+            normalized.push(ch);
+            continue;
+        }
+
+        if ch.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space {
+            normalized.push(' ');
+            pending_space = false;
+        }
+        let ch = match ch {
+            '"' | '\u{201C}' | '\u{201D}' => {
+                open_quote = Some(ch);
+                '"'
+            }
+            '\u{2018}' | '\u{2019}' => '\'',
+            _ => ch,
+        };
+        normalized.push(ch);
+    }
+    normalized
+}
+
+/// 以 `key=value` 形式写在选项部分的 WAAPI 选项
+const VALUE_OPTIONS: [&str; 2] = ["platform", "language"];
+
+/// 选项部分每段开头可以使用的变换关键字
+///
+/// - `return <字段...>` / `select <字段...>`：追加返回字段
+/// - `platform <平台>` / `language <语言>`：设置同名 WAAPI 选项
+/// - `range <跳过数量> <获取数量>`：在查询末尾追加 `skip`/`take`
+const TRANSFORMS: [&str; 5] = ["return", "select", "platform", "language", "range"];
+
+/// 按空白分割选项文本，返回每个选项在文本中的字节范围和解析后的内容
+///
+/// 双引号包裹的内容作为一个整体（引号本身不保留），
+/// 引号内可以用 `\"` 和 `\\` 转义引号和反斜杠
+fn split_option_spans(options: &str) -> Vec<(Range<usize>, String)> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = None;
+    let mut in_quotes = false;
+    let mut chars = options.char_indices();
+
+    while let Some((i, ch)) = chars.next() {
+        match ch {
+            '"' => {
+                in_quotes = !in_quotes;
+                start.get_or_insert(i);
+            }
+            '\\' if in_quotes => match chars.next() {
+                Some((_, next @ ('"' | '\\'))) => current.push(next),
+                Some((_, next)) => {
+                    current.push('\\');
+                    current.push(next);
+                }
+                None => current.push('\\'),
+            },
+            c if c.is_whitespace() && !in_quotes => {
+                if let Some(start) = start.take() {
+                    tokens.push((start..i, std::mem::take(&mut current)));
+                }
+            }
+            c => {
+                current.push(c);
+                start.get_or_insert(i);
+            }
+        }
+    }
+    if let Some(start) = start {
+        tokens.push((start..options.len(), current));
+    }
+    tokens
+}
+
+/// 如果选项为 `key=value` 形式，返回其值
+fn option_value<'a>(token: &'a str, key: &str) -> Option<&'a str> {
+    token.strip_prefix(key)?.strip_prefix('=')
+}
+
+/// 返回所有不在双引号字符串中的 `|` 的字节位置
+///
+/// 字符串中可以用 `\"` 转义引号，字符串中的 `|` 作为普通字符
+fn unquoted_pipes(code: &str) -> Vec<usize> {
+    let mut pipes = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, ch) in code.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '|' if !in_string => pipes.push(i),
+            _ => {}
+        }
+    }
+    pipes
+}
+
+/// 在第一个不在双引号字符串中的 `|` 处分割查询语句和选项部分
+///
+/// 没有分隔符时返回 `None`
+pub fn split_query_options(code: &str) -> Option<(&str, &str)> {
+    let pipe = *unquoted_pipes(code).first()?;
+    Some((&code[..pipe], &code[pipe + 1..]))
+}
+
+/// 按不在字符串中的 `|` 将选项部分分为多段
+fn split_sections(options: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for pipe in unquoted_pipes(options) {
+        sections.push(&options[start..pipe]);
+        start = pipe + 1;
+    }
+    sections.push(&options[start..]);
+    sections
+}
+
+/// 读取查询语句选项部分中 `key=value` 选项的值
+///
+/// 只读取选项部分的第一段，以变换关键字开头的段不包含 `key=value` 选项
+pub fn query_option(code: &str, key: &str) -> Option<String> {
+    let (_, options) = split_query_options(code)?;
+    let (first, _) = split_first_section(options);
+    QueryExecutor::split_options(first)
+        .iter()
+        .find_map(|token| option_value(token, key).map(str::to_string))
+}
+
+/// 设置或移除查询语句选项部分中的 `key=value` 选项
+///
+/// 其余选项文本保持原样；`value` 为 `None` 时移除该选项，
+/// 移除后选项部分为空时同时移除 `|`
+pub fn set_query_option(code: &str, key: &str, value: Option<&str>) -> String {
+    let (query, options) = match split_query_options(code) {
+        Some((query, options)) => (query.trim_end(), options),
+        None => (code.trim_end(), ""),
+    };
+    let (first, rest) = split_first_section(options);
+
+    let mut kept: Vec<&str> = split_option_spans(first)
+        .into_iter()
+        .filter(|(_, token)| option_value(token, key).is_none())
+        .map(|(range, _)| &first[range])
+        .collect();
+    let option = value.map(|value| {
+        if value.contains(char::is_whitespace) || value.contains('"') {
+            let escaped = value.replace('\\', "\\\\").replace('"', "\\\"");
+            format!("{}=\"{}\"", key, escaped)
+        } else {
+            format!("{}={}", key, value)
+        }
+    });
+    kept.extend(option.as_deref());
+
+    let first = kept.join(" ");
+    let sections: Vec<&str> = [first.as_str(), rest.trim()]
+        .into_iter()
+        .filter(|section| !section.is_empty())
+        .collect();
+    if sections.is_empty() {
+        query.to_string()
+    } else {
+        format!("{} | {}", query, sections.join(" | "))
+    }
+}
+
+/// 分割选项部分的第一段和其余各段
+///
+/// 第一段以变换关键字开头时不能写入 `key=value` 选项，此时第一段视为空
+fn split_first_section(options: &str) -> (&str, &str) {
+    let (first, rest) = split_query_options(options).unwrap_or((options, ""));
+    let is_transform = QueryExecutor::split_options(first)
+        .first()
+        .is_some_and(|token| TRANSFORMS.contains(&token.as_str()));
+    if is_transform {
+        ("", options)
+    } else {
+        (first, rest)
+    }
+}
+
+/// WAQL 查询执行器
+pub struct QueryExecutor {
+    /// 当前传输，空闲断开后为 `None`，下次请求时重新创建
+    transport: Option<Box<dyn Transport>>,
+    client_config: ClientConfig,
+    offline: bool,
+    connection_state: ConnectionState,
+    /// 没有请求多久后断开连接，`None` 表示不断开
+    idle_timeout: Option<Duration>,
+    /// 最近一次请求的时间
+    last_activity: Instant,
+    /// 缓存的项目信息，切换连接时清除
+    project_info: Option<ProjectInfo>,
+    /// 是否自动为缺少 `$` 的查询补全前缀
+    auto_fix_dollar: bool,
+    /// 执行前是否规范化查询中的空白和弯引号
+    normalize_input: bool,
+    /// 执行前是否去除查询首尾的空白
+    trim_input: bool,
+    /// 查询没有 `|` 选项部分时默认返回的字段
+    default_return: Vec<String>,
+    /// 表格最多显示的行数，`None` 表示不限制
+    max_rows: Option<usize>,
+    /// 查询结果显示前按顺序应用的后处理规则
+    post_rules: Vec<PostRule>,
+    /// 最近的请求记录，最早的在前
+    exchanges: VecDeque<Exchange>,
+}
+
+impl Default for QueryExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl QueryExecutor {
+    /// 创建新的查询执行器
+    pub fn new() -> Self {
+        Self::with_config(ClientConfig::default())
+    }
+
+    /// 使用指定连接配置创建查询执行器
+    pub fn with_config(client_config: ClientConfig) -> Self {
+        let transport = Box::new(WaapiTransport::new(client_config.clone()));
+        Self::with_transport(transport, client_config, false)
+    }
+
+    /// 创建使用内置示例数据的离线执行器
+    pub fn offline() -> Self {
+        Self::with_transport(Box::new(SampleTransport), ClientConfig::default(), true)
+    }
+
+    /// 使用指定传输创建执行器，查询设置为默认值
+    fn with_transport(
+        transport: Box<dyn Transport>,
+        client_config: ClientConfig,
+        offline: bool,
+    ) -> Self {
+        Self {
+            transport: Some(transport),
+            client_config,
+            offline,
+            connection_state: if offline {
+                ConnectionState::Offline
+            } else {
+                ConnectionState::Unknown
+            },
+            idle_timeout: None,
+            last_activity: Instant::now(),
+            project_info: None,
+            auto_fix_dollar: false,
+            normalize_input: false,
+            trim_input: true,
+            default_return: Vec::new(),
+            max_rows: None,
+            post_rules: Vec::new(),
+            exchanges: VecDeque::new(),
+        }
+    }
+
+    /// 切换离线演示模式
+    pub fn set_offline(&mut self, offline: bool) {
+        if offline != self.offline {
+            self.offline = offline;
+            self.reset_transport();
+        }
+    }
+
+    /// 更新连接配置，非离线模式下重建连接
+    pub fn set_client_config(&mut self, client_config: ClientConfig) {
+        if client_config == self.client_config {
+            return;
+        }
+        self.client_config = client_config;
+        if !self.offline {
+            self.reset_transport();
+        }
+    }
+
+    /// 按当前模式和连接配置重建传输，并清除连接状态和缓存
+    fn reset_transport(&mut self) {
+        let fresh = if self.offline {
+            Self::offline()
+        } else {
+            Self::with_config(self.client_config.clone())
+        };
+        self.transport = fresh.transport;
+        self.connection_state = fresh.connection_state;
+        self.project_info = None;
+    }
+
+    /// 当前传输，空闲断开后按当前模式和连接配置重新创建
+    fn transport(&mut self) -> &mut dyn Transport {
+        let transport = match self.transport.take() {
+            Some(transport) => transport,
+            None if self.offline => Box::new(SampleTransport) as Box<dyn Transport>,
+            None => Box::new(WaapiTransport::new(self.client_config.clone())),
+        };
+        self.transport.insert(transport).as_mut()
+    }
+
+    /// 设置没有请求多久后断开连接，`None` 表示不断开
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) {
+        self.idle_timeout = timeout;
+    }
+
+    /// 距离空闲断开还有多久
+    ///
+    /// 离线模式、未设置空闲超时或已经断开时返回 `None`
+    pub fn idle_remaining(&self) -> Option<Duration> {
+        if self.offline || self.connection_state == ConnectionState::Idle {
+            return None;
+        }
+        let timeout = self.idle_timeout?;
+        Some(timeout.saturating_sub(self.last_activity.elapsed()))
+    }
+
+    /// 断开连接并释放传输（包括复用的 HTTP 连接），下次请求时重新连接
+    pub fn disconnect_idle(&mut self) {
+        if self.offline {
+            return;
+        }
+        self.transport = None;
+        self.connection_state = ConnectionState::Idle;
+    }
+
+    /// 设置是否自动为缺少 `$` 的查询补全前缀
+    pub fn set_auto_fix_dollar(&mut self, enabled: bool) {
+        self.auto_fix_dollar = enabled;
+    }
+
+    /// 设置执行前是否规范化查询中的空白和弯引号
+    pub fn set_normalize_input(&mut self, enabled: bool) {
+        self.normalize_input = enabled;
+    }
+
+    /// 设置执行前是否去除查询首尾的空白
+    ///
+    /// 关闭后没有 `|` 选项部分的查询按原样发送；`|` 之前的查询部分总是去除首尾空白，
+    /// 开启输入规范化时也会去除首尾空白，见 [`normalize_query`]
+    pub fn set_trim_input(&mut self, enabled: bool) {
+        self.trim_input = enabled;
+    }
+
+    /// 设置查询没有 `|` 选项部分时默认返回的字段
+    pub fn set_default_return(&mut self, fields: Vec<String>) {
+        self.default_return = fields;
+    }
+
+    /// 设置表格最多显示的行数，`None` 表示不限制
+    pub fn set_max_rows(&mut self, max_rows: Option<usize>) {
+        self.max_rows = max_rows;
+    }
+
+    /// 设置查询结果的后处理规则
+    pub fn set_post_rules(&mut self, rules: Vec<PostRule>) {
+        self.post_rules = rules;
+    }
+
+    /// 表格最多显示的行数
+    pub fn max_rows(&self) -> Option<usize> {
+        self.max_rows
+    }
+
+    /// 是否处于离线演示模式
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// 最近一次请求的连接状态
+    pub fn connection_state(&self) -> ConnectionState {
+        self.connection_state
+    }
+
+    /// 根据请求结果更新连接状态
+    fn record_connection<T, E>(&mut self, result: &Result<T, E>) {
+        self.last_activity = Instant::now();
+        if !self.offline {
+            self.connection_state = if result.is_ok() {
+                ConnectionState::Connected
+            } else {
+                ConnectionState::Failed
+            };
+        }
+    }
+
+    /// 获取项目信息
+    ///
+    /// 首次调用时通过 `ak.wwise.core.getProjectInfo` 获取并缓存，
+    /// 切换连接后重新获取
+    ///
+    /// # Errors
+    ///
+    /// 调用失败或响应无效时返回 [`WaapiError`]
+    pub fn project_info(&mut self) -> Result<ProjectInfo, WaapiError> {
+        match &self.project_info {
+            Some(info) => Ok(info.clone()),
+            None => self.refresh_project_info(),
+        }
+    }
+
+    /// 重新获取项目信息并更新缓存
+    ///
+    /// # Errors
+    ///
+    /// 调用失败或响应无效时返回 [`WaapiError`]
+    pub fn refresh_project_info(&mut self) -> Result<ProjectInfo, WaapiError> {
+        let result = self
+            .transport_call(PROJECT_INFO_URI, json!({}), None)
+            .and_then(|value| ProjectInfo::from_value(&value));
+        self.project_info = result.as_ref().ok().cloned();
+        result
+    }
+
+    /// 获取 Wwise 版本名称
+    ///
+    /// # Errors
+    ///
+    /// 调用失败或响应无效时返回 [`WaapiError`]
+    pub fn wwise_version(&mut self) -> Result<String, WaapiError> {
+        self.transport_call(WWISE_INFO_URI, json!({}), None)
+            .and_then(|value| wwise_version(&value))
+    }
+
+    /// 获取项目支持的对象类型名称
+    ///
+    /// # Errors
+    ///
+    /// 调用失败或响应无效时返回 [`WaapiError`]
+    pub fn object_types(&mut self) -> Result<Vec<String>, WaapiError> {
+        self.transport_call(OBJECT_TYPES_URI, json!({}), None)
+            .and_then(|value| object_type_names(&value))
+    }
+
+    /// 发送完整的 WAAPI 调用，包括选项
+    ///
+    /// # Errors
+    ///
+    /// 调用失败时返回 [`WaapiError`]
+    pub fn call_request(&mut self, request: &RpcRequest) -> Result<Value, WaapiError> {
+        self.transport_call(&request.uri, request.args.clone(), request.options.clone())
+    }
+
+    /// 订阅 WAAPI 主题，推送时调用 `handler`
+    ///
+    /// 目前的 HTTP 传输和离线传输都不支持订阅，总是返回错误
+    ///
+    /// # Errors
+    ///
+    /// 传输不支持订阅时返回 [`WaapiError::Unsupported`]
+    #[allow(dead_code)]
+    pub fn subscribe(&mut self, topic: &str, handler: TopicHandler) -> Result<(), WaapiError> {
+        self.transport().subscribe(topic, handler)
+    }
+
+    /// 通过传输调用 WAAPI 接口，记录请求并更新连接状态
+    fn transport_call(
+        &mut self,
+        uri: &str,
+        args: Value,
+        options: Option<Value>,
+    ) -> Result<Value, WaapiError> {
+        let mut request = json!({ "uri": uri, "args": args });
+        if let Some(options) = &options {
+            request["options"] = options.clone();
+        }
+        let start = Instant::now();
+        let result = self.transport().call(uri, args, options);
+        let response = result
+            .as_ref()
+            .map(|value| match value {
+                Value::Object(map) => summarize_response(map),
+                other => summarize_items(other),
+            })
+            .map_err(|e| e.to_string());
+        self.record_exchange(request, response, start.elapsed());
+        self.record_connection(&result);
+        result
+    }
+
+    /// 通过传输执行 WAQL 查询，记录请求并更新连接状态
+    fn transport_query(
+        &mut self,
+        query: &str,
+        options: Option<Value>,
+    ) -> Result<Map<String, Value>, String> {
+        let mut request = json!({ "uri": WAQL_URI, "args": { "waql": query } });
+        if let Some(options) = &options {
+            request["options"] = options.clone();
+        }
+        let start = Instant::now();
+        let result = self.transport().waql_query(query, options);
+        let response = result.as_ref().map(summarize_response).map_err(Clone::clone);
+        self.record_exchange(request, response, start.elapsed());
+        self.record_connection(&result);
+        result
+    }
+
+    /// 记录一次请求，超过 [`MAX_EXCHANGES`] 条时丢弃最早的记录
+    fn record_exchange(
+        &mut self,
+        request: Value,
+        response: Result<Value, String>,
+        elapsed: Duration,
+    ) {
+        if self.exchanges.len() == MAX_EXCHANGES {
+            self.exchanges.pop_front();
+        }
+        self.exchanges.push_back(Exchange {
+            request,
+            response,
+            elapsed,
+        });
+    }
+
+    /// 最近的请求记录，最早的在前
+    pub fn exchanges(&self) -> &VecDeque<Exchange> {
+        &self.exchanges
+    }
+
+    /// 清空请求记录
+    pub fn clear_exchanges(&mut self) {
+        self.exchanges.clear();
+    }
+
+    /// 检测连接并返回往返延迟
+    ///
+    /// # Errors
+    ///
+    /// 如果请求失败，返回错误信息
+    pub fn ping(&mut self) -> Result<Duration, String> {
+        let start = Instant::now();
+        self.transport_query(PING_QUERY, Some(json!({"return": ["name"]})))
+            .map(|_| start.elapsed())
+            .map_err(|e| format!("连接失败: {}", e))
+    }
+
+    /// 创建在后台线程中使用的执行器
+    ///
+    /// 与本执行器共用传输的连接（HTTP 连接池），并使用相同的模式、连接配置和查询设置。
+    /// 空闲断开后会先重新创建本执行器的传输
+    fn worker(&mut self) -> Self {
+        let transport = self.transport().share();
+        let mut worker = Self::with_transport(transport, self.client_config.clone(), self.offline);
+        worker.auto_fix_dollar = self.auto_fix_dollar;
+        worker.normalize_input = self.normalize_input;
+        worker.trim_input = self.trim_input;
+        worker.default_return = self.default_return.clone();
+        worker.max_rows = self.max_rows;
+        worker.post_rules = self.post_rules.clone();
+        worker
+    }
+
+    /// 在后台线程中检测连接，避免连接超时时阻塞界面
+    ///
+    /// 连接正常时按需在同一线程中获取项目信息和对象类型。
+    /// 检测结果不会更新本执行器的连接状态和项目信息缓存
+    pub fn spawn_ping(&mut self, fetch_project: bool, fetch_types: bool) -> Receiver<PingOutcome> {
+        let (sender, receiver) = mpsc::channel();
+        let mut worker = self.worker();
+        thread::spawn(move || {
+            let latency = worker.ping();
+            let ok = latency.is_ok();
+            let project = if ok && fetch_project { worker.project_info().ok() } else { None };
+            let object_types = if ok && fetch_types { worker.object_types().ok() } else { None };
+            let _ = sender.send(PingOutcome {
+                latency,
+                project,
+                object_types,
+            });
+        });
+        receiver
+    }
+
+    /// 在后台线程中执行查询，等待响应和解析结果时不阻塞界面
+    ///
+    /// 后台线程使用与本执行器共用连接的执行器，见 [`QueryExecutor::worker`]，
+    /// 完成后通过 [`PendingQuery::poll`] 和 [`QueryExecutor::finish_query`] 取回结果
+    pub fn spawn_execute(&mut self, code: &str) -> PendingQuery {
+        let (sender, receiver) = mpsc::channel();
+        let code = code.to_string();
+        let mut worker = self.worker();
+        thread::spawn(move || {
+            let result = worker.execute(&code);
+            let _ = sender.send(QueryOutcome {
+                result,
+                exchanges: worker.exchanges,
+                connection_state: worker.connection_state,
+            });
+        });
+        PendingQuery { receiver }
+    }
+
+    /// 取回后台查询的结果，并记录其请求和连接状态
+    ///
+    /// # Errors
+    ///
+    /// 查询失败时返回错误信息
+    pub fn finish_query(&mut self, outcome: QueryOutcome) -> Result<QueryResult, String> {
+        for exchange in outcome.exchanges {
+            if self.exchanges.len() == MAX_EXCHANGES {
+                self.exchanges.pop_front();
+            }
+            self.exchanges.push_back(exchange);
+        }
+        // 查询在发送前失败（如语法错误）时连接状态未知，保持原状态
+        if !self.offline && outcome.connection_state != ConnectionState::Unknown {
+            self.connection_state = outcome.connection_state;
+            self.last_activity = Instant::now();
+        }
+        outcome.result
+    }
+
+    /// 执行 WAQL 查询
+    /// 
+    /// # Arguments
+    /// 
+    /// * `code` - WAQL 查询语句，可以包含 options（用 | 分隔）
+    /// 
+    /// # Returns
+    /// 
+    /// 返回查询结果或错误信息
+    pub fn execute(&mut self, code: &str) -> Result<QueryResult, String> {
+        if code.trim().is_empty() {
+            return Err("请输入 WAQL 查询语句".to_string());
+        }
+        let code = if self.trim_input { code.trim() } else { code };
+
+        let normalized = self.normalize_input.then(|| normalize_query(code));
+        let code = normalized.as_deref().unwrap_or(code);
+
+        let (query, options) = self.parse_query(code)?;
+        let fixed_query = if self.auto_fix_dollar {
+            fix_leading_dollar(&query)
+        } else {
+            None
+        };
+        let query = fixed_query.as_deref().unwrap_or(&query);
+
+        let start = Instant::now();
+        let result = self.transport_query(query, options);
+        let elapsed = start.elapsed();
+        let duplicate_keys = self.transport().take_duplicate_keys();
+
+        match result {
+            // 将 Map 转换为 Value
+            Ok(result) => Ok(QueryResult {
+                elapsed,
+                fixed_query,
+                duplicate_keys,
+                ..Self::build_result(Value::Object(result), self.max_rows, &self.post_rules)
+            }),
+            Err(e) => Err(format!("查询失败: {}", e)),
+        }
+    }
+
+    /// 从 JSON 值构建查询结果，用于显示 WAAPI 调用的响应，表格行数受 `max_rows` 限制
+    pub fn result_from_value(value: Value, max_rows: Option<usize>) -> QueryResult {
+        Self::build_result(value, max_rows, &[])
+    }
+
+    /// 从 JSON 文本构建查询结果
+    ///
+    /// 用于加载之前导出的 JSON 结果文件，表格行数受 `max_rows` 限制
+    ///
+    /// # Errors
+    ///
+    /// 如果文本不是合法的 JSON，返回错误信息
+    pub fn result_from_json(text: &str, max_rows: Option<usize>) -> Result<QueryResult, String> {
+        let value: Value =
+            serde_json::from_str(text).map_err(|e| format!("解析 JSON 失败: {}", e))?;
+        Ok(QueryResult {
+            duplicate_keys: duplicate_keys(text),
+            ..Self::build_result(value, max_rows, &[])
+        })
+    }
+
+    /// 根据 JSON 结果构建查询结果
+    ///
+    /// 行数超过 `BACKGROUND_PARSE_ROWS` 时在后台线程中解析表格，
+    /// 结果通过 `pending_table` 返回；较小的结果同步解析。
+    /// `count` 始终为数组的实际长度，不受 `max_rows` 影响。表格解析后应用 `rules`
+    fn build_result(
+        result_value: Value,
+        max_rows: Option<usize>,
+        rules: &[PostRule],
+    ) -> QueryResult {
+        let raw_json = Self::format_json(&result_value);
+        let array_keys = array_keys(&result_value);
+        let table_key = default_table_key(&array_keys).to_string();
+        let kind = ResultKind::of(&result_value, &table_key);
+
+        let return_len = result_value
+            .get(&table_key)
+            .and_then(Value::as_array)
+            .map(Vec::len)
+            .unwrap_or(0);
+
+        let parse_rows = max_rows.map_or(return_len, |max| return_len.min(max));
+        if parse_rows > BACKGROUND_PARSE_ROWS {
+            let (sender, receiver) = mpsc::channel();
+            let key = table_key.clone();
+            let rules = rules.to_vec();
+            thread::spawn(move || {
+                let table = Self::parse_table_data(&result_value, &key, max_rows)
+                    .map(|table| postprocess(table, &rules));
+                let _ = sender.send(table);
+            });
+            return QueryResult {
+                raw_json,
+                table_data: None,
+                pending_table: Some(PendingTable {
+                    rows: parse_rows,
+                    receiver,
+                }),
+                count: return_len,
+                elapsed: Duration::ZERO,
+                fixed_query: None,
+                array_keys,
+                table_key,
+                kind,
+                duplicate_keys: Vec::new(),
+            };
+        }
+
+        let table_data = Self::parse_table_data(&result_value, &table_key, max_rows)
+            .map(|table| postprocess(table, rules));
+        let count = table_data.as_ref().map(|t| t.total_rows).unwrap_or(0);
+
+        QueryResult {
+            raw_json,
+            table_data,
+            pending_table: None,
+            count,
+            elapsed: Duration::ZERO,
+            fixed_query: None,
+            array_keys,
+            table_key,
+            kind,
+            duplicate_keys: Vec::new(),
+        }
+    }
+
+    /// 从 JSON 文本中指定的顶层数组构建表格
+    ///
+    /// 用于在结果包含多个数组时切换表格显示的数组
+    pub fn table_from_json(text: &str, key: &str, max_rows: Option<usize>) -> Option<TableData> {
+        let value: Value = serde_json::from_str(text).ok()?;
+        Self::parse_table_data(&value, key, max_rows)
+    }
+
+    /// 将 JSON 结果格式化为字符串
+    ///
+    /// 结果较小时输出带缩进的格式，超过 `PRETTY_PRINT_LIMIT` 时直接输出紧凑格式
+    fn format_json(value: &Value) -> String {
+        let compact = match to_string(value) {
+            Ok(compact) => compact,
+            Err(_) => return "格式化结果失败".to_string(),
+        };
+        if compact.len() > PRETTY_PRINT_LIMIT {
+            return compact;
+        }
+        to_string_pretty(value).unwrap_or(compact)
+    }
+
+    /// 解析 WAQL 查询语句和选项
+    ///
+    /// 如果查询语句包含不在字符串中的 `|`，则分割为查询部分和选项部分，
+    /// 选项部分可以再用 `|` 分为多段，每段以 [`TRANSFORMS`] 中的关键字开头。
+    /// 第一段也可以沿用原有写法：`platform=...` 和 `language=...` 作为同名选项发送，
+    /// 其余作为返回字段。没有 `|` 时使用默认返回字段（如已设置）
+    ///
+    /// # Errors
+    ///
+    /// 变换关键字未知或参数不正确时返回错误信息
+    fn parse_query<'a>(&self, code: &'a str) -> Result<(Cow<'a, str>, Option<Value>), String> {
+        let Some((query_part, options_part)) = split_query_options(code) else {
+            let options = (!self.default_return.is_empty())
+                .then(|| json!({ "return": self.default_return }));
+            return Ok((Cow::Borrowed(code), options));
+        };
+
+        let mut query = Cow::Borrowed(query_part.trim());
+        let mut options = serde_json::Map::new();
+        let mut fields = Vec::new();
+        for (index, section) in split_sections(options_part).into_iter().enumerate() {
+            let mut tokens = Self::split_options(section);
+            let Some(first) = tokens.first() else {
+                continue;
+            };
+
+            if !TRANSFORMS.contains(&first.as_str()) {
+                if index > 0 {
+                    return Err(format!(
+                        "未知的选项变换: {}（支持 {}）",
+                        first,
+                        TRANSFORMS.join(", ")
+                    ));
+                }
+                // 第一段的原有写法：直接列出返回字段和 `key=value` 选项
+                for token in tokens {
+                    match VALUE_OPTIONS
+                        .iter()
+                        .find_map(|key| Some((*key, option_value(&token, key)?)))
+                    {
+                        Some((key, value)) => {
+                            options.insert(key.to_string(), json!(value));
+                        }
+                        None => fields.push(token),
+                    }
+                }
+                continue;
+            }
+
+            let keyword = tokens.remove(0);
+            match (keyword.as_str(), tokens.as_slice()) {
+                ("return" | "select", _) => fields.extend(tokens),
+                ("platform" | "language", [value]) => {
+                    options.insert(keyword, json!(value));
+                }
+                ("range", [skip, take]) => {
+                    let (Ok(skip), Ok(take)) = (skip.parse::<usize>(), take.parse::<usize>())
+                    else {
+                        return Err(format!("range 的参数必须是数字: {} {}", skip, take));
+                    };
+                    query = Cow::Owned(format!("{} skip {} take {}", query, skip, take));
+                }
+                ("range", _) => {
+                    return Err("range 需要两个参数: range <跳过数量> <获取数量>".to_string());
+                }
+                _ => return Err(format!("{} 需要一个参数", keyword)),
+            }
+        }
+        if !fields.is_empty() {
+            options.insert("return".to_string(), json!(fields));
+        }
+
+        let options = (!options.is_empty()).then_some(Value::Object(options));
+        Ok((query, options))
+    }
+
+    /// 按空白分割选项文本，规则见 [`split_option_spans`]
+    fn split_options(options: &str) -> Vec<String> {
+        split_option_spans(options)
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect()
+    }
+
+    /// 从 JSON 结果中解析表格数据
+    ///
+    /// 列名规则：
+    /// - 列名区分大小写，`name` 和 `Name` 是两个不同的列，与 WAAPI 返回的字段名一致
+    /// - 嵌套对象不展开，整体以 JSON 文本显示在一个单元格中，
+    ///   因此键名 `parent.name` 不会与嵌套的 `{"parent": {"name": ...}}` 冲突
+    /// - 列按首次出现的顺序排列，某个对象缺少的列显示为空
+    /// - 同一个对象中重复的键在解析 JSON 时只保留最后一个值，因此只占一列、显示最后的值；
+    ///   重复的键由 [`duplicate_keys`] 在原始文本上检查，记录在 [`QueryResult::duplicate_keys`]
+    /// - 数组中不是对象的元素（标量或数组）各占一行，值放在 [`VALUE_COLUMN`] 列，
+    ///   与对象混合时其余列为空；数组为空时返回 `None`。对象中已有同名的键时，
+    ///   该列改名为 `value_2`、`value_3` 等第一个不冲突的名称
+    ///
+    /// 设置了 `max_rows` 时只保留前 `max_rows` 个元素，列名也只从保留的行中收集，
+    /// 截断前的元素数量记录在 `total_rows` 中
+    fn parse_table_data(result: &Value, key: &str, max_rows: Option<usize>) -> Option<TableData> {
+        let return_array = result.get(key)?.as_array()?;
+        if return_array.is_empty() {
+            return None;
+        }
+        let total_rows = return_array.len();
+        let items = &return_array[..max_rows.map_or(total_rows, |max| total_rows.min(max))];
+
+        // 提取所有可能的列名（从所有对象的键中收集），不是对象的元素放在 value 列
+        let value_column = Self::value_column_name(items);
+        let mut columns = Vec::new();
+        let mut columns_set = std::collections::HashSet::new();
+
+        for item in items {
+            let keys: Vec<&str> = match item.as_object() {
+                Some(obj) => obj.keys().map(String::as_str).collect(),
+                None => vec![value_column.as_str()],
+            };
+            for key in keys {
+                if columns_set.insert(key.to_string()) {
+                    columns.push(key.to_string());
+                }
+            }
+        }
+
+        // 转换数据行，同时保留原始类型的值
+        let mut rows = Vec::new();
+        let mut typed_rows = Vec::new();
+        for item in items {
+            let mut row = HashMap::new();
+            let mut typed_row = HashMap::new();
+            let Some(obj) = item.as_object() else {
+                for col in &columns {
+                    row.insert(col.clone(), String::new());
+                }
+                row.insert(value_column.clone(), Self::value_to_string(item));
+                typed_row.insert(value_column.clone(), item.clone());
+                rows.push(row);
+                typed_rows.push(typed_row);
+                continue;
+            };
+            for col in &columns {
+                let value = obj.get(col).map(Self::value_to_string).unwrap_or_default();
+                row.insert(col.clone(), value);
+                if let Some(v) = obj.get(col) {
+                    typed_row.insert(col.clone(), v.clone());
+                }
+            }
+            rows.push(row);
+            typed_rows.push(typed_row);
+        }
+
+        Some(TableData {
+            columns,
+            rows,
+            typed_rows,
+            total_rows,
+        })
+    }
+
+    /// 选择放置非对象元素的列名：[`VALUE_COLUMN`]，与对象的键冲突时依次加上序号
+    fn value_column_name(items: &[Value]) -> String {
+        let taken = |name: &str| {
+            items
+                .iter()
+                .filter_map(Value::as_object)
+                .any(|obj| obj.contains_key(name))
+        };
+        let mut name = VALUE_COLUMN.to_string();
+        let mut suffix = 1;
+        while taken(&name) {
+            suffix += 1;
+            name = format!("{}_{}", VALUE_COLUMN, suffix);
+        }
+        name
+    }
+
+    /// 将 JSON Value 转换为字符串
+    fn value_to_string(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Null => "null".to_string(),
+            _ => serde_json::to_string(value).unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_query_without_options() {
+        let executor = QueryExecutor::new();
+        let (query, options) = executor.parse_query("$ from type Sound").unwrap();
+        assert_eq!(query, "$ from type Sound");
+        assert!(options.is_none());
+    }
+
+    #[test]
+    fn test_parse_query_with_options() {
+        let executor = QueryExecutor::new();
+        let (query, options) = executor.parse_query("$ from type Sound | name id").unwrap();
+        assert_eq!(query, "$ from type Sound");
+        assert!(options.is_some());
+    }
+
+    #[test]
+    fn test_parse_query_quoted_pipe() {
+        let executor = QueryExecutor::new();
+        let (query, options) = executor
+            .parse_query(r#"$ from type Sound where name : "a|b" | name"#)
+            .unwrap();
+        assert_eq!(query, r#"$ from type Sound where name : "a|b""#);
+        assert_eq!(options.unwrap(), json!({"return": ["name"]}));
+
+        let (query, options) = executor
+            .parse_query(r#"$ where notes : "say \"|\" ok""#)
+            .unwrap();
+        assert_eq!(query, r#"$ where notes : "say \"|\" ok""#);
+        assert!(options.is_none());
+    }
+
+    #[test]
+    fn test_parse_query_transforms() {
+        let executor = QueryExecutor::new();
+        let (query, options) = executor
+            .parse_query("$ from type Sound | select name @Volume | platform Windows | range 0 10")
+            .unwrap();
+        assert_eq!(query, "$ from type Sound skip 0 take 10");
+        assert_eq!(
+            options.unwrap(),
+            json!({"return": ["name", "@Volume"], "platform": "Windows"})
+        );
+
+        let (_, options) = executor
+            .parse_query("$ from type Sound | name | return id | language SFX")
+            .unwrap();
+        assert_eq!(
+            options.unwrap(),
+            json!({"return": ["name", "id"], "language": "SFX"})
+        );
+
+        assert!(executor.parse_query("$ from type Sound | name | sort name").is_err());
+        assert!(executor.parse_query("$ from type Sound | range 10").is_err());
+        assert!(executor.parse_query("$ from type Sound | range a b").is_err());
+        assert!(executor.parse_query("$ from type Sound | platform").is_err());
+    }
+
+    #[test]
+    fn test_parse_query_default_return() {
+        let mut executor = QueryExecutor::new();
+        executor.set_default_return(vec!["name".to_string(), "id".to_string()]);
+
+        let (_, options) = executor.parse_query("$ from type Sound").unwrap();
+        assert_eq!(options.unwrap(), json!({"return": ["name", "id"]}));
+
+        let (_, options) = executor.parse_query("$ from type Sound | type").unwrap();
+        assert_eq!(options.unwrap(), json!({"return": ["type"]}));
+    }
+
+    #[test]
+    fn test_split_options_plain() {
+        assert_eq!(
+            QueryExecutor::split_options("name  id\t@Volume"),
+            vec!["name", "id", "@Volume"]
+        );
+        assert!(QueryExecutor::split_options("   ").is_empty());
+    }
+
+    #[test]
+    fn test_split_options_quoted() {
+        assert_eq!(
+            QueryExecutor::split_options(r#"name "some thing" id"#),
+            vec!["name", "some thing", "id"]
+        );
+        assert_eq!(QueryExecutor::split_options(r#""""#), vec![""]);
+    }
+
+    #[test]
+    fn test_split_options_escaped_quotes() {
+        assert_eq!(
+            QueryExecutor::split_options(r#""say \"hi\"" "a\\b" "c\d""#),
+            vec![r#"say "hi""#, r"a\b", r"c\d"]
+        );
+    }
+
+    #[test]
+    fn test_parse_query_with_quoted_options() {
+        let executor = QueryExecutor::new();
+        let (_, options) = executor.parse_query(r#"$ from type Sound | name "a b""#).unwrap();
+        assert_eq!(options.unwrap(), json!({"return": ["name", "a b"]}));
+    }
+
+    #[test]
+    fn test_parse_query_with_value_options() {
+        let executor = QueryExecutor::new();
+        let (_, options) = executor
+            .parse_query("$ from type Sound | name platform=Windows language=SFX")
+            .unwrap();
+        assert_eq!(
+            options.unwrap(),
+            json!({"return": ["name"], "platform": "Windows", "language": "SFX"})
+        );
+
+        let (_, options) = executor.parse_query("$ from type Sound | platform=Windows").unwrap();
+        assert_eq!(options.unwrap(), json!({"platform": "Windows"}));
+    }
+
+    #[test]
+    fn test_set_query_option() {
+        let code = "$ from type Sound | name  \"a b\" platform=PS5";
+        assert_eq!(query_option(code, "platform").as_deref(), Some("PS5"));
+        assert!(query_option(code, "language").is_none());
+
+        let code = set_query_option(code, "platform", Some("Windows"));
+        assert_eq!(code, "$ from type Sound | name \"a b\" platform=Windows");
+
+        let code = set_query_option(&code, "language", Some("Chinese (PRC)"));
+        assert_eq!(
+            query_option(&code, "language").as_deref(),
+            Some("Chinese (PRC)")
+        );
+
+        let code = set_query_option(&code, "language", None);
+        let code = set_query_option(&code, "platform", None);
+        assert_eq!(code, "$ from type Sound | name \"a b\"");
+
+        assert_eq!(
+            set_query_option("$ from type Bus", "platform", Some("Switch")),
+            "$ from type Bus | platform=Switch"
+        );
+        assert_eq!(
+            set_query_option("$ from type Bus | platform=Switch", "platform", None),
+            "$ from type Bus"
+        );
+        assert_eq!(
+            set_query_option("$ from type Bus | name | range 0 5", "platform", Some("PS5")),
+            "$ from type Bus | name platform=PS5 | range 0 5"
+        );
+        assert_eq!(
+            set_query_option("$ from type Bus | select name", "platform", Some("PS5")),
+            "$ from type Bus | platform=PS5 | select name"
+        );
+    }
+
+    #[test]
+    fn test_fix_leading_dollar() {
+        assert_eq!(
+            fix_leading_dollar("from type Event").as_deref(),
+            Some("$ from type Event")
+        );
+        assert_eq!(
+            fix_leading_dollar("  WHERE name : \"a\"").as_deref(),
+            Some("$ WHERE name : \"a\"")
+        );
+        assert!(fix_leading_dollar("$ from type Event").is_none());
+        assert!(fix_leading_dollar("$from type Event").is_none());
+        assert!(fix_leading_dollar("fromage").is_none());
+        assert!(fix_leading_dollar("").is_none());
+    }
+
+    #[test]
+    fn test_execute_auto_fix_dollar() {
+        let mut executor = QueryExecutor::offline();
+        assert!(executor.execute("from type Event").is_err());
+
+        executor.set_auto_fix_dollar(true);
+        let result = executor.execute("from type Event").unwrap();
+        assert_eq!(result.fixed_query.as_deref(), Some("$ from type Event"));
+        assert!(result.count > 0);
+
+        let result = executor.execute("$ from type Event").unwrap();
+        assert!(result.fixed_query.is_none());
+    }
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("$ from type Sound\twhere  name\u{00A0}:\n “foot”"),
+            "$ from type Sound where name : \"foot\""
+        );
+        assert_eq!(
+            normalize_query("$ from type Sound where name : ‘foot’"),
+            "$ from type Sound where name : 'foot'"
+        );
+        // 字符串中的空白和引号保持不变
+        assert_eq!(
+            normalize_query("$ from type Sound where name : \"Foot  ‘L’\""),
+            "$ from type Sound where name : \"Foot  ‘L’\""
+        );
+        assert_eq!(
+            normalize_query("$ where name : “a  b” |  name"),
+            "$ where name : \"a  b\" | name"
+        );
+        // 直引号字符串中的弯引号不替换
+        assert_eq!(
+            normalize_query("$ where name : \"He said “hi”\"  take 1"),
+            "$ where name : \"He said “hi”\" take 1"
+        );
+        // 转义的引号不结束字符串
+        assert_eq!(
+            normalize_query(r#"$ where notes : "a\"b   c"   take  1"#),
+            r#"$ where notes : "a\"b   c" take 1"#
+        );
+        assert_eq!(
+            normalize_query(r#"$ where notes : “a\\”   take  1"#),
+            r#"$ where notes : "a\\" take 1"#
+        );
+    }
+
+    #[test]
+    fn test_exchanges_ring_buffer() {
+        let mut executor = QueryExecutor::offline();
+        executor.execute("$ from type Sound | name").unwrap();
+        let exchange = executor.exchanges().back().unwrap();
+        assert_eq!(exchange.request["uri"], WAQL_URI);
+        assert_eq!(exchange.request["args"]["waql"], "$ from type Sound");
+        assert!(exchange.response.as_ref().unwrap()["return"].is_array());
+
+        for _ in 0..MAX_EXCHANGES {
+            let _ = executor.execute("$ from type Event");
+        }
+        assert_eq!(executor.exchanges().len(), MAX_EXCHANGES);
+        assert_eq!(executor.exchanges()[0].request["args"]["waql"], "$ from type Event");
+
+        executor.clear_exchanges();
+        assert!(executor.exchanges().is_empty());
+    }
+
+    #[test]
+    fn test_summarize_response() {
+        let items: Vec<Value> = (0..EXCHANGE_MAX_ITEMS + 5).map(|i| json!(i)).collect();
+        let response = json!({"return": items, "count": 3, "short": [1, 2]});
+        let summary = summarize_response(response.as_object().unwrap());
+        let kept = summary["return"].as_array().unwrap();
+        assert_eq!(kept.len(), EXCHANGE_MAX_ITEMS + 1);
+        assert_eq!(kept[EXCHANGE_MAX_ITEMS - 1], json!(EXCHANGE_MAX_ITEMS - 1));
+        assert_eq!(kept[EXCHANGE_MAX_ITEMS], json!("… 5 more items"));
+        assert_eq!(summary["count"], 3);
+        assert_eq!(summary["short"], json!([1, 2]));
+    }
+
+    #[test]
+    fn test_parse_rpc_request() {
+        let request = RpcRequest::parse(
+            r#"{"uri": " ak.wwise.core.object.get ", "args": {"waql": "$ from type Event"},
+                "options": {"return": ["name"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(request.uri, "ak.wwise.core.object.get");
+        assert_eq!(request.args["waql"], "$ from type Event");
+        assert_eq!(request.to_json()["options"]["return"][0], "name");
+
+        let request = RpcRequest::parse(r#"{"uri": "ak.wwise.core.getInfo"}"#).unwrap();
+        assert_eq!(request.args, json!({}));
+        assert_eq!(request.to_json(), json!({"uri": "ak.wwise.core.getInfo", "args": {}}));
+
+        assert!(RpcRequest::parse("{").is_err());
+        assert!(RpcRequest::parse("[]").is_err());
+        assert!(RpcRequest::parse(r#"{"args": {}}"#).is_err());
+        assert!(RpcRequest::parse(r#"{"uri": "x", "args": []}"#).is_err());
+        assert!(RpcRequest::parse(r#"{"uri": "x", "option": {}}"#).is_err());
+    }
+
+    #[test]
+    fn test_spawn_execute() {
+        let mut executor = QueryExecutor::offline();
+        let pending = executor.spawn_execute("$ from type Event");
+        let outcome = loop {
+            if let Some(outcome) = pending.poll() {
+                break outcome;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        let result = executor.finish_query(outcome).unwrap();
+        assert!(result.count > 0);
+        assert_eq!(executor.exchanges().len(), 1);
+        assert_eq!(executor.connection_state(), ConnectionState::Offline);
+
+        let outcome = executor.spawn_execute("   ").wait();
+        assert!(executor.finish_query(outcome).is_err());
+        assert_eq!(executor.exchanges().len(), 1);
+    }
+
+    #[test]
+    fn test_execute_normalize_input() {
+        let mut executor = QueryExecutor::offline();
+        let query = "$ from type Sound where name : “foot”";
+        assert!(executor.execute(query).is_err());
+
+        executor.set_normalize_input(true);
+        assert!(executor.execute(query).unwrap().count > 0);
+    }
+
+    #[test]
+    fn test_execute_trim_input() {
+        let mut executor = QueryExecutor::offline();
+        executor.execute("  $ from type Event\n").unwrap();
+        assert_eq!(executor.exchanges()[0].request["args"]["waql"], "$ from type Event");
+
+        executor.set_trim_input(false);
+        executor.execute("  $ from type Event\n").unwrap();
+        assert_eq!(executor.exchanges()[1].request["args"]["waql"], "  $ from type Event\n");
+        assert!(executor.execute(" \n ").is_err());
+    }
+
+    #[test]
+    fn test_result_kind() {
+        let result = QueryExecutor::result_from_json(r#"{"return": []}"#, None).unwrap();
+        assert_eq!(result.kind, ResultKind::EmptyTable);
+        assert_eq!(result.count, 0);
+        assert!(result.table_data.is_none());
+
+        let result = QueryExecutor::result_from_json(r#"{"name": "Wwise"}"#, None).unwrap();
+        assert_eq!(result.kind, ResultKind::NonTabular);
+        assert!(result.table_data.is_none());
+
+        let result = QueryExecutor::result_from_json(r#"{"return": [1, 2]}"#, None).unwrap();
+        assert_eq!(result.kind, ResultKind::Table);
+        assert_eq!(result.table_data.unwrap().columns, vec![VALUE_COLUMN]);
+
+        let result =
+            QueryExecutor::result_from_json(r#"{"return": [{"name": "a"}]}"#, None).unwrap();
+        assert_eq!(result.kind, ResultKind::Table);
+        assert_eq!(result.count, 1);
+    }
+
+    #[test]
+    fn test_parse_mixed_return_array() {
+        let result = json!({
+            "return": [
+                {"name": "Foot", "@Volume": -3},
+                "Music",
+                42,
+                ["a", "b"],
+                null
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(table.columns, vec!["@Volume", "name", VALUE_COLUMN]);
+        assert_eq!(table.rows.len(), 5);
+        assert_eq!(table.total_rows, 5);
+        assert_eq!(table.rows[0]["name"], "Foot");
+        assert_eq!(table.rows[0][VALUE_COLUMN], "");
+        assert_eq!(table.rows[1][VALUE_COLUMN], "Music");
+        assert_eq!(table.rows[2][VALUE_COLUMN], "42");
+        assert_eq!(table.rows[3][VALUE_COLUMN], r#"["a","b"]"#);
+        assert_eq!(table.typed_rows[3][VALUE_COLUMN], json!(["a", "b"]));
+        assert_eq!(table.rows[4][VALUE_COLUMN], "null");
+        assert_eq!(table.rows[1]["name"], "");
+    }
+
+    #[test]
+    fn test_table_from_other_array_key() {
+        let text = r#"{"objects": [{"name": "a"}], "errors": [{"message": "x"}, {"message": "y"}]}"#;
+        let result = QueryExecutor::result_from_json(text, None).unwrap();
+        assert_eq!(result.array_keys, vec!["errors", "objects"]);
+        assert_eq!(result.table_key, "errors");
+        assert_eq!(result.count, 2);
+
+        let table = QueryExecutor::table_from_json(&result.raw_json, "objects", None).unwrap();
+        assert_eq!(table.columns, vec!["name"]);
+        assert!(QueryExecutor::table_from_json(&result.raw_json, "missing", None).is_none());
+
+        let result =
+            QueryExecutor::result_from_json(r#"{"a": [], "return": [{"name": "b"}]}"#, None).unwrap();
+        assert_eq!(result.table_key, "return");
+    }
+
+    #[test]
+    fn test_infer_schema() {
+        let result = json!({
+            "return": [
+                {
+                    "id": "{5A2C9E1F-0000-0000-0000-000000000001}",
+                    "name": "a", "@Volume": -3, "muted": true, "notes": 1
+                },
+                {
+                    "id": "{5A2C9E1F-0000-0000-0000-000000000002}",
+                    "name": "b", "@Volume": null, "muted": false, "notes": "x"
+                }
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        let schema = table.infer_schema();
+        let kind = |name: &str| schema.iter().find(|c| c.name == name).unwrap().kind;
+        assert_eq!(kind("id"), ColumnType::Guid);
+        assert_eq!(kind("name"), ColumnType::String);
+        assert_eq!(kind("@Volume"), ColumnType::Number);
+        assert_eq!(kind("muted"), ColumnType::Bool);
+        assert_eq!(kind("notes"), ColumnType::String);
+        assert_eq!(kind("@Volume").badge(), "num");
+        assert_eq!(
+            serde_json::to_value(&schema[0]).unwrap(),
+            json!({"name": "@Volume", "type": "number"})
+        );
+    }
+
+    #[test]
+    fn test_postprocess_rules() {
+        let result = json!({
+            "return": [
+                {"name": "Foot", "type": "Sound", "id": "{A}"},
+                {"name": "Hit", "type": "Sound", "id": "{B}"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        let rule = |op, column: &str, other: &str, target: &str| PostRule {
+            op,
+            column: column.to_string(),
+            other: other.to_string(),
+            separator: " / ".to_string(),
+            target: target.to_string(),
+        };
+        let rules = [
+            rule(PostOp::Concat, "type", "name", "label"),
+            rule(PostOp::Drop, "id", "", ""),
+            rule(PostOp::Rename, "name", "", "Name"),
+            // 无效的规则被忽略
+            rule(PostOp::Rename, "type", "", ""),
+        ];
+        let table = postprocess(table, &rules);
+
+        let mut columns = table.columns.clone();
+        columns.sort();
+        assert_eq!(columns, vec!["Name", "label", "type"]);
+        assert_eq!(table.rows[0]["label"], "Sound / Foot");
+        assert_eq!(table.rows[1]["Name"], "Hit");
+        assert_eq!(table.typed_rows[1]["label"], json!("Sound / Hit"));
+        assert!(!table.rows[0].contains_key("id"));
+        assert!(!table.typed_rows[0].contains_key("name"));
+    }
+
+    #[test]
+    fn test_upsert_by_key() {
+        let first = json!({
+            "return": [
+                {"id": "a", "name": "Foot", "@Volume": -3},
+                {"id": "b", "name": "Hit", "@Volume": 0}
+            ]
+        });
+        let second = json!({
+            "return": [
+                {"id": "b", "name": "Hit", "@Volume": -6},
+                {"id": "c", "name": "Jump", "@Volume": 0}
+            ]
+        });
+        let mut table = QueryExecutor::parse_table_data(&first, TABLE_KEY, None).unwrap();
+        let update = QueryExecutor::parse_table_data(&second, TABLE_KEY, None).unwrap();
+        let mut changed = table.upsert(update, "id");
+        changed.sort();
+
+        assert_eq!(table.rows.len(), 3);
+        assert_eq!(table.total_rows, 3);
+        assert_eq!(table.rows[0]["@Volume"], "-3");
+        assert_eq!(table.rows[1]["@Volume"], "-6");
+        assert_eq!(table.typed_rows[1]["@Volume"], json!(-6));
+        assert_eq!(table.rows[2]["name"], "Jump");
+        assert_eq!(
+            changed,
+            vec![
+                (1, "@Volume".to_string()),
+                (2, "@Volume".to_string()),
+                (2, "id".to_string()),
+                (2, "name".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_by_guid() {
+        let a = "{5A2C9E1F-0000-0000-0000-00000000000A}";
+        let b = "{5A2C9E1F-0000-0000-0000-00000000000B}";
+        let c = "{5A2C9E1F-0000-0000-0000-00000000000C}";
+        let snapshot = json!({
+            "return": [
+                {"id": a, "name": "Foot", "@Volume": -3},
+                {"id": b, "name": "Jump", "@Volume": 0}
+            ]
+        });
+        let current = json!({
+            "return": [
+                {"id": b, "name": "Jump", "@Volume": -6},
+                {"id": c, "name": "Land", "@Volume": 0}
+            ]
+        });
+        let snapshot = QueryExecutor::parse_table_data(&snapshot, TABLE_KEY, None).unwrap();
+        let current = QueryExecutor::parse_table_data(&current, TABLE_KEY, None).unwrap();
+
+        assert_eq!(
+            current.diff(&snapshot),
+            vec![
+                RowDiff::Changed {
+                    index: 0,
+                    snapshot_index: 1,
+                    columns: vec!["@Volume".to_string()],
+                },
+                RowDiff::Added(1),
+                RowDiff::Removed(0),
+            ]
+        );
+        assert!(current.diff(&current).is_empty());
+    }
+
+    #[test]
+    fn test_row_guid() {
+        let guid = "{5A2C9E1F-0000-0000-0000-000000000001}";
+        let parent = "{5A2C9E1F-0000-0000-0000-000000000002}";
+        let result = json!({
+            "return": [
+                {"name": "a", "parent": parent, "id": guid},
+                {"name": "b", "parent": parent},
+                {"name": "c"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(table.row_guid(0), Some(guid));
+        assert_eq!(table.row_guid(1), Some(parent));
+        assert_eq!(table.row_guid(2), None);
+        assert_eq!(table.row_guid(3), None);
+    }
+
+    #[test]
+    fn test_parse_table_max_rows() {
+        let result = json!({
+            "return": [
+                {"name": "a"},
+                1,
+                {"name": "b"},
+                {"name": "c", "@Volume": -3}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, Some(2)).unwrap();
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.total_rows, 4);
+        assert!(table.is_truncated());
+        assert_eq!(table.columns, vec!["name", VALUE_COLUMN]);
+        assert_eq!(table.rows[1][VALUE_COLUMN], "1");
+
+        let result = QueryExecutor::build_result(result, Some(2), &[]);
+        assert_eq!(result.count, 4);
+
+        let table = QueryExecutor::table_from_json(&result.raw_json, TABLE_KEY, Some(5)).unwrap();
+        assert!(!table.is_truncated());
+    }
+
+    #[test]
+    fn test_parse_table_columns_case_sensitive() {
+        let result = json!({
+            "return": [
+                {"name": "a"},
+                {"Name": "b", "name": "c"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(table.columns, vec!["name", "Name"]);
+        assert_eq!(table.rows[0]["Name"], "");
+        assert_eq!(table.rows[1]["Name"], "b");
+        assert_eq!(table.rows[1]["name"], "c");
+        assert!(!table.typed_rows[0].contains_key("Name"));
+    }
+
+    #[test]
+    fn test_parse_table_nested_objects_not_flattened() {
+        let result = json!({
+            "return": [
+                {"parent": {"name": "Bus"}, "parent.name": "flat"},
+                "not an object"
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(table.columns, vec!["parent", "parent.name", VALUE_COLUMN]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.rows[0]["parent"], r#"{"name":"Bus"}"#);
+        assert_eq!(table.rows[0]["parent.name"], "flat");
+        assert_eq!(table.rows[1]["parent"], "");
+        assert_eq!(table.rows[1][VALUE_COLUMN], "not an object");
+    }
+
+    #[test]
+    fn test_parse_table_value_column_conflict() {
+        let result = json!({
+            "return": [
+                {"name": "a", "value": 1},
+                {"name": "b", "value_2": 2},
+                "c"
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(table.columns, vec!["name", "value", "value_2", "value_3"]);
+        assert_eq!(table.rows[0]["value"], "1");
+        assert_eq!(table.rows[0]["value_3"], "");
+        assert_eq!(table.rows[2]["value"], "");
+        assert_eq!(table.rows[2]["value_3"], "c");
+        assert_eq!(table.typed_rows[2]["value_3"], json!("c"));
+    }
+
+    #[test]
+    fn test_order_columns() {
+        let mut table = sample_table();
+        let original = table.columns.clone();
+        assert_eq!(original, vec!["@Volume", "name"]);
+
+        table.order_columns(&["name".to_string(), "missing".to_string()]);
+        assert_eq!(table.columns, vec!["name", "@Volume"]);
+
+        table.order_columns(&["Volume".to_string()]);
+        assert_eq!(table.columns, vec!["@Volume", "name"]);
+    }
+
+    #[test]
+    fn test_is_guid() {
+        assert!(is_guid("{A1B2C3D4-0000-4a5b-9C8D-0123456789ab}"));
+        assert!(is_guid("a1b2c3d4-0000-4a5b-9c8d-0123456789ab"));
+        assert!(!is_guid("{A1B2C3D4-0000-4a5b-9C8D-0123456789ab"));
+        assert!(!is_guid("{G1B2C3D4-0000-4a5b-9C8D-0123456789ab}"));
+        assert!(!is_guid("{A1B2C3D4-0000-4a5b-9C8D}"));
+        assert!(!is_guid("Footstep"));
+        assert!(!is_guid(""));
+    }
+
+    #[test]
+    fn test_to_string_pretty_depth() {
+        let value = json!({
+            "return": [{"name": "Foot", "notes": {"a": 1}}, {"name": "Jump", "tags": []}],
+            "count": 2
+        });
+        assert_eq!(to_string_pretty_depth(&value, 10), to_string_pretty(&value).unwrap());
+        assert_eq!(
+            to_string_pretty_depth(&value, 1),
+            "{\n  \"count\": 2,\n  \"return\": [...]\n}"
+        );
+        let two = to_string_pretty_depth(&value, 2);
+        assert!(two.contains("\"return\": [\n    {...},\n    {...}\n  ]"));
+        assert_eq!(to_string_pretty_depth(&value, 0), "{...}");
+        assert_eq!(to_string_pretty_depth(&json!([]), 0), "[]");
+        assert_eq!(to_string_pretty_depth(&json!("a\"b"), 0), r#""a\"b""#);
+    }
+
+    #[test]
+    fn test_is_url() {
+        assert!(is_url("https://www.audiokinetic.com/library/"));
+        assert!(is_url("HTTP://localhost:8090/waapi"));
+        assert!(is_url("file:///C:/Sounds/foot.wav"));
+        assert!(!is_url("https://"));
+        assert!(!is_url("see https://example.com"));
+        assert!(!is_url("Footstep"));
+    }
+
+    #[test]
+    fn test_is_file_path() {
+        assert!(is_file_path(r"C:\Projects\Game\Originals\SFX\foot.wav"));
+        assert!(is_file_path("D:/Sounds/foot.wav"));
+        assert!(is_file_path(r"\\server\share\foot.wav"));
+        assert!(is_file_path("/Users/sound/Game/foot.wav"));
+        assert!(!is_file_path(r"\Actor-Mixer Hierarchy\Default Work Unit\Foot"));
+        assert!(!is_file_path("/foot"));
+        assert!(!is_file_path("C:"));
+        assert!(!is_file_path("1/2"));
+        assert!(!is_file_path("C:\\a\nb"));
+    }
+
+    #[test]
+    fn test_value_to_string() {
+        assert_eq!(QueryExecutor::value_to_string(&json!("test")), "test");
+        assert_eq!(QueryExecutor::value_to_string(&json!(42)), "42");
+        assert_eq!(QueryExecutor::value_to_string(&json!(true)), "true");
+        assert_eq!(QueryExecutor::value_to_string(&json!(null)), "null");
+    }
+
+    fn sample_table() -> TableData {
+        let result = json!({
+            "return": [
+                {"name": "Footstep", "@Volume": -3},
+                {"name": "Jump", "@Volume": -6.5},
+                {"name": "Music", "@Volume": null},
+                {"name": ""}
+            ]
+        });
+        QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap()
+    }
+
+    #[test]
+    fn test_group_indices() {
+        let result = json!({
+            "return": [
+                {"name": "A", "parent": "Foley"},
+                {"name": "B", "parent": "Music"},
+                {"name": "C", "parent": "Foley"},
+                {"name": "D"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(
+            table.group_indices("parent", &[0, 1, 2, 3]),
+            vec![
+                ("Foley".to_string(), vec![0, 2]),
+                ("Music".to_string(), vec![1]),
+                (String::new(), vec![3]),
+            ]
+        );
+        assert_eq!(
+            table.group_indices("parent", &[2, 1, 0]),
+            vec![("Foley".to_string(), vec![2, 0]), ("Music".to_string(), vec![1])]
+        );
+        assert!(table.group_indices("parent", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_filtered_indices() {
+        let table = sample_table();
+        assert_eq!(table.filtered_indices(""), vec![0, 1, 2, 3]);
+        assert_eq!(table.filtered_indices("JUMP"), vec![1]);
+        assert!(table.filtered_indices("nothing").is_empty());
+    }
+
+    #[test]
+    fn test_aggregate_numeric_column() {
+        let table = sample_table();
+        assert!(table.is_numeric_column("@Volume"));
+        assert!(!table.is_numeric_column("name"));
+
+        let all = table.filtered_indices("");
+        assert_eq!(table.aggregate("@Volume", &all, Aggregate::Sum), Some(-9.5));
+        assert_eq!(table.aggregate("@Volume", &all, Aggregate::Avg), Some(-4.75));
+        assert_eq!(table.aggregate("@Volume", &all, Aggregate::Min), Some(-6.5));
+        assert_eq!(table.aggregate("@Volume", &all, Aggregate::Max), Some(-3.0));
+        assert_eq!(table.aggregate("@Volume", &all, Aggregate::Count), Some(2.0));
+
+        // 聚合只统计筛选后的行
+        let filtered = table.filtered_indices("foot");
+        assert_eq!(table.aggregate("@Volume", &filtered, Aggregate::Sum), Some(-3.0));
+        assert_eq!(table.aggregate("@Volume", &[2], Aggregate::Max), None);
+    }
+
+    #[test]
+    fn test_compute_stats() {
+        let table = sample_table();
+        let stats = table.compute_stats();
+        let volume = stats.iter().find(|s| s.column == "@Volume").unwrap();
+        assert_eq!(volume.count, 2);
+        assert_eq!(volume.distinct, 2);
+        assert_eq!(volume.min, Some(-6.5));
+        assert_eq!(volume.max, Some(-3.0));
+        assert_eq!(volume.avg, Some(-4.75));
+
+        let name = stats.iter().find(|s| s.column == "name").unwrap();
+        assert_eq!(name.count, 3);
+        assert_eq!(name.distinct, 3);
+        assert_eq!(name.min, None);
+
+        let mut output = Vec::new();
+        table.write_stats_csv(&mut output, CsvFormat::default()).unwrap();
+        let text = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "column,count,min,max,avg,distinct");
+        assert!(lines.contains(&"@Volume,2,-6.5,-3,-4.75,2"));
+        assert!(lines.contains(&"name,3,,,,3"));
+    }
+
+    #[test]
+    fn test_top_values() {
+        let result = json!({
+            "return": [
+                {"parent": "Foley"},
+                {"parent": "Music"},
+                {"parent": "Foley"},
+                {"parent": "Ambience"},
+                {"parent": null},
+                {"parent": ""}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(
+            table.top_values("parent", 2),
+            vec![("Foley".to_string(), 2), ("Ambience".to_string(), 1)]
+        );
+        assert_eq!(table.top_values("parent", 10).len(), 3);
+        assert!(table.top_values("missing", 5).is_empty());
+        assert_eq!(table.column_stats("parent").count, 4);
+    }
+
+    #[test]
+    fn test_non_empty_count() {
+        let table = sample_table();
+        let all = table.filtered_indices("");
+        assert_eq!(table.non_empty_count("name", &all), 3);
+    }
+
+    #[test]
+    fn test_select_columns() {
+        let table = sample_table();
+        let columns = vec!["name".to_string(), "missing".to_string(), "@Volume".to_string()];
+        let selected = table.select_columns(&columns);
+        assert_eq!(selected.columns, vec!["name", "@Volume"]);
+        assert_eq!(selected.rows.len(), table.rows.len());
+        assert!(selected.rows.iter().all(|row| row.len() <= 2));
+        assert!(selected.to_csv_string().starts_with("name,@Volume\n"));
+        assert_eq!(table.select_columns(&[]).columns.len(), 0);
+    }
+
+    #[test]
+    fn test_duplicate_keys_keep_last_value() {
+        let text = r#"{"return": [{"name": "first", "id": "a", "name": "second"}, {"id": "b"}]}"#;
+        let table = QueryExecutor::table_from_json(text, "return", None).unwrap();
+        assert_eq!(table.columns, vec!["id", "name"]);
+        assert_eq!(table.rows[0]["name"], "second");
+        assert_eq!(table.rows[1]["name"], "");
+
+        let result = QueryExecutor::result_from_json(text, None).unwrap();
+        assert_eq!(result.duplicate_keys, vec!["name"]);
+        let result = QueryExecutor::result_from_json(r#"{"return": [{"id": "a"}]}"#, None);
+        assert!(result.unwrap().duplicate_keys.is_empty());
+    }
+
+    #[test]
+    fn test_subscribe_unsupported() {
+        let mut executor = QueryExecutor::offline();
+        let result = executor.subscribe("ak.wwise.core.object.nameChanged", Box::new(|_| {}));
+        assert!(matches!(result, Err(WaapiError::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_offline_connection_state() {
+        let mut executor = QueryExecutor::offline();
+        assert_eq!(executor.connection_state(), ConnectionState::Offline);
+        assert!(executor.execute("$ from type Event").is_ok());
+        assert!(executor.ping().is_ok());
+        assert_eq!(executor.connection_state(), ConnectionState::Offline);
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let mut executor = QueryExecutor::new();
+        assert_eq!(executor.idle_remaining(), None);
+        executor.set_idle_timeout(Some(Duration::ZERO));
+        assert_eq!(executor.idle_remaining(), Some(Duration::ZERO));
+        executor.disconnect_idle();
+        assert_eq!(executor.connection_state(), ConnectionState::Idle);
+        assert_eq!(executor.idle_remaining(), None);
+
+        let mut offline = QueryExecutor::offline();
+        offline.set_idle_timeout(Some(Duration::ZERO));
+        assert_eq!(offline.idle_remaining(), None);
+        offline.disconnect_idle();
+        assert_eq!(offline.connection_state(), ConnectionState::Offline);
+        assert!(offline.execute("$ from type Event").is_ok());
+    }
+
+    #[test]
+    fn test_large_result_parsed_in_background() {
+        let items: Vec<Value> = (0..BACKGROUND_PARSE_ROWS + 1)
+            .map(|i| json!({"name": format!("object_{}", i)}))
+            .collect();
+        let result = QueryExecutor::build_result(json!({ "return": items }), None, &[]);
+        assert!(result.table_data.is_none());
+        assert_eq!(result.count, BACKGROUND_PARSE_ROWS + 1);
+
+        let pending = result.pending_table.unwrap();
+        let table = loop {
+            if let Some(table) = pending.poll() {
+                break table;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(table.unwrap().rows.len(), BACKGROUND_PARSE_ROWS + 1);
+    }
+
+    fn csv_output(table: &TableData, safe: bool) -> String {
+        let mut output = Vec::new();
+        table.write_csv(&mut output, CsvFormat::default(), safe).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_distinct_values() {
+        let result = json!({
+            "return": [
+                {"id": "{A}", "name": "Foot"},
+                {"id": "{B}", "name": ""},
+                {"id": "{A}", "name": "Foot"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(table.distinct_values("id"), vec!["{A}", "{B}"]);
+        assert_eq!(table.distinct_values("name"), vec!["Foot"]);
+        assert!(table.distinct_values("missing").is_empty());
+    }
+
+    #[test]
+    fn test_to_csv_and_tsv_string() {
+        let mut first = HashMap::new();
+        first.insert("name".to_string(), "Footstep, heavy".to_string());
+        first.insert("@Volume".to_string(), "-3".to_string());
+        let mut second = HashMap::new();
+        second.insert("name".to_string(), "Jump".to_string());
+        let table = TableData {
+            columns: vec!["name".to_string(), "@Volume".to_string()],
+            rows: vec![first, second],
+            typed_rows: Vec::new(),
+            total_rows: 2,
+        };
+
+        assert_eq!(
+            table.to_csv_string(),
+            "name,@Volume\n\"Footstep, heavy\",-3\nJump,\n"
+        );
+        assert_eq!(
+            table.to_tsv_string(),
+            "name\t@Volume\nFootstep, heavy\t-3\nJump\t\n"
+        );
+    }
+
+    #[test]
+    fn test_filtered_to_json_string() {
+        let table = sample_table().filtered(&[1, 3]);
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(
+            table.to_json_string(),
+            "[\n  {\n    \"@Volume\": -6.5,\n    \"name\": \"Jump\"\n  },\n  {\n    \"name\": \"\"\n  }\n]"
+        );
+    }
+
+    #[test]
+    fn test_to_ndjson_string() {
+        let table = sample_table();
+        let text = table.to_ndjson_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), table.typed_rows.len());
+        assert!(text.ends_with('\n'));
+        for (line, row) in lines.iter().zip(&table.typed_rows) {
+            let value: Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["name"], row["name"]);
+        }
+        assert_eq!(lines[1], r#"{"@Volume":-6.5,"name":"Jump"}"#);
+
+        let empty = sample_table().filtered(&[]);
+        let path = std::env::temp_dir().join("waql_tool_empty_test.ndjson");
+        empty.export_to_ndjson(&path).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_export_to_parquet() {
+        use arrow::array::{Array, Float64Array, StringArray};
+        use arrow::datatypes::DataType;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let result = json!({
+            "return": [
+                {"name": "Footstep", "@Volume": -3, "notes": "a"},
+                {"name": "Jump", "@Volume": null, "notes": 1},
+                {"name": "Music"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        let path = std::env::temp_dir().join("waql_tool_test.parquet");
+        table.export_to_parquet(&path).unwrap();
+
+        let file = std::fs::File::open(&path).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap().build().unwrap();
+        let batches: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.num_rows(), 3);
+        let schema = batch.schema();
+        let column = |name: &str| batch.column(schema.index_of(name).unwrap()).clone();
+
+        let volume = column("@Volume");
+        assert_eq!(volume.data_type(), &DataType::Float64);
+        let volume = volume.as_any().downcast_ref::<Float64Array>().unwrap();
+        assert_eq!(volume.value(0), -3.0);
+        assert!(volume.is_null(1));
+        assert!(volume.is_null(2));
+
+        // 类型不一致的列写为文本
+        let notes = column("notes");
+        assert_eq!(notes.data_type(), &DataType::Utf8);
+        let notes = notes.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(notes.value(0), "a");
+        assert_eq!(notes.value(1), "1");
+        assert!(notes.is_null(2));
+    }
+
+    #[test]
+    fn test_to_markdown_string() {
+        let result = json!({
+            "return": [
+                {"name": "a|b", "notes": "line 1\nline 2"},
+                {"name": "Jump"}
+            ]
+        });
+        let table = QueryExecutor::parse_table_data(&result, TABLE_KEY, None).unwrap();
+        assert_eq!(
+            table.to_markdown_string(),
+            "| name | notes |\n| --- | --- |\n| a\\|b | line 1<br>line 2 |\n| Jump |  |\n"
+        );
+        assert_eq!(sample_table().filtered(&[]).to_markdown_string().lines().count(), 2);
+    }
+
+    #[test]
+    fn test_escape_csv_formula() {
+        assert_eq!(escape_csv_formula("=SUM(A1:A2)"), "'=SUM(A1:A2)");
+        assert_eq!(escape_csv_formula("+cmd"), "'+cmd");
+        assert_eq!(escape_csv_formula("-cmd"), "'-cmd");
+        assert_eq!(escape_csv_formula("@SUM(1)"), "'@SUM(1)");
+        assert_eq!(escape_csv_formula("\t=1"), "'\t=1");
+        assert_eq!(escape_csv_formula("\r=1"), "'\r=1");
+        assert_eq!(escape_csv_formula("Footstep"), "Footstep");
+        assert_eq!(escape_csv_formula("-6.5"), "-6.5");
+        assert_eq!(escape_csv_formula("+3"), "+3");
+        assert_eq!(escape_csv_formula(""), "");
+    }
+
+    #[test]
+    fn test_write_csv_safe() {
+        let mut row = HashMap::new();
+        row.insert("name".to_string(), "=HYPERLINK(\"x\")".to_string());
+        row.insert("@Volume".to_string(), "-3".to_string());
+        let table = TableData {
+            columns: vec!["name".to_string(), "@Volume".to_string()],
+            rows: vec![row],
+            typed_rows: Vec::new(),
+            total_rows: 1,
+        };
+
+        let safe = csv_output(&table, true);
+        assert!(safe.starts_with("name,@Volume\n"));
+        assert!(safe.contains("\"'=HYPERLINK(\"\"x\"\")\",-3"));
+
+        let raw = csv_output(&table, false);
+        assert!(raw.starts_with("name,@Volume\n"));
+        assert!(raw.contains("\"=HYPERLINK(\"\"x\"\")\",-3"));
+    }
+
+    #[test]
+    fn test_write_csv_semicolon() {
+        let mut row = HashMap::new();
+        row.insert("name".to_string(), "Foot; step".to_string());
+        row.insert("@Volume".to_string(), "-3.5".to_string());
+        let table = TableData {
+            columns: vec!["name".to_string(), "@Volume".to_string()],
+            rows: vec![row],
+            typed_rows: Vec::new(),
+            total_rows: 1,
+        };
+
+        let format = CsvFormat {
+            delimiter: CsvDelimiter::Semicolon,
+            ..CsvFormat::default()
+        };
+        let mut output = Vec::new();
+        table.write_csv(&mut output, format, true).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "name;@Volume\n\"Foot; step\";-3.5\n"
+        );
+
+        let format = CsvFormat {
+            delimiter: CsvDelimiter::Semicolon,
+            quoting: CsvQuoting::NonNumeric,
+            bom: false,
+        };
+        let mut output = Vec::new();
+        table.write_csv(&mut output, format, true).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\"name\";\"@Volume\"\n\"Foot; step\";-3.5\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_bom() {
+        let mut row = HashMap::new();
+        row.insert("name".to_string(), "脚步".to_string());
+        let table = TableData {
+            columns: vec!["name".to_string()],
+            rows: vec![row],
+            typed_rows: Vec::new(),
+            total_rows: 1,
+        };
+
+        let format = CsvFormat {
+            bom: true,
+            ..CsvFormat::default()
+        };
+        let mut output = Vec::new();
+        table.write_csv(&mut output, format, true).unwrap();
+        assert!(output.starts_with(UTF8_BOM));
+        assert_eq!(&output[UTF8_BOM.len()..], "name\n脚步\n".as_bytes());
+        assert!(!table.to_csv_string().starts_with('\u{feff}'));
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("All sounds"), "All sounds");
+        assert_eq!(sanitize_file_name("a/b\\c: d?"), "a_b_c_ d_");
+        assert_eq!(sanitize_file_name("  report.. "), "report");
+        assert_eq!(sanitize_file_name(" ... "), "query");
+        assert_eq!(sanitize_file_name("脚步 | 音量"), "脚步 _ 音量");
+    }
+
+    #[test]
+    fn test_health_check_backoff() {
+        let mut health = HealthCheck::new(5.0, 2.0, 30.0);
+        assert!(health.is_due(0.0));
+
+        health.record(true, 0.0);
+        assert!(!health.is_due(4.0));
+        assert!(health.is_due(5.0));
+
+        health.record(false, 5.0);
+        assert_eq!(health.remaining(5.0), 10.0);
+        health.record(false, 15.0);
+        assert_eq!(health.remaining(15.0), 20.0);
+        health.record(false, 35.0);
+        assert_eq!(health.remaining(35.0), 30.0);
+        assert_eq!(health.delay(), 30.0);
+
+        health.trigger();
+        assert!(health.is_due(36.0));
+        health.record(true, 36.0);
+        assert_eq!(health.remaining(36.0), 5.0);
+    }
+
+    #[test]
+    fn test_offline_wwise_version() {
+        let mut executor = QueryExecutor::offline();
+        assert!(executor.wwise_version().unwrap().contains("offline"));
+    }
+
+    #[test]
+    fn test_offline_project_info() {
+        let mut executor = QueryExecutor::offline();
+        let info = executor.project_info().unwrap();
+        assert_eq!(info.name, "SampleProject");
+        assert!(info.languages.iter().any(|l| l == "English(US)"));
+        assert_eq!(executor.project_info().unwrap(), info);
+    }
+
+    #[test]
+    fn test_result_from_json() {
+        let result =
+            QueryExecutor::result_from_json(r#"{"return": [{"name": "a"}, {"name": "b"}]}"#, None)
+                .unwrap();
+        assert_eq!(result.count, 2);
+        assert_eq!(result.table_data.unwrap().columns, vec!["name".to_string()]);
+
+        assert!(QueryExecutor::result_from_json("not json", None).is_err());
+    }
+
+    #[test]
+    fn test_format_json_skips_pretty_print_for_large_values() {
+        let small = json!({"return": [{"name": "a"}]});
+        assert!(QueryExecutor::format_json(&small).contains('\n'));
+
+        let items: Vec<Value> = (0..PRETTY_PRINT_LIMIT / 16)
+            .map(|i| json!({"name": format!("object_{}", i)}))
+            .collect();
+        let large = json!({ "return": items });
+        assert!(!QueryExecutor::format_json(&large).contains('\n'));
     }
 }
@@ -0,0 +1,250 @@
+//! 查询报告模块
+//!
+//! 将查询语句、结果表格和环境信息生成为 Markdown 或独立的 HTML 报告
+
+use std::ops::Range;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::query_executor::TableData;
+
+/// 将时间格式化为 `YYYY-MM-DD HH:MM:SS UTC`
+fn format_utc(time: SystemTime) -> String {
+    let secs = time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let (days, rem) = ((secs / 86_400) as i64, secs % 86_400);
+    // 公历日期换算，见 Howard Hinnant 的 civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+/// 转义 HTML 文本中的特殊字符
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped
+}
+
+/// 报告文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    /// Markdown 文档
+    #[default]
+    Markdown,
+    /// 带样式和查询语法高亮的 HTML 页面
+    Html,
+}
+
+impl ReportFormat {
+    /// 所有格式
+    pub const ALL: [ReportFormat; 2] = [ReportFormat::Markdown, ReportFormat::Html];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "Markdown",
+            ReportFormat::Html => "HTML",
+        }
+    }
+
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+        }
+    }
+}
+
+/// HTML 报告的样式，类名与 [`QueryReport::query_spans`] 中使用的类名对应
+const REPORT_CSS: &str = "\
+body { font-family: sans-serif; margin: 2em; color: #222; }
+pre.query { background: #f6f8fa; padding: 1em; border-radius: 4px; white-space: pre-wrap; }
+table { border-collapse: collapse; font-size: 0.9em; }
+th, td { border: 1px solid #d0d7de; padding: 4px 8px; text-align: left; }
+th { background: #f6f8fa; }
+tr:nth-child(even) td { background: #fbfbfb; }
+.keyword { color: #8250df; }
+.type { color: #0550ae; }
+.special { color: #cf222e; font-weight: bold; }
+.string { color: #0a3069; }
+.number { color: #953800; }
+.comment { color: #6e7781; }
+";
+
+/// 查询语句、结果表格和环境信息组成的报告
+pub struct QueryReport<'a> {
+    /// WAQL 查询语句
+    pub query: &'a str,
+    /// 查询语句中需要高亮的字节范围和 CSS 类名（`keyword`、`type`、`special`、`string`、
+    /// `number`、`comment`），由调用方分词得到，只用于 HTML 报告
+    pub query_spans: Vec<(Range<usize>, &'static str)>,
+    /// 生成时间
+    pub generated_at: SystemTime,
+    /// 连接名称
+    pub connection: String,
+    /// Wwise 项目名称
+    pub project: Option<String>,
+    /// 结果表格
+    pub table: &'a TableData,
+}
+
+impl QueryReport<'_> {
+    /// 按指定格式生成报告文本
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.to_markdown(),
+            ReportFormat::Html => self.to_html(),
+        }
+    }
+
+    /// 报告开头的信息项：名称和值
+    fn details(&self) -> Vec<(&'static str, String)> {
+        let mut details = vec![
+            ("Generated", format_utc(self.generated_at)),
+            ("Connection", self.connection.clone()),
+        ];
+        if let Some(project) = &self.project {
+            details.push(("Project", project.clone()));
+        }
+        let rows = if self.table.is_truncated() {
+            format!("{} (of {})", self.table.rows.len(), self.table.total_rows)
+        } else {
+            self.table.rows.len().to_string()
+        };
+        details.push(("Rows", rows));
+        details
+    }
+
+    /// 生成 Markdown 报告，结果表格见 [`TableData::to_markdown_string`]
+    fn to_markdown(&self) -> String {
+        let mut output = String::from("# WAQL Report\n\n");
+        for (name, value) in self.details() {
+            output.push_str(&format!("- **{}**: {}\n", name, value));
+        }
+        output.push_str(&format!("\n## Query\n\n```\n{}\n```\n", self.query.trim_end()));
+        output.push_str("\n## Result\n\n");
+        output.push_str(&self.table.to_markdown_string());
+        output
+    }
+
+    /// 生成独立的 HTML 报告，样式内嵌在页面中
+    fn to_html(&self) -> String {
+        let mut query = String::new();
+        let mut offset = 0;
+        for (range, class) in &self.query_spans {
+            if range.start < offset || range.end > self.query.len() {
+                continue;
+            }
+            query.push_str(&escape_html(&self.query[offset..range.start]));
+            query.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                class,
+                escape_html(&self.query[range.clone()])
+            ));
+            offset = range.end;
+        }
+        query.push_str(&escape_html(&self.query[offset..]));
+
+        let details: String = self
+            .details()
+            .into_iter()
+            .map(|(name, value)| format!("<li><b>{}</b>: {}</li>\n", name, escape_html(&value)))
+            .collect();
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>WAQL Report</title>\n<style>\n{}</style>\n</head>\n<body>\n\
+             <h1>WAQL Report</h1>\n<ul>\n{}</ul>\n<h2>Query</h2>\n\
+             <pre class=\"query\">{}</pre>\n<h2>Result</h2>\n{}</body>\n</html>\n",
+            REPORT_CSS,
+            details,
+            query.trim_end(),
+            self.table.to_html_table()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query_executor::{QueryExecutor, TABLE_KEY};
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01 00:00:00 UTC");
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(format_utc(time), "2023-11-14 22:13:20 UTC");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_782_400);
+        assert_eq!(format_utc(leap_day), "2000-02-29 00:00:00 UTC");
+    }
+
+    #[test]
+    fn test_query_report() {
+        let result = json!({
+            "return": [
+                {"name": "Footstep", "@Volume": -3},
+                {"name": "Jump", "@Volume": -6.5},
+                {"name": "Music", "@Volume": null},
+                {"name": ""}
+            ]
+        });
+        let table = QueryExecutor::table_from_json(&result.to_string(), TABLE_KEY, None).unwrap();
+        let report = QueryReport {
+            query: "$ from type Sound where name : \"<a>\"",
+            query_spans: vec![(0..1, "special"), (12..17, "type")],
+            generated_at: UNIX_EPOCH,
+            connection: "Local".to_string(),
+            project: Some("SampleProject".to_string()),
+            table: &table,
+        };
+
+        let markdown = report.render(ReportFormat::Markdown);
+        assert!(markdown.contains("- **Generated**: 1970-01-01 00:00:00 UTC"));
+        assert!(markdown.contains("- **Project**: SampleProject"));
+        assert!(markdown.contains("- **Rows**: 4"));
+        assert!(markdown.contains("```\n$ from type Sound where name : \"<a>\"\n```"));
+        assert!(markdown.ends_with(&table.to_markdown_string()));
+
+        let html = report.render(ReportFormat::Html);
+        assert!(html.contains(
+            "<span class=\"special\">$</span> from type <span class=\"type\">Sound</span> \
+             where name : &quot;&lt;a&gt;&quot;</pre>"
+        ));
+        assert!(html.contains("<th>@Volume</th><th>name</th>"));
+        assert!(html.contains("<td>Footstep</td>"));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(
+            escape_html("<a href=\"x\">Tom & Jerry's</a>"),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;"
+        );
+        assert_eq!(escape_html("声音"), "声音");
+    }
+}
@@ -0,0 +1,355 @@
+//! 查询传输模块
+//!
+//! 抽象 WAQL 查询的发送方式，便于在真实的 WAAPI 连接
+//! 和内置示例数据（离线演示模式）之间切换
+//!
+//! 目前只有一次请求一次响应的 HTTP 传输。订阅 WAAPI 主题需要 WAMP (WebSocket) 连接，
+//! 将来新增的传输实现 [`Transport::subscribe`] 即可，调用方不需要改动；
+//! 重建传输（切换连接或重连）后由执行器重新订阅
+
+use serde_json::{json, Map, Value};
+
+use crate::waapi::{
+    ClientConfig, WaapiError, WaapiHttpClient, PROJECT_INFO_URI, WWISE_INFO_URI,
+};
+
+/// WAAPI 主题推送的回调，参数为推送的数据
+// 订阅的扩展点，界面中还没有调用方
+#[allow(dead_code)]
+pub type TopicHandler = Box<dyn FnMut(Value) + Send>;
+
+/// 内置示例项目数据
+const SAMPLE_PROJECT: &str = include_str!("data/sample_project.json");
+
+/// 查询传输接口
+pub trait Transport: Send {
+    /// 执行 WAQL 查询
+    ///
+    /// # Returns
+    ///
+    /// 返回 WAAPI 响应对象（包含 `return` 数组）或错误信息
+    fn waql_query(
+        &mut self,
+        query: &str,
+        options: Option<Value>,
+    ) -> Result<Map<String, Value>, String>;
+
+    /// 调用任意 WAAPI 接口
+    ///
+    /// # Errors
+    ///
+    /// 调用失败时返回 [`WaapiError`]
+    fn call(
+        &mut self,
+        uri: &str,
+        args: Value,
+        options: Option<Value>,
+    ) -> Result<Value, WaapiError>;
+
+    /// 订阅 WAAPI 主题（如 `ak.wwise.core.object.nameChanged`），推送时调用 `handler`
+    ///
+    /// 默认不支持订阅，HTTP 传输和离线传输都使用默认实现
+    ///
+    /// # Errors
+    ///
+    /// 传输不支持订阅时返回 [`WaapiError::Unsupported`]
+    #[allow(dead_code)]
+    fn subscribe(&mut self, topic: &str, _handler: TopicHandler) -> Result<(), WaapiError> {
+        Err(WaapiError::Unsupported(format!(
+            "订阅 {} 需要 WAMP 连接，当前传输不支持",
+            topic
+        )))
+    }
+
+    /// 创建共用同一连接的传输，用于在后台线程中发送请求
+    fn share(&self) -> Box<dyn Transport>;
+
+    /// 取出最近一次响应中在同一个对象内重复出现的键
+    ///
+    /// 默认没有重复，只有读取原始响应文本的传输才能检查
+    fn take_duplicate_keys(&mut self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// 通过 WAAPI 连接 Wwise 的传输
+#[derive(Default)]
+pub struct WaapiTransport {
+    client: WaapiHttpClient,
+}
+
+impl WaapiTransport {
+    /// 使用指定连接配置创建传输
+    pub fn new(config: ClientConfig) -> Self {
+        Self {
+            client: WaapiHttpClient::new(config),
+        }
+    }
+}
+
+impl Transport for WaapiTransport {
+    fn waql_query(
+        &mut self,
+        query: &str,
+        options: Option<Value>,
+    ) -> Result<Map<String, Value>, String> {
+        self.client
+            .waql_query(query, options)
+            .map_err(|e| e.to_string())
+    }
+
+    fn call(
+        &mut self,
+        uri: &str,
+        args: Value,
+        options: Option<Value>,
+    ) -> Result<Value, WaapiError> {
+        self.client.call(uri, args, options)
+    }
+
+    fn share(&self) -> Box<dyn Transport> {
+        Box::new(Self {
+            client: self.client.share(),
+        })
+    }
+
+    fn take_duplicate_keys(&mut self) -> Vec<String> {
+        self.client.take_duplicate_keys()
+    }
+}
+
+/// 使用内置示例数据的离线传输
+///
+/// 仅支持 `$ from type <Type>` 形式的查询，可附加 `where name : "..."` 或
+/// `where name = "..."` 条件，用于演示、截图和无 Wwise 环境下的试用
+#[derive(Default)]
+pub struct SampleTransport;
+
+impl Transport for SampleTransport {
+    fn waql_query(
+        &mut self,
+        query: &str,
+        options: Option<Value>,
+    ) -> Result<Map<String, Value>, String> {
+        let objects = sample_objects(query)?;
+
+        let fields: Option<Vec<String>> = options
+            .as_ref()
+            .and_then(|o| o.get("return"))
+            .and_then(Value::as_array)
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter_map(|f| f.as_str().map(str::to_string))
+                    .collect()
+            });
+
+        let items = objects
+            .into_iter()
+            .map(|object| match &fields {
+                Some(fields) => project_fields(object, fields),
+                None => object,
+            })
+            .collect();
+
+        let mut result = Map::new();
+        result.insert("return".to_string(), Value::Array(items));
+        Ok(result)
+    }
+
+    fn call(
+        &mut self,
+        uri: &str,
+        _args: Value,
+        _options: Option<Value>,
+    ) -> Result<Value, WaapiError> {
+        match uri {
+            PROJECT_INFO_URI => Ok(sample_project_info()),
+            WWISE_INFO_URI => Ok(json!({
+                "displayName": "Wwise",
+                "version": {"displayName": "v2023.1.0 (offline demo)"}
+            })),
+            _ => Err(WaapiError::Connection(format!("离线模式不支持该接口: {}", uri))),
+        }
+    }
+
+    fn share(&self) -> Box<dyn Transport> {
+        Box::new(SampleTransport)
+    }
+}
+
+/// 示例项目信息
+fn sample_project_info() -> Value {
+    json!({
+        "name": "SampleProject",
+        "platforms": [
+            {"id": "{6E6A0F6B-0000-0000-0000-000000000001}", "name": "Windows"},
+            {"id": "{6E6A0F6B-0000-0000-0000-000000000002}", "name": "PS5"},
+            {"id": "{6E6A0F6B-0000-0000-0000-000000000003}", "name": "Switch"}
+        ],
+        "languages": [
+            {"id": "{8B1A3C2D-0000-0000-0000-000000000001}", "name": "SFX"},
+            {"id": "{8B1A3C2D-0000-0000-0000-000000000002}", "name": "English(US)"},
+            {"id": "{8B1A3C2D-0000-0000-0000-000000000003}", "name": "Chinese(PRC)"}
+        ],
+        "referenceLanguageId": "{8B1A3C2D-0000-0000-0000-000000000002}",
+        "currentPlatformId": "{6E6A0F6B-0000-0000-0000-000000000001}",
+        "currentLanguageId": "{8B1A3C2D-0000-0000-0000-000000000002}"
+    })
+}
+
+/// 按查询语句筛选示例对象
+fn sample_objects(query: &str) -> Result<Vec<Value>, String> {
+    let unsupported = || format!("离线模式不支持该查询: {}", query);
+
+    let rest = query
+        .trim()
+        .strip_prefix('$')
+        .map(str::trim_start)
+        .and_then(|s| s.strip_prefix("from"))
+        .map(str::trim_start)
+        .and_then(|s| s.strip_prefix("type"))
+        .ok_or_else(unsupported)?;
+
+    let rest = rest.trim_start();
+    let type_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let (types, condition) = rest.split_at(type_end);
+    let types: Vec<&str> = types.split(',').filter(|t| !t.is_empty()).collect();
+    if types.is_empty() {
+        return Err(unsupported());
+    }
+
+    let condition = condition.trim();
+    let name_filter = if condition.is_empty() {
+        None
+    } else {
+        Some(parse_name_condition(condition).ok_or_else(unsupported)?)
+    };
+
+    let objects: Vec<Value> =
+        serde_json::from_str(SAMPLE_PROJECT).map_err(|e| format!("示例数据损坏: {}", e))?;
+
+    Ok(objects
+        .into_iter()
+        .filter(|object| {
+            let object_type = object.get("type").and_then(Value::as_str).unwrap_or("");
+            types.iter().any(|t| t.eq_ignore_ascii_case(object_type))
+        })
+        .filter(|object| match &name_filter {
+            Some((value, exact)) => {
+                let name = object.get("name").and_then(Value::as_str).unwrap_or("");
+                if *exact {
+                    name == value
+                } else {
+                    name.to_lowercase().contains(&value.to_lowercase())
+                }
+            }
+            None => true,
+        })
+        .collect())
+}
+
+/// 解析 `where name : "value"` 或 `where name = "value"` 条件
+///
+/// 返回匹配值以及是否为精确匹配
+fn parse_name_condition(condition: &str) -> Option<(String, bool)> {
+    let rest = condition.strip_prefix("where")?.trim_start();
+    let rest = rest.strip_prefix("name")?.trim_start();
+    let (exact, rest) = if let Some(rest) = rest.strip_prefix(':') {
+        (false, rest)
+    } else {
+        (true, rest.strip_prefix('=')?)
+    };
+    let value = rest.trim().strip_prefix('"')?.strip_suffix('"')?;
+    Some((value.to_string(), exact))
+}
+
+/// 只保留指定字段
+fn project_fields(object: Value, fields: &[String]) -> Value {
+    let mut projected = Map::new();
+    if let Value::Object(mut map) = object {
+        for field in fields {
+            if let Some(value) = map.remove(field) {
+                projected.insert(field.clone(), value);
+            }
+        }
+    }
+    Value::Object(projected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waapi::ProjectInfo;
+
+    fn returned(result: &Map<String, Value>) -> &Vec<Value> {
+        result["return"].as_array().unwrap()
+    }
+
+    #[test]
+    fn test_subscribe_unsupported() {
+        let topic = "ak.wwise.core.object.nameChanged";
+        let mut transport = WaapiTransport::default();
+        assert!(matches!(
+            transport.subscribe(topic, Box::new(|_| {})),
+            Err(WaapiError::Unsupported(message)) if message.contains(topic)
+        ));
+        assert!(SampleTransport.subscribe(topic, Box::new(|_| {})).is_err());
+    }
+
+    #[test]
+    fn test_sample_from_type() {
+        let mut transport = SampleTransport;
+        let result = transport.waql_query("$ from type Event", None).unwrap();
+        let items = returned(&result);
+        assert!(!items.is_empty());
+        assert!(items.iter().all(|item| item["type"] == "Event"));
+    }
+
+    #[test]
+    fn test_sample_name_condition() {
+        let mut transport = SampleTransport;
+        let result = transport
+            .waql_query("$ from type Sound where name : \"footstep\"", None)
+            .unwrap();
+        assert_eq!(returned(&result).len(), 2);
+
+        let result = transport
+            .waql_query("$ from type Sound where name = \"Jump_Grunt\"", None)
+            .unwrap();
+        assert_eq!(returned(&result).len(), 1);
+    }
+
+    #[test]
+    fn test_sample_return_projection() {
+        let mut transport = SampleTransport;
+        let result = transport
+            .waql_query("$ from type Bus", Some(json!({"return": ["name", "@Volume"]})))
+            .unwrap();
+        for item in returned(&result) {
+            let object = item.as_object().unwrap();
+            assert_eq!(object.len(), 2);
+            assert!(object.contains_key("name"));
+            assert!(object.contains_key("@Volume"));
+        }
+    }
+
+    #[test]
+    fn test_sample_unsupported_query() {
+        let mut transport = SampleTransport;
+        assert!(transport.waql_query("$ from object \"\\\\Events\"", None).is_err());
+        assert!(transport
+            .waql_query("$ from type Sound where @Volume < 0", None)
+            .is_err());
+    }
+
+    #[test]
+    fn test_sample_project_info() {
+        let mut transport = SampleTransport;
+        let value = transport.call(PROJECT_INFO_URI, json!({}), None).unwrap();
+        let info = ProjectInfo::from_value(&value).unwrap();
+        assert_eq!(info.platforms.len(), 3);
+        assert_eq!(info.current_platform.as_deref(), Some("Windows"));
+        assert!(transport.call("ak.wwise.core.remoteControl.connect", json!({}), None).is_err());
+    }
+}
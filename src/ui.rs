@@ -2,13 +2,100 @@
 //! 
 //! 包含各种 UI 组件的渲染逻辑
 
-use crate::config::UserConfig;
-use crate::query_executor::TableData;
+use crate::config::{
+    parse_tags, CellRule, Connection, EnterBehavior, NullDisplay, QueryTemplate, RuleOp,
+    SavedQuery, UserConfig,
+};
+use crate::query_executor::{
+    is_file_path, is_guid, is_url, query_option, set_query_option, to_string_pretty_depth,
+    Aggregate, ColumnStats, ConnectionState, CsvDelimiter, CsvQuoting, ColumnSchema, Exchange,
+    ExportFormat, PostOp, PostRule, ReportFormat, RowDiff, RpcRequest, TableData,
+};
+use crate::waapi::ProjectInfo;
+use egui::text::{CCursor, CCursorRange, LayoutJob, TextFormat};
 use egui::{TextBuffer, TextEdit};
-use egui_code_editor::{ColorTheme, Completer, Syntax, Token};
+use egui_code_editor::{ColorTheme, Syntax, TokenType};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::Duration;
+use waql_tool::{
+    fuzzy_match, in_select_clause, quote_string, search_palette, token_at_caret, tokenize_json,
+    tokenize_with, FuzzyMatch, LintWarning, PaletteEntry, TokenKind, WhereCondition, WhereOp,
+    WordCompleter, EXAMPLE_QUERIES, QUERY_TEMPLATES, WAAPI_ACCESSORS, WAAPI_PROPERTIES,
+};
 
-/// 输入提示文本
-const INPUT_HINT_TEXT: &str = "Enter the WAQL statement here.";
+/// 编辑器为空时切换示例查询的间隔（秒）
+const EXAMPLE_ROTATE_SECS: f64 = 4.0;
+
+/// 代码编辑器控件 ID
+const EDITOR_ID_SOURCE: &str = "waql_code_editor";
+
+/// 原始 JSON 视图最多显示的字节数
+const RAW_VIEW_LIMIT: usize = 64 * 1024;
+
+/// 树形视图中数组每页显示的元素数量
+const TREE_PAGE_SIZE: usize = 100;
+
+/// 单元格悬停提示中 JSON 最多显示的字节数
+const CELL_TOOLTIP_LIMIT: usize = 8 * 1024;
+
+/// 按键更新后变化单元格的高亮时长（秒）
+const FLASH_SECS: f64 = 1.5;
+
+/// 自动适应列宽时最多测量的行数
+const FIT_SAMPLE_ROWS: usize = 200;
+
+/// 自动适应列宽时单元格最多测量的字符数，更长的内容总会达到宽度上限
+const FIT_SAMPLE_CHARS: usize = 120;
+
+/// 自动适应的最小列宽
+const FIT_MIN_WIDTH: f32 = 40.0;
+
+/// 自动适应的最大列宽
+const FIT_MAX_WIDTH: f32 = 400.0;
+
+/// 列信息菜单中显示的最常见值数量
+const COLUMN_INFO_TOP_VALUES: usize = 5;
+
+/// 数值列自动适应的最大列宽
+const FIT_MAX_NUMERIC_WIDTH: f32 = 120.0;
+
+/// 提示消息显示时长（秒）
+const TOAST_DURATION: f64 = 2.0;
+
+/// 补全弹窗默认最多显示的候选数
+const MAX_COMPLETIONS: usize = 12;
+
+/// 插入面板最多显示的条目数
+const MAX_PALETTE_ITEMS: usize = 200;
+
+/// 撤销记录最多保留的步数
+const MAX_UNDO_STEPS: usize = 100;
+
+/// 间隔小于该时长（秒）的连续单字符修改合并为一步撤销
+const UNDO_GROUP_SECS: f64 = 1.0;
+
+/// 合并为一步撤销的连续修改最多持续的时长（秒），超过后开始新的一步
+const UNDO_GROUP_MAX_SECS: f64 = 5.0;
+
+/// 强制打开补全弹窗的快捷键
+const FORCE_COMPLETION_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::CTRL, egui::Key::Space);
+
+/// 撤销快捷键
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+/// 重做快捷键
+const REDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Y);
+
+/// 重做快捷键（Ctrl+Shift+Z）
+const REDO_SHIFT_SHORTCUT: egui::KeyboardShortcut = egui::KeyboardShortcut::new(
+    egui::Modifiers::COMMAND.plus(egui::Modifiers::SHIFT),
+    egui::Key::Z,
+);
 
 /// 可用的代码编辑器主题列表
 pub const THEMES: [ColorTheme; 8] = [
@@ -22,19 +109,571 @@ pub const THEMES: [ColorTheme; 8] = [
     ColorTheme::SONOKAI,
 ];
 
+/// 应用主题，并根据主题自动切换明暗模式
+pub fn apply_theme(ctx: &egui::Context, theme: &ColorTheme) {
+    let visuals = if theme.is_dark() {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    };
+    ctx.set_visuals(visuals);
+}
+
+/// 获取主题列表中的下一个主题
+pub fn next_theme(current: &ColorTheme) -> ColorTheme {
+    let index = THEMES
+        .iter()
+        .position(|t| t.name() == current.name())
+        .map(|i| (i + 1) % THEMES.len())
+        .unwrap_or(0);
+    THEMES[index]
+}
+
+/// 短暂显示的提示消息
+pub struct Toast {
+    /// 消息内容
+    message: String,
+    /// 消失时间（egui 输入时间，秒）
+    expires_at: f64,
+}
+
+impl Toast {
+    /// 创建从当前时刻开始显示的提示消息
+    pub fn new(ctx: &egui::Context, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            expires_at: ctx.input(|i| i.time) + TOAST_DURATION,
+        }
+    }
+}
+
+/// 在窗口右下角渲染提示消息，过期后自动清除
+pub fn render_toast(ctx: &egui::Context, toast: &mut Option<Toast>) {
+    let Some(current) = toast else {
+        return;
+    };
+
+    let remaining = current.expires_at - ctx.input(|i| i.time);
+    if remaining <= 0.0 {
+        *toast = None;
+        return;
+    }
+
+    egui::Area::new(egui::Id::new("toast"))
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-16.0, -16.0])
+        .order(egui::Order::Tooltip)
+        .interactable(false)
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(&current.message);
+            });
+        });
+    ctx.request_repaint_after(std::time::Duration::from_secs_f64(remaining));
+}
+
+/// 获取代码编辑器控件的 ID
+pub fn editor_id() -> egui::Id {
+    egui::Id::new(EDITOR_ID_SOURCE)
+}
+
+/// 将键盘焦点移回代码编辑器
+pub fn focus_editor(ctx: &egui::Context) {
+    ctx.memory_mut(|m| m.request_focus(editor_id()));
+}
+
+/// 判断本帧是否向代码编辑器粘贴了多行文本
+pub fn multiline_paste(ctx: &egui::Context) -> bool {
+    ctx.memory(|m| m.has_focus(editor_id()))
+        && ctx.input(|i| {
+            i.events
+                .iter()
+                .any(|event| matches!(event, egui::Event::Paste(text) if text.contains('\n')))
+        })
+}
+
+/// 获取词法单元在主题中的颜色
+fn token_color(theme: &ColorTheme, kind: TokenKind) -> egui::Color32 {
+    let ty = match kind {
+        TokenKind::Keyword => TokenType::Keyword,
+        TokenKind::Type => TokenType::Type,
+        TokenKind::Special => TokenType::Special,
+        TokenKind::Literal => TokenType::Literal,
+        TokenKind::Numeric => TokenType::Numeric(false),
+        TokenKind::Str => TokenType::Str('"'),
+        TokenKind::Punctuation => TokenType::Punctuation(' '),
+        TokenKind::Whitespace => TokenType::Whitespace(' '),
+        TokenKind::Comment => TokenType::Comment(false),
+        TokenKind::Function => TokenType::Function,
+        TokenKind::Unknown => TokenType::Unknown,
+    };
+    theme.type_color(ty)
+}
+
+/// 编辑器代码补全
+///
+/// 只根据光标处的标识符匹配候选词。弹窗打开时方向键选择，Tab 接受选中的（没有选中时为第一个）候选词，
+/// 回车只在用方向键选中候选词后接受，否则照常运行查询；Esc 关闭，Ctrl+Space 强制打开
+pub struct CodeCompleter {
+    /// 补全词库
+    words: WordCompleter,
+    /// 当前候选词
+    suggestions: Vec<String>,
+    /// 用方向键选中的候选词，`None` 表示尚未选择
+    selected: Option<usize>,
+    /// 候选词将替换的标识符字节范围
+    token_range: Range<usize>,
+    /// 用户按 Esc 关闭弹窗后，在文本改变前不再显示
+    dismissed: bool,
+    /// 显示弹窗所需的最少字符数
+    min_chars: usize,
+    /// 最多显示的候选数
+    max_suggestions: usize,
+    /// 是否还有超出数量限制而未显示的候选词
+    has_more: bool,
+    /// 是否通过 Ctrl+Space 强制打开，此时忽略最少字符数
+    forced: bool,
+}
+
+impl CodeCompleter {
+    /// 使用语法定义创建补全器
+    pub fn new(syntax: &Syntax) -> Self {
+        Self {
+            words: WordCompleter::from_syntax(syntax),
+            suggestions: Vec::new(),
+            selected: None,
+            token_range: 0..0,
+            dismissed: false,
+            min_chars: 1,
+            max_suggestions: MAX_COMPLETIONS,
+            has_more: false,
+            forced: false,
+        }
+    }
+
+    /// 设置显示弹窗所需的最少字符数和最多显示的候选数
+    pub fn set_limits(&mut self, min_chars: usize, max_suggestions: usize) {
+        self.min_chars = min_chars.max(1);
+        self.max_suggestions = max_suggestions.max(1);
+    }
+
+    /// 添加补全词
+    pub fn push_word(&mut self, word: &str) {
+        self.words.push_word(word);
+    }
+
+    /// 弹窗是否打开
+    fn is_open(&self) -> bool {
+        !self.suggestions.is_empty() && !self.dismissed
+    }
+
+    /// 关闭弹窗
+    fn close(&mut self) {
+        self.suggestions.clear();
+        self.selected = None;
+        self.forced = false;
+    }
+
+    /// 强制打开弹窗，忽略最少字符数，光标处没有单词时列出所有补全词
+    fn force_open(&mut self) {
+        self.forced = true;
+        self.dismissed = false;
+    }
+
+    /// 根据光标位置更新候选词
+    ///
+    /// 光标位于 `select` 子句中时优先推荐属性和访问器名称。
+    /// 单词短于最少字符数时不显示候选（强制打开时除外），超出数量限制的候选被截断
+    fn update(&mut self, text: &str, caret: usize) {
+        let token = token_at_caret(text, caret);
+        if !self.forced && token.prefix.chars().count() < self.min_chars {
+            self.close();
+            return;
+        }
+        let in_select = in_select_clause(text, caret);
+        let prefer = |word: &str| {
+            in_select && (WAAPI_PROPERTIES.contains(&word) || WAAPI_ACCESSORS.contains(&word))
+        };
+        // 多取一个候选用于判断是否被截断
+        let limit = self.max_suggestions + 1;
+        let candidates = if token.prefix.is_empty() {
+            self.words.all_preferring(limit, prefer)
+        } else {
+            self.words.suggestions_preferring(token.prefix, limit, prefer)
+        };
+        let mut suggestions: Vec<String> = candidates.into_iter().map(str::to_string).collect();
+        self.has_more = suggestions.len() > self.max_suggestions;
+        suggestions.truncate(self.max_suggestions);
+        if suggestions != self.suggestions {
+            self.selected = None;
+        }
+        if suggestions.is_empty() {
+            self.forced = false;
+        }
+        self.suggestions = suggestions;
+        self.token_range = token.range;
+    }
+
+    /// 处理弹窗按键
+    ///
+    /// # Returns
+    ///
+    /// 用户接受补全时返回选中的候选词
+    fn handle_keys(&mut self, ctx: &egui::Context) -> Option<String> {
+        if !self.is_open() {
+            return None;
+        }
+
+        // 没有选中候选词时不消费回车，交给编辑器运行查询
+        let highlighted = self.selected.is_some();
+        let (up, down, accept, escape) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Tab)
+                    || (highlighted && i.consume_key(egui::Modifiers::NONE, egui::Key::Enter)),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+            )
+        });
+
+        let count = self.suggestions.len();
+        if up {
+            self.selected = Some(match self.selected {
+                Some(index) if index > 0 => index - 1,
+                _ => count - 1,
+            });
+        }
+        if down {
+            self.selected = Some(self.selected.map_or(0, |index| (index + 1) % count));
+        }
+        if escape {
+            self.dismissed = true;
+            self.forced = false;
+        }
+        if accept {
+            return self.suggestions.get(self.selected.unwrap_or(0)).cloned();
+        }
+        None
+    }
+
+    /// 用候选词替换光标处的标识符
+    fn accept(&mut self, ctx: &egui::Context, code: &mut String, word: &str) {
+        let range = self.token_range.clone();
+        self.close();
+        if range.end > code.len()
+            || !code.is_char_boundary(range.start)
+            || !code.is_char_boundary(range.end)
+        {
+            return;
+        }
+
+        code.replace_range(range.clone(), word);
+
+        let id = editor_id();
+        let mut state = egui::text_edit::TextEditState::load(ctx, id).unwrap_or_default();
+        let caret = code[..range.start].chars().count() + word.chars().count();
+        state
+            .cursor
+            .set_char_range(Some(CCursorRange::one(CCursor::new(caret))));
+        state.store(ctx, id);
+        ctx.memory_mut(|m| m.request_focus(id));
+    }
+
+    /// 在指定位置渲染补全弹窗
+    ///
+    /// # Returns
+    ///
+    /// 用户点击候选词时返回该词
+    fn show_popup(&self, ctx: &egui::Context, pos: egui::Pos2) -> Option<String> {
+        let mut chosen = None;
+        egui::Area::new(editor_id().with("completion"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(pos)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    for (index, word) in self.suggestions.iter().enumerate() {
+                        let label = ui.selectable_label(
+                            Some(index) == self.selected,
+                            egui::RichText::new(word).monospace(),
+                        );
+                        if label.clicked() {
+                            chosen = Some(word.clone());
+                        }
+                    }
+                    if self.has_more {
+                        ui.weak("…more");
+                    }
+                });
+            });
+        chosen
+    }
+}
+
+/// 编辑器中浏览查询历史的位置
+#[derive(Default)]
+pub struct HistoryCursor {
+    /// 当前显示的历史记录索引，`None` 表示未在浏览历史
+    index: Option<usize>,
+    /// 开始浏览历史前编辑器中的内容，向下翻过最新记录时恢复
+    draft: String,
+    /// 最近一次填入编辑器的历史记录
+    recalled: String,
+}
+
+impl HistoryCursor {
+    /// 用户修改了填入的历史记录时停止浏览，下次从最新的记录开始
+    fn sync(&mut self, code: &str) {
+        if self.index.is_some() && code != self.recalled {
+            self.index = None;
+        }
+    }
+
+    /// 移到更早的一条记录
+    fn older(&mut self, code: &str, history: &[String]) -> Option<String> {
+        let next = match self.index {
+            None => {
+                self.draft = code.to_string();
+                0
+            }
+            Some(index) => index + 1,
+        };
+        let query = history.get(next)?;
+        self.index = Some(next);
+        self.recalled = query.clone();
+        Some(query.clone())
+    }
+
+    /// 移到更新的一条记录，翻过最新记录时恢复原来的内容
+    fn newer(&mut self, history: &[String]) -> Option<String> {
+        let index = self.index?;
+        if index == 0 {
+            self.index = None;
+            return Some(std::mem::take(&mut self.draft));
+        }
+        let query = history.get(index - 1)?;
+        self.index = Some(index - 1);
+        self.recalled = query.clone();
+        Some(query.clone())
+    }
+}
+
+/// 在单行编辑器中用上下方向键浏览查询历史
+///
+/// 光标位于开头时按上键显示更早的记录，位于末尾时按下键显示更新的记录。
+/// 多行模式、补全弹窗打开或编辑器没有焦点时不处理，保持正常的光标移动。
+/// 需要在渲染编辑器之前调用，以便先于编辑器处理按键
+pub fn navigate_history(
+    ctx: &egui::Context,
+    code: &mut String,
+    history: &[String],
+    cursor: &mut HistoryCursor,
+    completer: &CodeCompleter,
+) {
+    cursor.sync(code);
+    if completer.is_open() || !ctx.memory(|m| m.has_focus(editor_id())) {
+        return;
+    }
+    let Some(caret) = caret_index(ctx) else {
+        return;
+    };
+
+    let at_start = caret == 0;
+    let at_end = caret == code.chars().count();
+    let (up, down) = ctx.input_mut(|i| {
+        (
+            at_start && i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            at_end && i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+        )
+    });
+
+    let recalled = if up {
+        cursor.older(code, history)
+    } else if down {
+        cursor.newer(history)
+    } else {
+        None
+    };
+    let Some(recalled) = recalled else {
+        return;
+    };
+
+    *code = recalled;
+    // 上键将光标放在开头，可以继续向上浏览；下键放在末尾
+    let caret = if up { 0 } else { code.chars().count() };
+    let id = editor_id();
+    let mut state = egui::text_edit::TextEditState::load(ctx, id).unwrap_or_default();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(caret))));
+    state.store(ctx, id);
+}
+
+/// 编辑器的撤销和重做记录
+///
+/// egui 自带的撤销只记录键盘输入，无法撤销插入面板、补全、历史记录等直接修改 `code` 的操作，
+/// 因此每帧比较编辑器内容，自行记录所有修改
+#[derive(Default)]
+pub struct EditHistory {
+    /// 可以撤销到的内容，最近的在后
+    undo: VecDeque<String>,
+    /// 撤销后可以重做的内容，最近的在后
+    redo: Vec<String>,
+    /// 最近一次记录的内容
+    current: String,
+    /// 最近一次修改的时间（egui 输入时间，秒）
+    last_edit: f64,
+    /// 当前这一步撤销中第一次修改的时间
+    group_start: f64,
+}
+
+impl EditHistory {
+    /// 创建以 `code` 为初始内容的撤销记录
+    pub fn new(code: &str) -> Self {
+        let mut history = Self::default();
+        history.reset(code);
+        history
+    }
+
+    /// 清空撤销和重做记录，以 `code` 为当前内容，撤销不会回到之前的内容
+    pub fn reset(&mut self, code: &str) {
+        self.undo.clear();
+        self.redo.clear();
+        self.current = code.to_string();
+        self.last_edit = f64::NEG_INFINITY;
+    }
+
+    /// 记录内容的变化
+    ///
+    /// 短时间内连续的单字符修改合并为一步，但一步最多持续 [`UNDO_GROUP_MAX_SECS`]；
+    /// 粘贴等较大的修改单独成为一步
+    fn track(&mut self, code: &str, time: f64) {
+        if code == self.current {
+            return;
+        }
+        let grouped = time - self.last_edit < UNDO_GROUP_SECS
+            && time - self.group_start < UNDO_GROUP_MAX_SECS
+            && code.chars().count().abs_diff(self.current.chars().count()) <= 1;
+        if !grouped {
+            self.undo.push_back(std::mem::take(&mut self.current));
+            if self.undo.len() > MAX_UNDO_STEPS {
+                self.undo.pop_front();
+            }
+            self.group_start = time;
+        }
+        self.current = code.to_string();
+        self.redo.clear();
+        self.last_edit = time;
+    }
+
+    /// 撤销一步，返回撤销后的内容
+    fn undo(&mut self) -> Option<String> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(std::mem::replace(&mut self.current, previous));
+        // 撤销后的下一次修改单独成为一步
+        self.last_edit = f64::NEG_INFINITY;
+        Some(self.current.clone())
+    }
+
+    /// 重做一步，返回重做后的内容
+    fn redo(&mut self) -> Option<String> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(std::mem::replace(&mut self.current, next));
+        self.last_edit = f64::NEG_INFINITY;
+        Some(self.current.clone())
+    }
+}
+
+/// 记录编辑器内容的修改，并处理撤销（Ctrl+Z）和重做（Ctrl+Y / Ctrl+Shift+Z）
+///
+/// 需要每帧在渲染编辑器之前调用，以便记录上一帧的修改并先于编辑器处理按键。
+/// 只在编辑器有焦点时处理快捷键，撤销或重做后光标移到末尾
+pub fn handle_undo_redo(ctx: &egui::Context, code: &mut String, history: &mut EditHistory) {
+    history.track(code, ctx.input(|i| i.time));
+    if !ctx.memory(|m| m.has_focus(editor_id())) {
+        return;
+    }
+
+    // Ctrl+Z 的匹配会忽略 Shift，需要先检查 Ctrl+Shift+Z
+    let (undo, redo) = ctx.input_mut(|i| {
+        let redo = i.consume_shortcut(&REDO_SHIFT_SHORTCUT) || i.consume_shortcut(&REDO_SHORTCUT);
+        (!redo && i.consume_shortcut(&UNDO_SHORTCUT), redo)
+    });
+    let restored = if undo {
+        history.undo()
+    } else if redo {
+        history.redo()
+    } else {
+        None
+    };
+    let Some(restored) = restored else {
+        return;
+    };
+
+    *code = restored;
+    let id = editor_id();
+    let mut state = egui::text_edit::TextEditState::load(ctx, id).unwrap_or_default();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(code.chars().count()))));
+    state.store(ctx, id);
+}
+
+/// 获取编辑器中选中的文本
+///
+/// 没有选中内容时返回 `None`
+pub fn selected_text(ctx: &egui::Context, code: &str) -> Option<String> {
+    let range = egui::text_edit::TextEditState::load(ctx, editor_id())?
+        .cursor
+        .char_range()?;
+    let start = range.primary.index.min(range.secondary.index);
+    let end = range.primary.index.max(range.secondary.index);
+    if start == end {
+        return None;
+    }
+    Some(code.chars().skip(start).take(end - start).collect())
+}
+
+/// 获取编辑器光标的字符索引
+pub fn caret_index(ctx: &egui::Context) -> Option<usize> {
+    egui::text_edit::TextEditState::load(ctx, editor_id())?
+        .cursor
+        .char_range()
+        .map(|range| range.primary.index)
+}
+
 /// 渲染代码输入编辑器
+///
+/// `newline_key` 为 `None` 时使用单行编辑器，否则使用多行编辑器并以该快捷键换行
 pub fn render_code_editor(
     ui: &mut egui::Ui,
     code: &mut String,
-    completer: &mut Completer,
+    completer: &mut CodeCompleter,
     syntax: &Syntax,
     theme: &ColorTheme,
     fontsize: f32,
+    newline_key: Option<egui::KeyboardShortcut>,
 ) {
-    ui.horizontal(|h| {
-        completer.show_on_text_widget(h, syntax, theme, |ui| {
-            TextEdit::singleline(code)
-                .hint_text(INPUT_HINT_TEXT)
+    // 弹窗打开时先处理补全按键，避免回车触发查询
+    if ui.memory(|m| m.has_focus(editor_id())) {
+        if ui.input_mut(|i| i.consume_shortcut(&FORCE_COMPLETION_SHORTCUT)) {
+            completer.force_open();
+        }
+        if let Some(word) = completer.handle_keys(ui.ctx()) {
+            completer.accept(ui.ctx(), code, &word);
+        }
+    }
+
+    let example = current_example(ui.ctx(), code.is_empty());
+
+    let output = ui
+        .horizontal(|ui| {
+            let editor = if let Some(key) = newline_key {
+                TextEdit::multiline(code).desired_rows(6).return_key(key)
+            } else {
+                TextEdit::singleline(code)
+            };
+            editor
+                .id(editor_id())
+                .hint_text(format!("e.g. {example}"))
                 .font(egui::FontId::monospace(fontsize))
                 .desired_width(f32::INFINITY)
                 .lock_focus(true)
@@ -43,17 +682,297 @@ pub fn render_code_editor(
                     let font_id = egui::FontId::monospace(fontsize);
 
                     // 语法高亮
-                    for token in Token::default().tokens(syntax, text.as_str()) {
-                        let color = theme.type_color(token.ty());
+                    let text = text.as_str();
+                    for (kind, range) in tokenize_with(syntax, text) {
+                        let color = token_color(theme, kind);
                         let format = egui::text::TextFormat::simple(font_id.clone(), color);
-                        layout_job.append(token.buffer(), 0.0, format);
+                        layout_job.append(&text[range], 0.0, format);
                     }
 
                     ui.fonts_mut(|f| f.layout_job(layout_job))
                 })
                 .show(ui)
+        })
+        .inner;
+
+    // 编辑器为空时显示可点击的示例，点击后插入编辑器
+    if code.is_empty() {
+        ui.horizontal(|ui| {
+            ui.weak("Example:");
+            if ui.link(example).on_hover_text("Click to insert").clicked() {
+                insert_at_cursor(ui.ctx(), code, example);
+            }
+        });
+    }
+
+    if !output.response.has_focus() {
+        completer.close();
+        return;
+    }
+    if output.response.changed() {
+        completer.dismissed = false;
+    }
+
+    // 只用光标处的标识符匹配补全
+    let Some(caret) = output.cursor_range.map(|range| range.primary) else {
+        completer.close();
+        return;
+    };
+    completer.update(code, caret.index);
+
+    if completer.is_open() {
+        let caret_rect = output.galley.pos_from_cursor(caret);
+        let pos = output.galley_pos + caret_rect.left_bottom().to_vec2();
+        if let Some(word) = completer.show_popup(ui.ctx(), pos) {
+            completer.accept(ui.ctx(), code, &word);
+        }
+    }
+}
+
+/// 当前轮换到的示例查询
+///
+/// `rotate` 为 `true` 时（编辑器为空）按时间切换示例并安排下一次重绘
+fn current_example(ctx: &egui::Context, rotate: bool) -> &'static str {
+    if !rotate {
+        return EXAMPLE_QUERIES[0];
+    }
+    let time = ctx.input(|i| i.time);
+    let index = (time / EXAMPLE_ROTATE_SECS) as usize % EXAMPLE_QUERIES.len();
+    ctx.request_repaint_after(Duration::from_secs_f64(
+        EXAMPLE_ROTATE_SECS - time % EXAMPLE_ROTATE_SECS,
+    ));
+    EXAMPLE_QUERIES[index]
+}
+
+/// 在编辑器光标处插入文本
+///
+/// 如果有选中内容则替换选区，没有光标信息时追加到末尾。插入后光标移动到插入文本之后，
+/// 并将焦点还给编辑器
+pub fn insert_at_cursor(ctx: &egui::Context, code: &mut String, text: &str) {
+    let id = editor_id();
+    let mut state = egui::text_edit::TextEditState::load(ctx, id).unwrap_or_default();
+
+    let char_count = code.chars().count();
+    let (start, end) = state
+        .cursor
+        .char_range()
+        .map(|range| {
+            let a = range.primary.index.min(char_count);
+            let b = range.secondary.index.min(char_count);
+            (a.min(b), a.max(b))
+        })
+        .unwrap_or((char_count, char_count));
+
+    let start_byte = char_to_byte_index(code, start);
+    let end_byte = char_to_byte_index(code, end);
+    code.replace_range(start_byte..end_byte, text);
+
+    let caret = start + text.chars().count();
+    state
+        .cursor
+        .set_char_range(Some(CCursorRange::one(CCursor::new(caret))));
+    state.store(ctx, id);
+    ctx.memory_mut(|m| m.request_focus(id));
+}
+
+/// 将字符索引转换为字节索引
+fn char_to_byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len())
+}
+
+/// 插入面板状态
+#[derive(Default)]
+pub struct PaletteState {
+    /// 面板是否打开
+    pub open: bool,
+    /// 搜索文本
+    pub query: String,
+    /// 当前选中的条目（在匹配结果中的位置）
+    pub selected: usize,
+}
+
+/// 渲染插入面板
+///
+/// 支持方向键选择、回车插入、Esc 关闭
+///
+/// # Returns
+///
+/// 返回用户选中的条目文本
+pub fn render_palette(
+    ctx: &egui::Context,
+    state: &mut PaletteState,
+    entries: &[PaletteEntry],
+) -> Option<&'static str> {
+    if !state.open {
+        return None;
+    }
+
+    // 在其他控件之前消费按键，避免回车触发查询
+    let (up, down, enter, escape) = ctx.input_mut(|i| {
+        (
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            i.consume_key(egui::Modifiers::NONE, egui::Key::Escape),
+        )
+    });
+
+    if escape {
+        *state = PaletteState::default();
+        ctx.memory_mut(|m| m.request_focus(editor_id()));
+        return None;
+    }
+
+    let matches = search_palette(&state.query, entries);
+    let visible = matches.len().min(MAX_PALETTE_ITEMS);
+    if visible > 0 {
+        if up {
+            state.selected = state.selected.checked_sub(1).unwrap_or(visible - 1);
+        }
+        if down {
+            state.selected = (state.selected + 1) % visible;
+        }
+        state.selected = state.selected.min(visible - 1);
+    }
+
+    let mut chosen = if enter {
+        matches.get(state.selected).map(|(index, _)| entries[*index].text)
+    } else {
+        None
+    };
+
+    egui::Window::new("Insert")
+        .title_bar(false)
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+        .show(ctx, |ui| {
+            let response = ui.add(
+                TextEdit::singleline(&mut state.query)
+                    .hint_text("Search keywords, accessors and properties...")
+                    .desired_width(400.0),
+            );
+            response.request_focus();
+            if response.changed() {
+                state.selected = 0;
+            }
+
+            ui.separator();
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    for (row, (index, m)) in matches.iter().take(visible).enumerate() {
+                        let entry = entries[*index];
+                        let is_selected = row == state.selected;
+                        let label = ui
+                            .horizontal(|ui| {
+                                let label = ui.selectable_label(
+                                    is_selected,
+                                    highlight_matches(ui, entry.text, &m.indices),
+                                );
+                                ui.weak(entry.kind.description());
+                                if let Some(detail) = entry.detail {
+                                    ui.label(egui::RichText::new(detail).small().weak());
+                                }
+                                label
+                            })
+                            .inner;
+                        if is_selected && (up || down) {
+                            label.scroll_to_me(Some(egui::Align::Center));
+                        }
+                        if label.clicked() {
+                            chosen = Some(entry.text);
+                        }
+                    }
+                    if matches.is_empty() {
+                        ui.weak("No matches");
+                    }
+                });
         });
+
+    if chosen.is_some() {
+        *state = PaletteState::default();
+    }
+    chosen
+}
+
+/// 高亮显示模糊匹配的字符
+fn highlight_matches(ui: &egui::Ui, text: &str, indices: &[usize]) -> LayoutJob {
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let normal = TextFormat::simple(font_id.clone(), ui.visuals().text_color());
+    let strong = TextFormat::simple(font_id, ui.visuals().strong_text_color());
+
+    let mut job = LayoutJob::default();
+    for (index, ch) in text.chars().enumerate() {
+        let format = if indices.contains(&index) {
+            strong.clone()
+        } else {
+            normal.clone()
+        };
+        job.append(ch.encode_utf8(&mut [0; 4]), 0.0, format);
+    }
+    job
+}
+
+/// 渲染搜索框，搜索文本保存在 egui 内存中
+///
+/// 返回当前的搜索文本
+fn search_box(ui: &mut egui::Ui, id_salt: &str, hint: &str) -> String {
+    let id = ui.id().with(id_salt);
+    let mut search = ui.data(|d| d.get_temp::<String>(id)).unwrap_or_default();
+    ui.horizontal(|ui| {
+        ui.label("🔍");
+        if ui
+            .add(TextEdit::singleline(&mut search).hint_text(hint).desired_width(240.0))
+            .changed()
+        {
+            ui.data_mut(|d| d.insert_temp(id, search.clone()));
+        }
+    });
+    search
+}
+
+/// 保存的查询的搜索匹配结果
+struct SavedQueryMatch {
+    /// 匹配得分，越高越相关
+    score: i32,
+    /// 匹配到名称或标签时为该文本，匹配到查询语句时为 `None`
+    label: Option<String>,
+    /// 匹配文本中被匹配的字符索引
+    indices: Vec<usize>,
+}
+
+/// 在保存的查询的语句、名称和标签中模糊搜索，取得分最高的一项
+///
+/// 搜索文本为空时匹配所有查询
+fn match_saved_query(pattern: &str, query: &SavedQuery) -> Option<SavedQueryMatch> {
+    let mut best = fuzzy_match(pattern, &query.query).map(|m| SavedQueryMatch {
+        score: m.score,
+        label: None,
+        indices: m.indices,
     });
+    if pattern.trim().is_empty() {
+        return best;
+    }
+    let labels = std::iter::once(&query.name).chain(&query.tags);
+    for label in labels.filter(|label| !label.is_empty()) {
+        let Some(m) = fuzzy_match(pattern, label) else {
+            continue;
+        };
+        if best.as_ref().is_none_or(|best| m.score > best.score) {
+            best = Some(SavedQueryMatch {
+                score: m.score,
+                label: Some(label.clone()),
+                indices: m.indices,
+            });
+        }
+    }
+    best
 }
 
 /// 渲染配置面板
@@ -62,12 +981,29 @@ pub fn render_config_panel(
     config: &mut UserConfig,
     theme: &mut ColorTheme,
     custom_keyword: &mut String,
-    completer: &mut Completer,
+    completer: &mut CodeCompleter,
     code: &mut String,
     ctx: &egui::Context,
 ) -> ConfigPanelActions {
     let mut actions = ConfigPanelActions::default();
 
+    // 配置文件：手动编辑后可以重新加载
+    ui.horizontal(|ui| {
+        if ui
+            .button("Reload config")
+            .on_hover_text("Re-read user_data.json from disk")
+            .clicked()
+        {
+            actions.reload_config = true;
+        }
+        if config.changed_on_disk() {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                "⚠ user_data.json was modified outside the app; the next save will overwrite it",
+            );
+        }
+    });
+
     // 主题选择区域
     ui.group(|ui| {
         ui.heading("Theme");
@@ -78,13 +1014,7 @@ pub fn render_config_panel(
                     .selectable_value(theme, *available_theme, available_theme.name())
                     .clicked()
                 {
-                    // 根据主题自动切换明暗模式
-                    let visuals = if available_theme.is_dark() {
-                        egui::Visuals::dark()
-                    } else {
-                        egui::Visuals::light()
-                    };
-                    ctx.set_visuals(visuals);
+                    apply_theme(ctx, available_theme);
 
                     // 保存主题到配置
                     config.theme_name = available_theme.name().to_string();
@@ -92,61 +1022,778 @@ pub fn render_config_panel(
                 }
             }
         });
+        if ui
+            .checkbox(&mut config.colorblind_palette, "Colorblind-friendly status colors")
+            .on_hover_text(
+                "Use blue/orange instead of green/red for status and cell rules, \
+                 and show ✔/✖ next to colored status text",
+            )
+            .changed()
+        {
+            actions.save_config = true;
+        }
     });
 
     ui.separator();
 
-    // 字体大小调节区域
+    // 编辑器设置区域
     ui.group(|ui| {
-        ui.heading("Font Size");
+        ui.heading("Editor");
         ui.separator();
+        if ui
+            .checkbox(
+                &mut config.multiline_editor,
+                "Multi-line editor (Ctrl+Enter runs the selection or current statement)",
+            )
+            .changed()
+        {
+            actions.save_config = true;
+        }
         ui.horizontal(|ui| {
-            ui.label("Size:");
-            if ui.add(egui::Slider::new(&mut config.fontsize, 10.0..=32.0).text("pt")).changed() {
-                actions.fontsize_changed = true;
+            ui.label("Enter key:");
+            let mut behavior = config.enter_behavior();
+            egui::ComboBox::from_id_salt("enter_behavior")
+                .selected_text(behavior.label())
+                .show_ui(ui, |ui| {
+                    for option in EnterBehavior::ALL {
+                        if ui.selectable_value(&mut behavior, option, option.label()).changed() {
+                            config.enter_behavior = Some(behavior);
+                            actions.save_config = true;
+                        }
+                    }
+                });
+        })
+        .response
+        .on_hover_text("Ctrl+Enter always runs; with \"Run query\" use Shift+Enter for a newline");
+        if ui
+            .checkbox(
+                &mut config.auto_fix_dollar,
+                "Auto-fix leading $ (e.g. \"from type Event\" runs as \"$ from type Event\")",
+            )
+            .changed()
+        {
+            actions.auto_fix_changed = true;
+            actions.save_config = true;
+        }
+        if ui
+            .checkbox(
+                &mut config.normalize_input,
+                "Normalize pasted input (collapse whitespace, straighten smart quotes)",
+            )
+            .changed()
+        {
+            actions.normalize_changed = true;
+            actions.save_config = true;
+        }
+        if ui
+            .checkbox(&mut config.trim_input, "Trim leading/trailing whitespace before running")
+            .on_hover_text("Turn off to send queries without | options exactly as typed")
+            .changed()
+        {
+            actions.trim_changed = true;
+            actions.save_config = true;
+        }
+        if ui
+            .checkbox(&mut config.keep_editor_focus, "Keep editor focused after running a query")
+            .changed()
+        {
+            actions.save_config = true;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Default return:");
+            // 编辑中的文本暂存在 egui 内存中，失去焦点时再解析
+            let default_return_id = ui.id().with("default_return");
+            let mut text = ui
+                .data(|d| d.get_temp::<String>(default_return_id))
+                .unwrap_or_else(|| config.default_return.join(" "));
+            let response = ui.add(
+                TextEdit::singleline(&mut text)
+                    .id(default_return_id)
+                    .hint_text("e.g. name id type (used when the query has no | options)")
+                    .desired_width(320.0),
+            );
+            if response.changed() {
+                ui.data_mut(|d| d.insert_temp(default_return_id, text.clone()));
+            }
+            if response.lost_focus() {
+                config.default_return = text.split_whitespace().map(str::to_string).collect();
+                ui.data_mut(|d| d.remove::<String>(default_return_id));
+                actions.default_return_changed = true;
                 actions.save_config = true;
             }
-            if ui.button("Reset").clicked() {
-                config.fontsize = 18.0;
-                actions.fontsize_changed = true;
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .checkbox(&mut config.auto_select, "Append select")
+                .on_hover_text("Append a select clause to queries without select or | options")
+                .changed()
+            {
                 actions.save_config = true;
             }
+            ui.add_enabled_ui(config.auto_select, |ui| {
+                // 编辑中的文本暂存在 egui 内存中，失去焦点时再解析
+                let auto_select_id = ui.id().with("auto_select_fields");
+                let mut text = ui
+                    .data(|d| d.get_temp::<String>(auto_select_id))
+                    .unwrap_or_else(|| config.auto_select_fields.join(" "));
+                let response = ui.add(
+                    TextEdit::singleline(&mut text)
+                        .id(auto_select_id)
+                        .hint_text("e.g. name id type")
+                        .desired_width(240.0),
+                );
+                if response.changed() {
+                    ui.data_mut(|d| d.insert_temp(auto_select_id, text.clone()));
+                }
+                if response.lost_focus() {
+                    config.auto_select_fields =
+                        text.split_whitespace().map(str::to_string).collect();
+                    ui.data_mut(|d| d.remove::<String>(auto_select_id));
+                    actions.save_config = true;
+                }
+            });
         });
-    });
-
-    ui.separator();
-
-    // WAQL 语句列表区域
-    ui.group(|ui| {
-        ui.heading("Saved Queries");
-        ui.separator();
-
-        for (index, query) in config.saved_queries.iter().enumerate() {
+        if ui
+            .checkbox(
+                &mut config.large_query_guard,
+                "Confirm before running queries on broad types without a where filter",
+            )
+            .changed()
+        {
+            actions.save_config = true;
+        }
+        ui.add_enabled_ui(config.large_query_guard, |ui| {
             ui.horizontal(|ui| {
-                if ui.button("Load").clicked() {
-                    *code = query.clone();
+                ui.label("Broad types:");
+                // 编辑中的文本暂存在 egui 内存中，失去焦点时再解析
+                let broad_types_id = ui.id().with("broad_types");
+                let mut text = ui
+                    .data(|d| d.get_temp::<String>(broad_types_id))
+                    .unwrap_or_else(|| config.broad_types.join(" "));
+                let response = ui.add(
+                    TextEdit::singleline(&mut text)
+                        .id(broad_types_id)
+                        .hint_text("e.g. Sound Event")
+                        .desired_width(240.0),
+                );
+                if response.changed() {
+                    ui.data_mut(|d| d.insert_temp(broad_types_id, text.clone()));
                 }
-                ui.label(query);
-                if ui.button("❌").clicked() {
-                    actions.remove_query_index = Some(index);
+                if response.lost_focus() {
+                    config.broad_types = text.split_whitespace().map(str::to_string).collect();
+                    ui.data_mut(|d| d.remove::<String>(broad_types_id));
+                    actions.save_config = true;
+                }
+                ui.label("suggest take");
+                if ui
+                    .add(egui::DragValue::new(&mut config.large_query_limit).range(1..=1_000_000))
+                    .changed()
+                {
+                    actions.save_config = true;
                 }
             });
-        }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Completion after");
+            let min_chars =
+                ui.add(egui::DragValue::new(&mut config.min_completion_chars).range(1..=5));
+            ui.label("chars, show up to");
+            let max = ui.add(egui::DragValue::new(&mut config.max_suggestions).range(1..=50));
+            ui.label("suggestions");
+            if min_chars.changed() || max.changed() {
+                actions.completion_changed = true;
+                actions.save_config = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            let mut changed = ui
+                .checkbox(&mut config.auto_refresh, "Auto-refresh results every")
+                .on_hover_text(
+                    "Re-run the last query and replace the results; pauses while you edit \
+                     the query or interact with the table",
+                )
+                .changed();
+            ui.add_enabled_ui(config.auto_refresh, |ui| {
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.auto_refresh_secs).range(1..=3600))
+                    .changed();
+            });
+            ui.label("s");
+            if changed {
+                actions.save_config = true;
+            }
+        });
     });
 
     ui.separator();
 
-    // 自定义关键词区域
+    // 导出设置区域
     ui.group(|ui| {
-        ui.heading("Custom Keywords");
+        ui.heading("Export");
         ui.separator();
-
+        if ui
+            .checkbox(
+                &mut config.safe_csv_export,
+                "Safe CSV (escape cells that spreadsheets could run as formulas)",
+            )
+            .changed()
+        {
+            actions.save_config = true;
+        }
+        if ui
+            .checkbox(&mut config.csv_bom, "Add UTF-8 BOM (for Excel on Windows)")
+            .changed()
+        {
+            actions.save_config = true;
+        }
         ui.horizontal(|ui| {
-            ui.label("Add:");
-            ui.text_edit_singleline(custom_keyword);
-            if ui.button("Add").clicked() {
-                let keyword = custom_keyword.trim().to_string();
-                if config.add_custom_keyword(keyword.clone()) {
+            ui.label("CSV delimiter:");
+            egui::ComboBox::from_id_salt("csv_delimiter")
+                .selected_text(config.csv_delimiter.label())
+                .show_ui(ui, |ui| {
+                    for option in CsvDelimiter::ALL {
+                        if ui
+                            .selectable_value(&mut config.csv_delimiter, option, option.label())
+                            .changed()
+                        {
+                            actions.save_config = true;
+                        }
+                    }
+                });
+            ui.label("Quoting:");
+            egui::ComboBox::from_id_salt("csv_quoting")
+                .selected_text(config.csv_quoting.label())
+                .show_ui(ui, |ui| {
+                    for option in CsvQuoting::ALL {
+                        if ui
+                            .selectable_value(&mut config.csv_quoting, option, option.label())
+                            .changed()
+                        {
+                            actions.save_config = true;
+                        }
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Max rows (0 = unlimited):");
+            if ui
+                .add(egui::DragValue::new(&mut config.max_rows).speed(100.0))
+                .on_hover_text("Larger results are truncated in the table and in exports")
+                .changed()
+            {
+                actions.max_rows_changed = true;
+                actions.save_config = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Raw JSON depth (0 = unlimited):");
+            if ui
+                .add(egui::DragValue::new(&mut config.json_max_depth).range(0..=64))
+                .on_hover_text("Collapse deeper objects and arrays to {...} and [...] in raw JSON")
+                .changed()
+            {
+                actions.save_config = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Show null/empty cells as:");
+            egui::ComboBox::from_id_salt("null_display")
+                .selected_text(config.null_display.label())
+                .show_ui(ui, |ui| {
+                    for option in NullDisplay::ALL {
+                        if ui
+                            .selectable_value(&mut config.null_display, option, option.label())
+                            .changed()
+                        {
+                            actions.save_config = true;
+                        }
+                    }
+                });
+        });
+        if ui
+            .checkbox(&mut config.show_type_badges, "Show column type badges in the table header")
+            .changed()
+        {
+            actions.save_config = true;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Empty result message:");
+            if ui
+                .add(TextEdit::singleline(&mut config.empty_result_message).desired_width(200.0))
+                .on_hover_text("Shown when a query returns an empty object list")
+                .lost_focus()
+            {
+                actions.save_config = true;
+            }
+        });
+    });
+
+    ui.separator();
+
+    // 单元格着色规则区域
+    ui.group(|ui| {
+        ui.heading("Cell Rules");
+        ui.separator();
+        if ui
+            .checkbox(&mut config.cell_rules_enabled, "Color cells matching rules")
+            .changed()
+        {
+            actions.save_config = true;
+        }
+
+        let mut remove_rule = None;
+        for (index, rule) in config.cell_rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let column = ui.add(
+                    TextEdit::singleline(&mut rule.column)
+                        .hint_text("column")
+                        .desired_width(100.0),
+                );
+                egui::ComboBox::from_id_salt(("cell_rule_op", index))
+                    .selected_text(rule.op.label())
+                    .width(70.0)
+                    .show_ui(ui, |ui| {
+                        for op in RuleOp::ALL {
+                            if ui.selectable_value(&mut rule.op, op, op.label()).changed() {
+                                actions.save_config = true;
+                            }
+                        }
+                    });
+                let value = ui.add(
+                    TextEdit::singleline(&mut rule.value)
+                        .hint_text("value")
+                        .desired_width(80.0),
+                );
+                if column.lost_focus() || value.lost_focus() {
+                    actions.save_config = true;
+                }
+                if ui.color_edit_button_srgb(&mut rule.color).changed() {
+                    actions.save_config = true;
+                }
+                if ui.small_button("❌").clicked() {
+                    remove_rule = Some(index);
+                }
+                // 无效的规则不会生效，在这里提示原因
+                if let Err(reason) = rule.validate() {
+                    ui.colored_label(egui::Color32::YELLOW, format!("⚠ ignored: {}", reason));
+                }
+            });
+        }
+        if let Some(index) = remove_rule {
+            config.cell_rules.remove(index);
+            actions.save_config = true;
+        }
+        if ui.button("Add rule").clicked() {
+            config.cell_rules.push(CellRule::default());
+            actions.save_config = true;
+        }
+    });
+
+    ui.separator();
+
+    // 结果后处理规则区域
+    ui.group(|ui| {
+        ui.heading("Post-processing");
+        ui.separator();
+        ui.weak("Applied in order to query results before they are shown");
+
+        let mut changed = false;
+        let mut remove_rule = None;
+        for (index, rule) in config.post_rules.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_salt(("post_rule_op", index))
+                    .selected_text(rule.op.label())
+                    .width(70.0)
+                    .show_ui(ui, |ui| {
+                        for op in PostOp::ALL {
+                            changed |= ui.selectable_value(&mut rule.op, op, op.label()).changed();
+                        }
+                    });
+                let mut field = |ui: &mut egui::Ui, text: &mut String, hint: &str| {
+                    let response =
+                        ui.add(TextEdit::singleline(text).hint_text(hint).desired_width(80.0));
+                    changed |= response.lost_focus();
+                };
+                field(ui, &mut rule.column, "column");
+                if rule.op == PostOp::Concat {
+                    field(ui, &mut rule.other, "column");
+                    field(ui, &mut rule.separator, "separator");
+                }
+                if rule.op != PostOp::Drop {
+                    ui.label("→");
+                    field(ui, &mut rule.target, "new column");
+                }
+                if ui.small_button("❌").clicked() {
+                    remove_rule = Some(index);
+                }
+                if let Err(reason) = rule.validate() {
+                    ui.colored_label(egui::Color32::YELLOW, format!("⚠ ignored: {}", reason));
+                }
+            });
+        }
+        if let Some(index) = remove_rule {
+            config.post_rules.remove(index);
+            changed = true;
+        }
+        if ui.button("Add rule").clicked() {
+            config.post_rules.push(PostRule::default());
+            changed = true;
+        }
+        if changed {
+            actions.post_rules_changed = true;
+            actions.save_config = true;
+        }
+    });
+
+    ui.separator();
+
+    // 字体大小调节区域
+    ui.group(|ui| {
+        ui.heading("Font Size");
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Size:");
+            if ui.add(egui::Slider::new(&mut config.fontsize, 10.0..=32.0).text("pt")).changed() {
+                actions.fontsize_changed = true;
+                actions.save_config = true;
+            }
+            if ui.button("Reset").clicked() {
+                config.fontsize = 18.0;
+                actions.fontsize_changed = true;
+                actions.save_config = true;
+            }
+        });
+    });
+
+    ui.separator();
+
+    // 连接设置区域
+    ui.group(|ui| {
+        ui.heading("Connection");
+        ui.separator();
+        if ui
+            .checkbox(&mut config.offline_mode, "Offline demo mode (built-in sample data)")
+            .changed()
+        {
+            actions.offline_changed = true;
+            actions.save_config = true;
+        }
+        ui.horizontal(|ui| {
+            let mut changed = ui
+                .checkbox(&mut config.health_check, "Health check every")
+                .on_hover_text("Ping the connection periodically and reconnect automatically")
+                .changed();
+            ui.add_enabled_ui(config.health_check, |ui| {
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.health_check_secs).range(1..=3600))
+                    .changed();
+                ui.label("s, backoff ×");
+                changed |= ui
+                    .add(
+                        egui::DragValue::new(&mut config.reconnect_backoff)
+                            .range(1.0..=10.0)
+                            .speed(0.1),
+                    )
+                    .changed();
+                ui.label("up to");
+                changed |= ui
+                    .add(egui::DragValue::new(&mut config.max_reconnect_secs).range(1..=3600))
+                    .changed();
+                ui.label("s");
+            });
+            if changed {
+                actions.health_changed = true;
+                actions.save_config = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Disconnect when idle for:");
+            if ui
+                .add(egui::DragValue::new(&mut config.idle_timeout_secs).range(0..=86400))
+                .on_hover_text(
+                    "Close the connection after this many seconds without a request (0 = never); \
+                     it reconnects on the next query",
+                )
+                .changed()
+            {
+                actions.idle_timeout_changed = true;
+                actions.save_config = true;
+            }
+            ui.label("s");
+        });
+        ui.horizontal(|ui| {
+            if ui
+                .checkbox(&mut config.background_queries, "Run queries in the background")
+                .on_hover_text(
+                    "Keep the window responsive while waiting for and parsing large responses",
+                )
+                .changed()
+            {
+                actions.save_config = true;
+            }
+            ui.label("Timeout:");
+            if ui
+                .add(egui::DragValue::new(&mut config.request_timeout_secs).range(1..=3600))
+                .on_hover_text("Overall time allowed for a request, including reading the response")
+                .changed()
+            {
+                actions.client_config_changed = true;
+                actions.save_config = true;
+            }
+            ui.label("s");
+        });
+        if ui
+            .checkbox(
+                &mut config.preview_mutations,
+                "Preview and confirm WAAPI calls that modify the project",
+            )
+            .changed()
+        {
+            actions.save_config = true;
+        }
+        ui.horizontal(|ui| {
+            ui.label("Auth token:");
+            let response = ui.add(
+                TextEdit::singleline(&mut config.auth_token)
+                    .password(true)
+                    .hint_text("Bearer token for WAAPI proxy (optional)")
+                    .desired_width(240.0),
+            );
+            if response.lost_focus() {
+                actions.client_config_changed = true;
+                actions.save_config = true;
+            }
+        });
+
+        // 自定义请求头，名称为空的行不会发送
+        ui.label("Extra request headers:");
+        let mut remove_header = None;
+        for (index, (name, value)) in config.headers.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                let name_response =
+                    ui.add(TextEdit::singleline(name).hint_text("Name").desired_width(140.0));
+                let value_response =
+                    ui.add(TextEdit::singleline(value).hint_text("Value").desired_width(160.0));
+                if name_response.lost_focus() || value_response.lost_focus() {
+                    actions.client_config_changed = true;
+                    actions.save_config = true;
+                }
+                if ui.button("❌").clicked() {
+                    remove_header = Some(index);
+                }
+                if name.trim().is_empty() {
+                    ui.colored_label(egui::Color32::YELLOW, "⚠ Name is empty, not sent");
+                }
+            });
+        }
+        if let Some(index) = remove_header {
+            config.headers.remove(index);
+            actions.client_config_changed = true;
+            actions.save_config = true;
+        }
+        if ui.button("Add header").clicked() {
+            config.headers.push((String::new(), String::new()));
+        }
+
+        ui.separator();
+
+        for (index, connection) in config.connections.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let active = connection.name == config.active_connection;
+                if ui.radio(active, &connection.name).clicked() && !active {
+                    actions.activate_connection = Some(connection.name.clone());
+                }
+                ui.label(format!("{}:{}", connection.host, connection.port));
+                if ui.button("❌").clicked() {
+                    actions.remove_connection_index = Some(index);
+                }
+            });
+        }
+
+        // 新连接的输入内容暂存在 egui 内存中
+        let draft_id = ui.id().with("new_connection");
+        let mut draft = ui
+            .data(|d| d.get_temp::<Connection>(draft_id))
+            .unwrap_or_else(|| Connection {
+                name: String::new(),
+                ..Connection::default()
+            });
+        ui.horizontal(|ui| {
+            ui.add(
+                TextEdit::singleline(&mut draft.name)
+                    .hint_text("Name")
+                    .desired_width(100.0),
+            );
+            ui.add(
+                TextEdit::singleline(&mut draft.host)
+                    .hint_text("Host")
+                    .desired_width(120.0),
+            );
+            ui.add(egui::DragValue::new(&mut draft.port).range(1..=u16::MAX));
+            if ui.button("Add").clicked() && config.add_connection(draft.clone()) {
+                draft.name.clear();
+                actions.save_config = true;
+            }
+        });
+        ui.data_mut(|d| d.insert_temp(draft_id, draft));
+    });
+
+    ui.separator();
+
+    // WAQL 语句列表区域
+    ui.group(|ui| {
+        ui.heading("Saved Queries");
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(
+                    !config.saved_queries.is_empty(),
+                    egui::Button::new("Export Set..."),
+                )
+                .clicked()
+            {
+                actions.export_query_set = true;
+            }
+            if ui.button("Import Set...").clicked() {
+                actions.import_query_set = true;
+            }
+            if ui
+                .add_enabled(
+                    !config.saved_queries.is_empty(),
+                    egui::Button::new("Batch Export..."),
+                )
+                .clicked()
+            {
+                actions.batch_export = true;
+            }
+            if ui
+                .add_enabled(!config.saved_queries.is_empty(), egui::Button::new("Validate..."))
+                .on_hover_text("Run every saved query once and report failures")
+                .clicked()
+            {
+                actions.validate_library = true;
+            }
+        });
+
+        let search = search_box(ui, "saved_query_search", "Search name, tags or query");
+
+        ui.separator();
+
+        // 只列出全局查询和属于当前连接的查询，搜索时按匹配得分排序
+        let active_connection = config.active_connection.clone();
+        let mut matches: Vec<(usize, SavedQueryMatch)> = config
+            .saved_queries
+            .iter()
+            .enumerate()
+            .filter(|(_, query)| query.is_visible_in(&active_connection))
+            .filter_map(|(index, query)| Some((index, match_saved_query(&search, query)?)))
+            .collect();
+        matches.sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
+
+        for (index, matched) in matches {
+            let query = &mut config.saved_queries[index];
+            ui.horizontal(|ui| {
+                if ui.button("Load").clicked() {
+                    *code = query.query.clone();
+                }
+
+                let mut scoped = query.connection.is_some();
+                if ui
+                    .checkbox(&mut scoped, "This connection only")
+                    .on_hover_text(format!("Only show this query for \"{active_connection}\""))
+                    .changed()
+                {
+                    query.connection = scoped.then(|| active_connection.clone());
+                    actions.save_config = true;
+                }
+
+                let name_response = ui.add(
+                    TextEdit::singleline(&mut query.name)
+                        .hint_text("Name")
+                        .desired_width(120.0),
+                );
+                if name_response.lost_focus() {
+                    actions.save_config = true;
+                }
+
+                // 编辑中的标签文本暂存在 egui 内存中，失去焦点时再解析
+                let tags_id = ui.id().with(("saved_query_tags", index));
+                let mut tags_text = ui
+                    .data(|d| d.get_temp::<String>(tags_id))
+                    .unwrap_or_else(|| query.tags.join(", "));
+                let tags_response = ui.add(
+                    TextEdit::singleline(&mut tags_text)
+                        .id(tags_id)
+                        .hint_text("Tags")
+                        .desired_width(100.0),
+                );
+                if tags_response.changed() {
+                    ui.data_mut(|d| d.insert_temp(tags_id, tags_text.clone()));
+                }
+                if tags_response.lost_focus() {
+                    query.tags = parse_tags(&tags_text);
+                    ui.data_mut(|d| d.remove::<String>(tags_id));
+                    actions.save_config = true;
+                }
+
+                match &matched.label {
+                    Some(label) => {
+                        ui.label(highlight_matches(ui, label, &matched.indices));
+                        ui.label(&query.query);
+                    }
+                    None => {
+                        ui.label(highlight_matches(ui, &query.query, &matched.indices));
+                    }
+                }
+                if ui.button("❌").clicked() {
+                    actions.remove_query_index = Some(index);
+                }
+            });
+        }
+    });
+
+    ui.separator();
+
+    // 查询历史区域
+    ui.group(|ui| {
+        ui.heading("History");
+        ui.separator();
+
+        let search = search_box(ui, "history_search", "Search history");
+        let mut matches: Vec<(&String, FuzzyMatch)> = config
+            .query_history
+            .iter()
+            .filter_map(|query| Some((query, fuzzy_match(&search, query)?)))
+            .collect();
+        matches.sort_by_key(|(_, m)| std::cmp::Reverse(m.score));
+
+        if config.query_history.is_empty() {
+            ui.weak("No queries run yet");
+        }
+        for (query, matched) in matches {
+            ui.horizontal(|ui| {
+                if ui.button("Load").clicked() {
+                    *code = query.clone();
+                }
+                ui.label(highlight_matches(ui, query, &matched.indices));
+            });
+        }
+    });
+
+    ui.separator();
+
+    // 自定义关键词区域
+    ui.group(|ui| {
+        ui.heading("Custom Keywords");
+        ui.separator();
+
+        ui.horizontal(|ui| {
+            ui.label("Add:");
+            ui.text_edit_singleline(custom_keyword);
+            if ui.button("Add").clicked() {
+                let keyword = custom_keyword.trim().to_string();
+                if config.add_custom_keyword(keyword.clone()) {
                     completer.push_word(&keyword);
                     custom_keyword.clear();
                     actions.save_config = true;
@@ -154,166 +1801,2900 @@ pub fn render_config_panel(
             }
         });
 
-        ui.separator();
+        ui.separator();
+
+        for (index, keyword) in config.custom_keywords.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(keyword);
+                if ui.button("❌").clicked() {
+                    actions.remove_keyword_index = Some(index);
+                }
+            });
+        }
+    });
+
+    actions
+}
+
+/// 配置面板操作结果
+#[derive(Default)]
+pub struct ConfigPanelActions {
+    /// 是否需要保存配置
+    pub save_config: bool,
+    /// 是否从磁盘重新加载配置
+    pub reload_config: bool,
+    /// 需要删除的查询索引
+    pub remove_query_index: Option<usize>,
+    /// 是否导出查询集
+    pub export_query_set: bool,
+    /// 是否导入查询集
+    pub import_query_set: bool,
+    /// 是否打开批量导出窗口
+    pub batch_export: bool,
+    /// 是否打开保存的查询检查窗口
+    pub validate_library: bool,
+    /// 需要删除的关键词索引
+    pub remove_keyword_index: Option<usize>,
+    /// 字体大小是否改变
+    pub fontsize_changed: bool,
+    /// 离线模式是否改变
+    pub offline_changed: bool,
+    /// 自动补全 `$` 设置是否改变
+    pub auto_fix_changed: bool,
+    /// 输入规范化设置是否改变
+    pub normalize_changed: bool,
+    /// 首尾空白处理设置是否改变
+    pub trim_changed: bool,
+    /// 结果后处理规则是否改变
+    pub post_rules_changed: bool,
+    /// 健康检查设置是否改变
+    pub health_changed: bool,
+    /// 空闲断开时间是否改变
+    pub idle_timeout_changed: bool,
+    /// 默认返回字段是否改变
+    pub default_return_changed: bool,
+    /// 补全触发长度或候选数是否改变
+    pub completion_changed: bool,
+    /// 最大行数限制是否改变
+    pub max_rows_changed: bool,
+    /// 连接配置是否改变
+    pub client_config_changed: bool,
+    /// 需要切换到的连接名称
+    pub activate_connection: Option<String>,
+    /// 需要删除的连接索引
+    pub remove_connection_index: Option<usize>,
+}
+
+/// 渲染编辑器下方的查询检查提示
+///
+/// # Returns
+///
+/// 点击关闭按钮时返回该提示的索引
+pub fn render_lint_warnings(ui: &mut egui::Ui, warnings: &[LintWarning]) -> Option<usize> {
+    let mut dismissed = None;
+    for (index, warning) in warnings.iter().enumerate() {
+        ui.horizontal(|ui| {
+            ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", warning.message));
+            if ui.small_button("✖").on_hover_text("Dismiss").clicked() {
+                dismissed = Some(index);
+            }
+        });
+    }
+    dismissed
+}
+
+/// 渲染平台和语言选项下拉框
+///
+/// 选择后在查询语句的 `|` 选项部分写入 `platform=...` / `language=...`，
+/// 下拉框的当前值从选项文本中读取，因此手动编辑选项时保持同步
+pub fn render_option_selectors(ui: &mut egui::Ui, code: &mut String, project: &ProjectInfo) {
+    ui.horizontal(|ui| {
+        for (key, label, values) in [
+            ("platform", "Platform", &project.platforms),
+            ("language", "Language", &project.languages),
+        ] {
+            let current = query_option(code, key);
+            let mut selected = current.clone();
+            ui.label(label);
+            egui::ComboBox::from_id_salt(("query_option", key))
+                .selected_text(current.as_deref().unwrap_or("(none)"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut selected, None, "(none)");
+                    for value in values {
+                        ui.selectable_value(&mut selected, Some(value.clone()), value);
+                    }
+                });
+            if selected != current {
+                *code = set_query_option(code, key, selected.as_deref());
+            }
+        }
+    });
+}
+
+/// 模板菜单操作结果
+#[derive(Default)]
+pub struct TemplateActions {
+    /// 选中的模板查询，以及是否追加到编辑器而不是替换
+    pub insert: Option<(String, bool)>,
+    /// 以该名称将当前查询保存为模板
+    pub save_current: Option<String>,
+    /// 需要删除的用户模板索引
+    pub remove: Option<usize>,
+}
+
+/// 渲染模板菜单，列出内置模板和用户模板
+///
+/// 点击模板替换编辑器内容，按住 Shift 点击时追加。`new_name` 为保存当前查询时输入的模板名称
+pub fn render_templates_menu(
+    ui: &mut egui::Ui,
+    templates: &[QueryTemplate],
+    has_code: bool,
+    new_name: &mut String,
+) -> TemplateActions {
+    let mut actions = TemplateActions::default();
+    ui.menu_button("Templates", |ui| {
+        for (name, query) in QUERY_TEMPLATES {
+            if let Some(insert) = template_button(ui, name, query) {
+                actions.insert = Some(insert);
+            }
+        }
+        if !templates.is_empty() {
+            ui.separator();
+            for (index, template) in templates.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if let Some(insert) = template_button(ui, &template.name, &template.query) {
+                        actions.insert = Some(insert);
+                    }
+                    if ui.small_button("x").on_hover_text("Remove template").clicked() {
+                        actions.remove = Some(index);
+                    }
+                });
+            }
+        }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.add(TextEdit::singleline(new_name).hint_text("Template name").desired_width(140.0));
+            let can_save = has_code && !new_name.trim().is_empty();
+            if ui.add_enabled(can_save, egui::Button::new("Save current")).clicked() {
+                actions.save_current = Some(new_name.trim().to_string());
+                new_name.clear();
+                ui.close();
+            }
+        });
+    });
+    actions
+}
+
+/// 渲染模板菜单中的一项，点击时返回模板查询和是否追加
+fn template_button(ui: &mut egui::Ui, name: &str, query: &str) -> Option<(String, bool)> {
+    let clicked = ui
+        .button(name)
+        .on_hover_text(format!("{query}\n\nShift+click to append"))
+        .clicked();
+    if !clicked {
+        return None;
+    }
+    ui.close();
+    Some((query.to_string(), ui.input(|i| i.modifiers.shift)))
+}
+
+/// 按自定义列导出窗口的状态
+#[derive(Default)]
+pub struct ExportColumnsState {
+    /// 是否显示窗口
+    pub open: bool,
+    /// 按导出顺序排列的列名和是否导出
+    pub columns: Vec<(String, bool)>,
+    /// 导出格式
+    pub format: ExportFormat,
+}
+
+impl ExportColumnsState {
+    /// 按当前表格的列更新列表
+    ///
+    /// 已有的列保留顺序和选择，新出现的列追加到末尾并选中，表格中已没有的列被移除
+    pub fn sync(&mut self, columns: &[String]) {
+        self.columns.retain(|(name, _)| columns.contains(name));
+        for column in columns {
+            if !self.columns.iter().any(|(name, _)| name == column) {
+                self.columns.push((column.clone(), true));
+            }
+        }
+    }
+
+    /// 选中导出的列，按导出顺序
+    pub fn selected(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter(|(_, checked)| *checked)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+/// 渲染按自定义列导出的窗口，`columns` 为当前表格的列，点击导出时返回 `true`
+///
+/// 只改变导出文件中的列和顺序，不影响结果表格的显示
+pub fn render_export_columns_window(
+    ctx: &egui::Context,
+    state: &mut ExportColumnsState,
+    columns: &[String],
+) -> bool {
+    state.sync(columns);
+    let mut export = false;
+    let mut open = state.open;
+    egui::Window::new("Export Columns")
+        .open(&mut open)
+        .default_size([320.0, 420.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button("All").clicked() {
+                    state.columns.iter_mut().for_each(|(_, checked)| *checked = true);
+                }
+                if ui.small_button("None").clicked() {
+                    state.columns.iter_mut().for_each(|(_, checked)| *checked = false);
+                }
+                if ui
+                    .small_button("Table order")
+                    .on_hover_text("Restore the order of the result table")
+                    .clicked()
+                {
+                    state.columns.sort_by_key(|(name, _)| columns.iter().position(|c| c == name));
+                }
+            });
+            ui.weak("Drag ☰ to reorder");
+
+            // 拖动列名前的手柄调整导出顺序
+            let mut moved = None;
+            egui::ScrollArea::vertical()
+                .id_salt("export_columns")
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (index, (name, checked)) in state.columns.iter_mut().enumerate() {
+                        let row = ui
+                            .horizontal(|ui| {
+                                let id = ui.id().with(("export_column", index));
+                                ui.dnd_drag_source(id, index, |ui| ui.label("☰"));
+                                ui.checkbox(checked, name.as_str());
+                            })
+                            .response;
+                        if row.dnd_hover_payload::<usize>().is_some() {
+                            let stroke = ui.visuals().selection.stroke;
+                            ui.painter().hline(row.rect.x_range(), row.rect.top(), stroke);
+                        }
+                        if let Some(from) = row.dnd_release_payload::<usize>() {
+                            moved = Some((*from, index));
+                        }
+                    }
+                });
+            if let Some((from, to)) = moved {
+                let column = state.columns.remove(from);
+                state.columns.insert(to, column);
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Format:");
+                egui::ComboBox::from_id_salt("export_columns_format")
+                    .selected_text(state.format.label())
+                    .show_ui(ui, |ui| {
+                        for &format in ExportFormat::ALL {
+                            ui.selectable_value(&mut state.format, format, format.label());
+                        }
+                    });
+                let count = state.columns.iter().filter(|(_, checked)| *checked).count();
+                let button = egui::Button::new(format!("Export {} columns...", count));
+                if ui.add_enabled(count > 0, button).clicked() {
+                    export = true;
+                }
+            });
+        });
+    state.open = open;
+    export
+}
+
+/// 批量导出窗口的状态
+#[derive(Default)]
+pub struct BatchExportState {
+    /// 是否显示窗口
+    pub open: bool,
+    /// 选中的保存的查询索引
+    pub selected: BTreeSet<usize>,
+    /// 导出目录
+    pub folder: Option<PathBuf>,
+    /// 导出格式
+    pub format: ExportFormat,
+    /// 最近一次批量导出每个查询的结果：查询标题和导出的文件或错误信息
+    pub report: Vec<(String, Result<PathBuf, String>)>,
+}
+
+/// 批量导出窗口操作结果
+#[derive(Default)]
+pub struct BatchExportActions {
+    /// 是否选择导出目录
+    pub choose_folder: bool,
+    /// 是否开始导出
+    pub run: bool,
+}
+
+/// 渲染批量导出窗口
+///
+/// 只列出在当前连接中显示的保存的查询
+pub fn render_batch_export_window(
+    ctx: &egui::Context,
+    state: &mut BatchExportState,
+    queries: &[SavedQuery],
+    active_connection: &str,
+) -> BatchExportActions {
+    let mut actions = BatchExportActions::default();
+    let mut open = state.open;
+    egui::Window::new("Batch Export")
+        .open(&mut open)
+        .default_size([480.0, 420.0])
+        .show(ctx, |ui| {
+            let visible: Vec<usize> = queries
+                .iter()
+                .enumerate()
+                .filter(|(_, query)| query.is_visible_in(active_connection))
+                .map(|(index, _)| index)
+                .collect();
+            state.selected.retain(|index| visible.contains(index));
+
+            ui.horizontal(|ui| {
+                if ui.small_button("All").clicked() {
+                    state.selected.extend(visible.iter().copied());
+                }
+                if ui.small_button("None").clicked() {
+                    state.selected.clear();
+                }
+                ui.weak(format!("{} selected", state.selected.len()));
+            });
+            egui::ScrollArea::vertical()
+                .id_salt("batch_queries")
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    for &index in &visible {
+                        let mut checked = state.selected.contains(&index);
+                        if ui
+                            .checkbox(&mut checked, queries[index].title())
+                            .on_hover_text(&queries[index].query)
+                            .changed()
+                        {
+                            if checked {
+                                state.selected.insert(index);
+                            } else {
+                                state.selected.remove(&index);
+                            }
+                        }
+                    }
+                });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Folder:");
+                match &state.folder {
+                    Some(folder) => ui.monospace(folder.display().to_string()),
+                    None => ui.weak("not chosen"),
+                };
+                if ui.button("Choose...").clicked() {
+                    actions.choose_folder = true;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Format:");
+                egui::ComboBox::from_id_salt("batch_format")
+                    .selected_text(state.format.label())
+                    .show_ui(ui, |ui| {
+                        for &format in ExportFormat::ALL {
+                            ui.selectable_value(&mut state.format, format, format.label());
+                        }
+                    });
+            });
+            let ready = !state.selected.is_empty() && state.folder.is_some();
+            if ui.add_enabled(ready, egui::Button::new("Export")).clicked() {
+                actions.run = true;
+            }
+
+            if !state.report.is_empty() {
+                ui.separator();
+                let failed = state.report.iter().filter(|(_, r)| r.is_err()).count();
+                ui.strong(format!(
+                    "{} exported, {} failed",
+                    state.report.len() - failed,
+                    failed
+                ));
+                egui::ScrollArea::vertical()
+                    .id_salt("batch_report")
+                    .show(ui, |ui| {
+                        for (title, result) in &state.report {
+                            match result {
+                                Ok(path) => ui.label(format!("✔ {} → {}", title, path.display())),
+                                Err(e) => ui.colored_label(
+                                    StatusPalette::get(ui.ctx()).error,
+                                    format!("✖ {}: {}", title, e),
+                                ),
+                            };
+                        }
+                    });
+            }
+        });
+    state.open = open;
+    actions
+}
+
+/// 保存的查询检查结果中的一项
+pub struct ValidateEntry {
+    /// 查询标题
+    pub title: String,
+    /// WAQL 查询语句
+    pub query: String,
+    /// 结果数量或错误信息
+    pub result: Result<usize, String>,
+}
+
+/// 保存的查询检查窗口的状态
+#[derive(Default)]
+pub struct ValidateState {
+    /// 是否显示窗口
+    pub open: bool,
+    /// 等待执行的保存的查询索引，每帧执行一个以便显示进度
+    pub pending: VecDeque<usize>,
+    /// 本次检查的查询总数
+    pub total: usize,
+    /// 已执行的查询的结果，按执行顺序排列
+    pub report: Vec<ValidateEntry>,
+}
+
+impl ValidateState {
+    /// 是否正在检查
+    pub fn is_running(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// 失败的查询数量
+    pub fn failed(&self) -> usize {
+        self.report.iter().filter(|entry| entry.result.is_err()).count()
+    }
+
+    /// 将检查结果转换为表格，用于导出
+    pub fn to_table(&self) -> TableData {
+        let columns: Vec<String> = ["name", "query", "status", "count", "error"]
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        let rows: Vec<HashMap<String, String>> = self
+            .report
+            .iter()
+            .map(|entry| {
+                let (status, count, error) = match &entry.result {
+                    Ok(count) => ("pass", count.to_string(), String::new()),
+                    Err(e) => ("fail", String::new(), e.clone()),
+                };
+                let values = [
+                    entry.title.clone(),
+                    entry.query.clone(),
+                    status.to_string(),
+                    count,
+                    error,
+                ];
+                columns.iter().cloned().zip(values).collect()
+            })
+            .collect();
+        TableData {
+            columns,
+            total_rows: rows.len(),
+            rows,
+            typed_rows: Vec::new(),
+        }
+    }
+}
+
+/// 保存的查询检查窗口操作结果
+#[derive(Default)]
+pub struct ValidateActions {
+    /// 是否开始检查
+    pub run: bool,
+    /// 是否停止检查
+    pub cancel: bool,
+    /// 是否导出检查结果
+    pub export: bool,
+}
+
+/// 渲染保存的查询检查窗口
+///
+/// 依次执行当前连接中显示的所有保存的查询，列出每个查询是否成功、结果数量和错误信息
+pub fn render_validate_window(ctx: &egui::Context, state: &mut ValidateState) -> ValidateActions {
+    let mut actions = ValidateActions::default();
+    let mut open = state.open;
+    egui::Window::new("Validate Library")
+        .open(&mut open)
+        .default_size([560.0, 420.0])
+        .show(ctx, |ui| {
+            let running = state.is_running();
+            ui.horizontal(|ui| {
+                if ui.add_enabled(!running, egui::Button::new("Run all")).clicked() {
+                    actions.run = true;
+                }
+                if ui.add_enabled(running, egui::Button::new("Stop")).clicked() {
+                    actions.cancel = true;
+                }
+                let can_export = !running && !state.report.is_empty();
+                if ui.add_enabled(can_export, egui::Button::new("Export CSV...")).clicked() {
+                    actions.export = true;
+                }
+            });
+            if running {
+                let done = state.report.len();
+                ui.add(
+                    egui::ProgressBar::new(done as f32 / state.total.max(1) as f32)
+                        .text(format!("{}/{}", done, state.total)),
+                );
+            }
+            if state.report.is_empty() {
+                if !running {
+                    ui.weak("Runs every saved query once and reports which ones fail");
+                }
+                return;
+            }
+
+            ui.separator();
+            let failed = state.failed();
+            ui.strong(format!("{} passed, {} failed", state.report.len() - failed, failed));
+            egui::ScrollArea::vertical()
+                .id_salt("validate_report")
+                .show(ui, |ui| {
+                    egui::Grid::new("validate_grid").striped(true).show(ui, |ui| {
+                        for entry in &state.report {
+                            match &entry.result {
+                                Ok(count) => {
+                                    ui.label("✔");
+                                    ui.label(&entry.title).on_hover_text(&entry.query);
+                                    ui.label(format!("{} rows", count));
+                                }
+                                Err(e) => {
+                                    let color = StatusPalette::get(ui.ctx()).error;
+                                    ui.colored_label(color, "✖");
+                                    ui.label(&entry.title).on_hover_text(&entry.query);
+                                    ui.colored_label(color, e);
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+        });
+    state.open = open;
+    actions
+}
+
+/// WAAPI 调用窗口的状态
+#[derive(Default)]
+pub struct RpcState {
+    /// WAAPI 接口
+    pub uri: String,
+    /// JSON 格式的调用参数
+    pub args: String,
+    /// 是否直接编辑完整的 JSON-RPC 请求，响应显示在结果面板中
+    pub raw: bool,
+    /// 完整的 JSON-RPC 请求文本（`uri`、`args` 和 `options`）
+    pub raw_request: String,
+    /// 等待确认的调用，确认前不会发送
+    pub pending: Option<RpcRequest>,
+    /// 最近一次调用的响应或错误信息
+    pub response: String,
+    /// 最近一次调用是否出错
+    pub has_error: bool,
+}
+
+/// WAAPI 调用窗口操作结果
+#[derive(Default)]
+pub struct RpcActions {
+    /// 是否发送调用（修改操作可能需要先确认）
+    pub send: bool,
+    /// 是否确认发送等待确认的调用
+    pub confirm: bool,
+}
+
+/// 渲染 WAAPI 调用窗口
+///
+/// 有等待确认的调用时显示将要发送的内容，并禁止编辑接口和参数
+pub fn render_rpc_window(ctx: &egui::Context, open: &mut bool, state: &mut RpcState) -> RpcActions {
+    let mut actions = RpcActions::default();
+    egui::Window::new("WAAPI Call")
+        .open(open)
+        .default_size([500.0, 400.0])
+        .show(ctx, |ui| {
+            let editable = state.pending.is_none();
+            ui.add_enabled_ui(editable, |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut state.raw, false, "URI and args");
+                    ui.selectable_value(&mut state.raw, true, "Raw JSON-RPC")
+                        .on_hover_text("Type the whole request; the response opens in the results");
+                });
+            });
+            ui.separator();
+
+            let can_send = if state.raw {
+                render_raw_rpc_editor(ui, editable, &mut state.raw_request)
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label("URI:");
+                    ui.add_enabled(
+                        editable,
+                        TextEdit::singleline(&mut state.uri)
+                            .hint_text("ak.wwise.core.object.setProperty")
+                            .desired_width(f32::INFINITY),
+                    );
+                });
+                ui.label("Args (JSON):");
+                ui.add_enabled(
+                    editable,
+                    TextEdit::multiline(&mut state.args)
+                        .code_editor()
+                        .hint_text("{}")
+                        .desired_rows(6)
+                        .desired_width(f32::INFINITY),
+                );
+                !state.uri.trim().is_empty()
+            };
+
+            if let Some(request) = &state.pending {
+                ui.separator();
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    "⚠ This call may modify the project. Review the payload before sending:",
+                );
+                let mut text = serde_json::to_string_pretty(&request.to_json()).unwrap_or_default();
+                egui::ScrollArea::vertical()
+                    .id_salt("rpc_preview")
+                    .max_height(160.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            TextEdit::multiline(&mut text)
+                                .code_editor()
+                                .interactive(false)
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+                ui.horizontal(|ui| {
+                    if ui.button("Confirm and send").clicked() {
+                        actions.confirm = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        state.pending = None;
+                    }
+                });
+            } else if ui.add_enabled(can_send, egui::Button::new("Send")).clicked() {
+                actions.send = true;
+            }
+
+            if !state.response.is_empty() {
+                ui.separator();
+                let color = if state.has_error {
+                    StatusPalette::get(ui.ctx()).error
+                } else {
+                    ui.visuals().text_color()
+                };
+                egui::ScrollArea::vertical()
+                    .id_salt("rpc_response")
+                    .show(ui, |ui| {
+                        ui.colored_label(color, egui::RichText::new(&state.response).monospace());
+                    });
+            }
+        });
+    actions
+}
+
+/// 渲染完整 JSON-RPC 请求的编辑框，并在下方显示校验错误
+///
+/// 返回请求是否可以发送
+fn render_raw_rpc_editor(ui: &mut egui::Ui, editable: bool, text: &mut String) -> bool {
+    ui.label("Request (JSON object with uri, args and options):");
+    ui.add_enabled(
+        editable,
+        TextEdit::multiline(text)
+            .code_editor()
+            .hint_text(r#"{"uri": "ak.wwise.core.getInfo", "args": {}}"#)
+            .desired_rows(8)
+            .desired_width(f32::INFINITY),
+    );
+    if text.trim().is_empty() {
+        return false;
+    }
+    match RpcRequest::parse(text) {
+        Ok(_) => {
+            ui.weak("The response is shown in the results panel.");
+            true
+        }
+        Err(e) => {
+            ui.colored_label(StatusPalette::get(ui.ctx()).error, format!("✖ {}", e));
+            false
+        }
+    }
+}
+
+/// 大查询确认窗口中的选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LargeQueryChoice {
+    /// 直接执行
+    Run,
+    /// 追加 `take` 子句后执行
+    AddTake,
+    /// 取消执行
+    Cancel,
+}
+
+/// 渲染大查询确认窗口
+///
+/// `kind` 为查询中对象数量很多的类型，`limit` 为建议追加的 `take` 数量
+pub fn render_large_query_window(
+    ctx: &egui::Context,
+    query: &str,
+    kind: &str,
+    limit: usize,
+) -> Option<LargeQueryChoice> {
+    let mut choice = None;
+    egui::Window::new("Large query")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "⚠ This query has no where filter and lists every {kind}. \
+                     On a large project it can return tens of thousands of objects."
+                ),
+            );
+            ui.monospace(query);
+            ui.label("Consider adding a where filter, or limit the number of objects with take.");
+            ui.horizontal(|ui| {
+                if ui.button(format!("Add take {limit} and run")).clicked() {
+                    choice = Some(LargeQueryChoice::AddTake);
+                }
+                if ui.button("Run anyway").clicked() {
+                    choice = Some(LargeQueryChoice::Run);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(LargeQueryChoice::Cancel);
+                }
+            });
+        });
+    choice
+}
+
+/// 保存相似查询时的选择
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimilarQueryChoice {
+    /// 用新的查询替换已有的查询
+    Update,
+    /// 作为新的查询保存
+    SaveNew,
+    /// 取消保存
+    Cancel,
+}
+
+/// 渲染保存相似查询的确认窗口
+pub fn render_similar_query_window(
+    ctx: &egui::Context,
+    query: &str,
+    existing: &SavedQuery,
+) -> Option<SimilarQueryChoice> {
+    let mut choice = None;
+    egui::Window::new("Similar saved query")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!("⚠ Similar to an existing saved query: {}", existing.title()),
+            );
+            ui.label("Existing:");
+            ui.monospace(&existing.query);
+            ui.label("New:");
+            ui.monospace(query);
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Update existing")
+                    .on_hover_text("Replace the existing query text, keeping its name and tags")
+                    .clicked()
+                {
+                    choice = Some(SimilarQueryChoice::Update);
+                }
+                if ui.button("Save as new").clicked() {
+                    choice = Some(SimilarQueryChoice::SaveNew);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(SimilarQueryChoice::Cancel);
+                }
+            });
+        });
+    choice
+}
+
+/// 渲染重新加载配置的确认窗口
+///
+/// 返回 `Some(true)` 表示重新加载，`Some(false)` 表示取消
+pub fn render_reload_config_window(ctx: &egui::Context, changed_on_disk: bool) -> Option<bool> {
+    let mut choice = None;
+    egui::Window::new("Reload config")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(ctx, |ui| {
+            ui.label("Reload user_data.json from disk? Unsaved changes in the app are discarded.");
+            if changed_on_disk {
+                ui.colored_label(
+                    egui::Color32::YELLOW,
+                    "⚠ The file was modified outside the app since the last save.",
+                );
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Reload").clicked() {
+                    choice = Some(true);
+                }
+                if ui.button("Cancel").clicked() {
+                    choice = Some(false);
+                }
+            });
+        });
+    choice
+}
+
+/// 帮助窗口中列出的快捷键和说明
+const SHORTCUTS: [(&str, &str); 13] = [
+    ("Enter", "Run the query (when the Enter key setting is \"Run query\")"),
+    ("Shift+Enter", "Insert a newline in the multi-line editor when Enter runs"),
+    (
+        "Ctrl+Enter",
+        "Run the query; in the multi-line editor, the selection or the statement at the caret",
+    ),
+    ("Tab", "Accept the highlighted completion, or the first one"),
+    ("↑ / ↓", "Highlight a completion; Enter then accepts it instead of running"),
+    ("Esc", "Dismiss the completion popup"),
+    ("Ctrl+Space", "Open completion regardless of how much has been typed"),
+    ("Ctrl+P", "Open the insert palette"),
+    ("Ctrl+T", "Cycle the editor theme"),
+    ("Ctrl+Z / Ctrl+Y", "Undo / redo editor changes"),
+    ("↑ at start / ↓ at end", "Browse query history (single-line editor)"),
+    ("F5", "Re-run the last executed query, whatever the editor contains"),
+    ("F1", "Show this help"),
+];
+
+/// 检查器窗口操作结果
+#[derive(Default)]
+pub struct InspectorActions {
+    /// 是否清空请求记录
+    pub clear: bool,
+    /// 需要复制到剪贴板的请求或响应
+    pub copied: Option<String>,
+}
+
+/// 渲染检查器窗口，列出最近的 WAAPI 请求和响应，最新的在前
+pub fn render_inspector_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    exchanges: &VecDeque<Exchange>,
+) -> InspectorActions {
+    let mut actions = InspectorActions::default();
+    egui::Window::new("Inspector")
+        .open(open)
+        .default_size([600.0, 400.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label(format!("{} requests", exchanges.len()));
+                if ui.button("Clear").clicked() {
+                    actions.clear = true;
+                }
+            });
+            ui.separator();
+            egui::ScrollArea::vertical().auto_shrink([false; 2]).show(ui, |ui| {
+                for (index, exchange) in exchanges.iter().enumerate().rev() {
+                    let uri = exchange.request["uri"].as_str().unwrap_or("?");
+                    let palette = StatusPalette::get(ui.ctx());
+                    let ok = exchange.response.is_ok();
+                    let status = palette.text(ok, if ok { "OK" } else { "Error" });
+                    let color = palette.color(ok);
+                    let title = egui::RichText::new(format!(
+                        "{} · {} ms · {}",
+                        uri,
+                        exchange.elapsed.as_millis(),
+                        status
+                    ))
+                    .color(color);
+                    egui::CollapsingHeader::new(title)
+                        .id_salt(("exchange", index))
+                        .show(ui, |ui| {
+                            let request = serde_json::to_string_pretty(&exchange.request)
+                                .unwrap_or_default();
+                            let response = match &exchange.response {
+                                Ok(value) => {
+                                    serde_json::to_string_pretty(value).unwrap_or_default()
+                                }
+                                Err(message) => message.clone(),
+                            };
+                            for (label, text) in [("Request", request), ("Response", response)] {
+                                ui.horizontal(|ui| {
+                                    ui.strong(label);
+                                    if ui.small_button("Copy").clicked() {
+                                        actions.copied = Some(text.clone());
+                                    }
+                                });
+                                let (shown, truncated) =
+                                    truncate_for_display(&text, RAW_VIEW_LIMIT);
+                                ui.monospace(shown);
+                                if truncated {
+                                    ui.weak("… (truncated, copy to see all)");
+                                }
+                            }
+                        });
+                }
+            });
+        });
+    actions
+}
+
+/// `where` 条件构建窗口的状态
+#[derive(Default)]
+pub struct WhereBuilderState {
+    /// 窗口是否打开
+    pub open: bool,
+    /// 编辑中的条件
+    pub condition: WhereCondition,
+}
+
+/// 渲染 `where` 条件构建窗口
+///
+/// 属性可以从补全使用的访问器和属性（带 `@` 前缀）中选择，也可以直接输入。
+/// 点击"Append"时返回要追加到查询中的条件
+pub fn render_where_builder(
+    ctx: &egui::Context,
+    state: &mut WhereBuilderState,
+    has_code: bool,
+) -> Option<WhereCondition> {
+    let mut append = None;
+    let condition = &mut state.condition;
+    egui::Window::new("Where Builder")
+        .open(&mut state.open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.add(
+                    TextEdit::singleline(&mut condition.property)
+                        .hint_text("property")
+                        .desired_width(160.0),
+                );
+                ui.menu_button("▾", |ui| {
+                    egui::ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                        let properties = WAAPI_PROPERTIES.iter().map(|p| format!("@{}", p));
+                        let names = WAAPI_ACCESSORS.iter().map(|a| a.to_string()).chain(properties);
+                        for name in names {
+                            if ui.button(&name).clicked() {
+                                condition.property = name;
+                                ui.close();
+                            }
+                        }
+                    });
+                });
+                egui::ComboBox::from_id_salt("where_op")
+                    .selected_text(condition.op.label())
+                    .width(80.0)
+                    .show_ui(ui, |ui| {
+                        for op in WhereOp::ALL {
+                            ui.selectable_value(&mut condition.op, op, op.label());
+                        }
+                    });
+                ui.add(
+                    TextEdit::singleline(&mut condition.value)
+                        .hint_text("value")
+                        .desired_width(160.0),
+                );
+            });
+            let valid = !condition.property.trim().is_empty();
+            if valid {
+                ui.monospace(condition.to_waql());
+            }
+            if ui
+                .add_enabled(valid && has_code, egui::Button::new("Append"))
+                .on_hover_text("Add as a `where` clause, or with `and` after an existing one")
+                .clicked()
+            {
+                append = Some(condition.clone());
+            }
+        });
+    append
+}
+
+/// 渲染快捷键帮助窗口
+pub fn render_help_window(ctx: &egui::Context, open: &mut bool) {
+    egui::Window::new("Keyboard Shortcuts")
+        .open(open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("shortcuts").num_columns(2).striped(true).show(ui, |ui| {
+                for (keys, description) in SHORTCUTS {
+                    ui.monospace(keys);
+                    ui.label(description);
+                    ui.end_row();
+                }
+            });
+        });
+}
+
+/// 关于面板中显示的环境信息，用于反馈问题
+pub struct Diagnostics {
+    /// 工具版本
+    pub version: &'static str,
+    /// Wwise 版本，获取失败时为错误信息
+    pub wwise_version: Result<String, String>,
+    /// 配置文件路径
+    pub config_path: PathBuf,
+    /// 当前连接名称和地址
+    pub connection: String,
+    /// 是否处于离线演示模式
+    pub offline: bool,
+    /// 编辑器主题名称
+    pub theme: String,
+    /// 字体大小
+    pub fontsize: f32,
+}
+
+impl Diagnostics {
+    /// 以 `名称: 值` 的形式逐行列出
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        let wwise = match &self.wwise_version {
+            Ok(version) => version.clone(),
+            Err(e) => format!("unavailable ({})", e),
+        };
+        vec![
+            ("WAQL Tool", self.version.to_string()),
+            ("Wwise", wwise),
+            ("OS", std::env::consts::OS.to_string()),
+            ("Config", self.config_path.display().to_string()),
+            ("Connection", self.connection.clone()),
+            ("Offline mode", self.offline.to_string()),
+            ("Theme", self.theme.clone()),
+            ("Font size", self.fontsize.to_string()),
+        ]
+    }
+
+    /// 转换为复制到剪贴板的文本
+    fn to_text(&self) -> String {
+        self.entries()
+            .into_iter()
+            .map(|(name, value)| format!("{}: {}\n", name, value))
+            .collect()
+    }
+}
+
+/// 渲染关于面板
+///
+/// # Returns
+///
+/// 点击"Copy diagnostics"时返回需要复制的诊断信息
+pub fn render_about_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    diagnostics: &Diagnostics,
+) -> Option<String> {
+    let mut copied = None;
+    egui::Window::new("About")
+        .open(open)
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("diagnostics").num_columns(2).show(ui, |ui| {
+                for (name, value) in diagnostics.entries() {
+                    ui.strong(name);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            if ui.button("Copy diagnostics").clicked() {
+                copied = Some(diagnostics.to_text());
+            }
+        });
+    copied
+}
+
+/// 成功和错误状态使用的颜色
+///
+/// 每帧由主程序按设置存入 egui 内存，渲染时通过 [`StatusPalette::get`] 读取
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusPalette {
+    /// 成功的颜色
+    pub success: egui::Color32,
+    /// 错误的颜色
+    pub error: egui::Color32,
+    /// 是否在状态文本前显示 ✔/✖ 图标
+    pub icons: bool,
+}
+
+impl StatusPalette {
+    /// 默认的绿色/红色
+    pub const STANDARD: Self = Self {
+        success: egui::Color32::GREEN,
+        error: egui::Color32::RED,
+        icons: false,
+    };
+
+    /// 红绿色盲也容易区分的蓝色/橙色（Okabe-Ito 配色）
+    pub const COLORBLIND: Self = Self {
+        success: egui::Color32::from_rgb(86, 180, 233),
+        error: egui::Color32::from_rgb(230, 159, 0),
+        icons: true,
+    };
+
+    /// 按设置选择配色
+    pub fn new(colorblind: bool) -> Self {
+        if colorblind { Self::COLORBLIND } else { Self::STANDARD }
+    }
+
+    fn id() -> egui::Id {
+        egui::Id::new("status_palette")
+    }
+
+    /// 存入 egui 内存
+    pub fn store(self, ctx: &egui::Context) {
+        ctx.data_mut(|d| d.insert_temp(Self::id(), self));
+    }
+
+    /// 读取当前配色，没有存入时使用 [`StatusPalette::STANDARD`]
+    pub fn get(ctx: &egui::Context) -> Self {
+        ctx.data(|d| d.get_temp(Self::id())).unwrap_or(Self::STANDARD)
+    }
+
+    /// 成功或错误的颜色
+    pub fn color(&self, ok: bool) -> egui::Color32 {
+        if ok { self.success } else { self.error }
+    }
+
+    /// 状态文本，开启图标时在前面加上 ✔/✖
+    pub fn text(&self, ok: bool, text: &str) -> String {
+        match (self.icons, ok) {
+            (false, _) => text.to_string(),
+            (true, true) => format!("✔ {}", text),
+            (true, false) => format!("✖ {}", text),
+        }
+    }
+
+    /// 调整着色规则的颜色：色盲配色下偏绿的颜色换成成功颜色，偏红的颜色换成错误颜色
+    pub fn adapt(&self, color: egui::Color32) -> egui::Color32 {
+        if *self == Self::STANDARD {
+            return color;
+        }
+        let [r, g, b, _] = color.to_array();
+        let (r, g, b) = (u16::from(r), u16::from(g), u16::from(b));
+        if g > r + 40 && g > b + 40 {
+            self.success
+        } else if r > g * 2 && r > b * 2 {
+            self.error
+        } else {
+            color
+        }
+    }
+}
+
+/// 控制按钮栏显示时的状态
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ControlButtonState {
+    /// 编辑器中是否有查询
+    pub has_code: bool,
+    /// 是否有结果（JSON 文本或表格）
+    pub has_results: bool,
+    /// 是否有表格数据
+    pub has_table_data: bool,
+    /// 当前结果是否为错误信息
+    pub has_error: bool,
+    /// 是否处于离线演示模式
+    pub offline: bool,
+    /// 是否使用多行编辑器
+    pub multiline: bool,
+}
+
+/// 渲染控制按钮栏
+pub fn render_control_buttons(
+    ui: &mut egui::Ui,
+    state: ControlButtonState,
+    show_config_panel: &mut bool,
+    status_message: &str,
+) -> ControlButtonActions {
+    let ControlButtonState {
+        has_code,
+        has_results,
+        has_table_data,
+        has_error,
+        offline,
+        multiline,
+    } = state;
+    let mut actions = ControlButtonActions::default();
+
+    ui.horizontal(|ui| {
+        // 运行按钮
+        if ui.add_enabled(has_code, egui::Button::new("Run WAQL")).clicked() {
+            actions.run_query = true;
+        }
+
+        // 运行选中内容按钮（多行模式）
+        if multiline
+            && ui
+                .add_enabled(has_code, egui::Button::new("Run Selection"))
+                .on_hover_text("Run the selected text, or the statement at the caret (Ctrl+Enter)")
+                .clicked()
+        {
+            actions.run_selection = true;
+        }
+
+        // 保存按钮
+        if ui.add_enabled(has_code, egui::Button::new("Save WAQL")).clicked() {
+            actions.save_query = true;
+        }
+
+        // 导出 CSV 按钮
+        if ui.add_enabled(has_table_data, egui::Button::new("Export CSV")).clicked() {
+            actions.export_csv = true;
+        }
+
+        // 运行、导出 CSV 并在文件管理器中显示
+        let report_button = ui
+            .add_enabled(has_code, egui::Button::new("Run + Export"))
+            .on_hover_text(
+                "Run the query, export the table to CSV and reveal it in the file manager. \
+                 Right-click to choose the export file",
+            );
+        if report_button.clicked() {
+            actions.run_export_reveal = true;
+        }
+        report_button.context_menu(|ui| {
+            if ui.button("Choose export file...").clicked() {
+                actions.choose_report_path = true;
+                ui.close();
+            }
+        });
+
+        // 导出 JSON 按钮
+        if ui
+            .add_enabled(has_results && !has_error, egui::Button::new("Export JSON"))
+            .clicked()
+        {
+            actions.export_json = true;
+        }
+
+        // 导出 NDJSON 按钮
+        if ui
+            .add_enabled(has_table_data, egui::Button::new("Export NDJSON"))
+            .on_hover_text("Export the table as newline-delimited JSON, one object per row")
+            .clicked()
+        {
+            actions.export_ndjson = true;
+        }
+
+        // 导出 Parquet 按钮
+        #[cfg(feature = "parquet")]
+        if ui
+            .add_enabled(has_table_data, egui::Button::new("Export Parquet"))
+            .on_hover_text("Export the table as Parquet with inferred column types")
+            .clicked()
+        {
+            actions.export_parquet = true;
+        }
+
+        // 选择导出的列和顺序
+        if ui
+            .add_enabled(has_table_data, egui::Button::new("Export Columns..."))
+            .on_hover_text("Choose and reorder the columns to export")
+            .clicked()
+        {
+            actions.export_columns = true;
+        }
+
+        // 导出列类型描述按钮
+        if ui
+            .add_enabled(has_table_data, egui::Button::new("Export Schema"))
+            .on_hover_text("Export column names and inferred types as JSON")
+            .clicked()
+        {
+            actions.export_schema = true;
+        }
+
+        // 导出列统计按钮
+        if ui
+            .add_enabled(has_table_data, egui::Button::new("Export Stats"))
+            .on_hover_text("Export per-column count, min, max, avg and distinct count as CSV")
+            .clicked()
+        {
+            actions.export_stats = true;
+        }
+
+        // 导出报告菜单
+        ui.add_enabled_ui(has_table_data, |ui| {
+            ui.menu_button("Export Report", |ui| {
+                for format in ReportFormat::ALL {
+                    if ui.button(format.label()).clicked() {
+                        actions.export_report = Some(format);
+                        ui.close();
+                    }
+                }
+            })
+            .response
+            .on_hover_text("Export the query, connection info and result table as one document");
+        });
+
+        // 清空按钮
+        if ui.add_enabled(has_results, egui::Button::new("Clear Results")).clicked() {
+            actions.clear_results = true;
+        }
+
+        ui.separator();
+
+        // 显示/隐藏配置按钮
+        let config_button_text = if *show_config_panel {
+            "Hide Config"
+        } else {
+            "Show Config"
+        };
+        if ui.button(config_button_text).clicked() {
+            *show_config_panel = !*show_config_panel;
+        }
+        if ui
+            .button("WAAPI Call")
+            .on_hover_text("Call any WAAPI function with JSON arguments")
+            .clicked()
+        {
+            actions.show_rpc = true;
+        }
+        if ui
+            .button("Inspector")
+            .on_hover_text("Show recent WAAPI requests and responses")
+            .clicked()
+        {
+            actions.toggle_inspector = true;
+        }
+        if ui
+            .add_enabled(has_code, egui::Button::new("Where..."))
+            .on_hover_text("Build a `where` condition and append it to the query")
+            .clicked()
+        {
+            actions.show_where_builder = true;
+        }
+        if ui.button("?").on_hover_text("Keyboard shortcuts (F1)").clicked() {
+            actions.show_help = true;
+        }
+        if ui.button("About").clicked() {
+            actions.show_about = true;
+        }
+
+        // 离线模式提示
+        if offline {
+            ui.separator();
+            ui.colored_label(egui::Color32::YELLOW, "Offline demo");
+        }
+
+        // 状态消息显示
+        if !status_message.is_empty() {
+            ui.separator();
+            let palette = StatusPalette::get(ui.ctx());
+            ui.colored_label(palette.color(!has_error), palette.text(!has_error, status_message));
+        }
+    });
+
+    actions
+}
+
+/// 控制按钮操作结果
+#[derive(Default)]
+pub struct ControlButtonActions {
+    /// 是否运行查询
+    pub run_query: bool,
+    /// 是否运行选中内容或光标所在语句
+    pub run_selection: bool,
+    /// 是否保存查询
+    pub save_query: bool,
+    /// 是否导出 CSV
+    pub export_csv: bool,
+    /// 是否运行查询、导出 CSV 并在文件管理器中显示
+    pub run_export_reveal: bool,
+    /// 是否重新选择一键导出的 CSV 文件路径
+    pub choose_report_path: bool,
+    /// 是否导出 JSON
+    pub export_json: bool,
+    /// 是否导出 NDJSON
+    pub export_ndjson: bool,
+    /// 是否导出 Parquet
+    #[cfg(feature = "parquet")]
+    pub export_parquet: bool,
+    /// 是否打开按自定义列导出的窗口
+    pub export_columns: bool,
+    /// 是否导出列类型描述
+    pub export_schema: bool,
+    /// 是否导出列统计信息
+    pub export_stats: bool,
+    /// 需要导出的报告格式
+    pub export_report: Option<ReportFormat>,
+    /// 是否清空结果
+    pub clear_results: bool,
+    /// 是否打开关于面板
+    pub show_about: bool,
+    /// 是否打开快捷键帮助
+    pub show_help: bool,
+    /// 是否打开 `where` 条件构建窗口
+    pub show_where_builder: bool,
+    /// 是否切换检查器窗口的显示
+    pub toggle_inspector: bool,
+    /// 是否打开 WAAPI 调用窗口
+    pub show_rpc: bool,
+}
+
+/// 结果区域操作结果
+#[derive(Default)]
+pub struct ResultActions {
+    /// 需要复制到剪贴板的文本（单元格原始值或导出的行）
+    pub copied_value: Option<String>,
+    /// 是否切换了用于生成表格的顶层键
+    pub table_key_changed: bool,
+    /// 需要查看子对象的对象 GUID
+    pub show_children: Option<String>,
+    /// 需要用系统默认程序打开的文件路径
+    pub open_path: Option<String>,
+    /// 需要在文件管理器中显示的文件路径
+    pub reveal_path: Option<String>,
+    /// 需要复制到剪贴板的 WAQL 对象引用列表和其中值的个数
+    pub copied_list: Option<(String, usize)>,
+    /// 冻结的列数是否改变
+    pub frozen_columns_changed: bool,
+    /// 是否修改了保存的列宽
+    pub column_widths_changed: bool,
+    /// 是否在单独的窗口中打开当前表格的副本
+    pub pop_out: bool,
+}
+
+/// 渲染结果显示区域
+///
+/// `rules` 为表格单元格的着色规则，无效的规则会被忽略
+pub fn render_results(
+    ui: &mut egui::Ui,
+    result: &str,
+    table_data: &Option<TableData>,
+    has_error: bool,
+    view: &mut TableViewState,
+    theme: &ColorTheme,
+    rules: &[CellRule],
+) -> ResultActions {
+    let mut actions = ResultActions::default();
+
+    // 结果包含多个数组时选择用于生成表格的键
+    if !has_error && view.array_keys.len() > 1 {
+        ui.horizontal(|ui| {
+            ui.label("Table from:");
+            egui::ComboBox::from_id_salt("table_key")
+                .selected_text(view.table_key.as_str())
+                .show_ui(ui, |ui| {
+                    for key in &view.array_keys {
+                        if ui
+                            .selectable_value(&mut view.table_key, key.clone(), key)
+                            .changed()
+                        {
+                            actions.table_key_changed = true;
+                        }
+                    }
+                });
+        });
+    }
+
+    // 结果视图切换：表格、原始 JSON 和树形视图
+    if !has_error && !result.is_empty() {
+        ui.horizontal(|ui| {
+            ui.label("View:");
+            for option in ResultView::ALL {
+                let enabled = option != ResultView::Table || table_data.is_some();
+                ui.add_enabled_ui(enabled, |ui| {
+                    ui.selectable_value(&mut view.result_view, option, option.label());
+                });
+            }
+        });
+    }
+    // 选择表格视图但结果不是表格时显示原始 JSON
+    let table_data = match view.result_view {
+        ResultView::Table => table_data.as_ref(),
+        ResultView::Raw | ResultView::Tree => None,
+    };
+
+    // 表格工具栏，筛选后的行在筛选框更新后计算一次，同时用于复制和表格显示
+    let mut indices = Vec::new();
+    if let (false, Some(data)) = (has_error, table_data) {
+        if data.is_truncated() {
+            ui.colored_label(
+                egui::Color32::YELLOW,
+                format!(
+                    "⚠ Showing first {} of {} rows (max rows limit) — table and exports are truncated",
+                    data.rows.len(),
+                    data.total_rows
+                ),
+            );
+        }
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.add(
+                TextEdit::singleline(&mut view.filter)
+                    .hint_text("Filter rows...")
+                    .desired_width(200.0),
+            );
+            if !view.filter.is_empty() && ui.small_button("✖").clicked() {
+                view.filter.clear();
+            }
+            ui.checkbox(&mut view.show_aggregates, "Totals");
+            ui.label("Group by:");
+            let previous = view.group_by.clone();
+            egui::ComboBox::from_id_salt("group_by")
+                .selected_text(view.group_by.as_deref().unwrap_or("(none)"))
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut view.group_by, None, "(none)");
+                    for column in &data.columns {
+                        ui.selectable_value(&mut view.group_by, Some(column.clone()), column);
+                    }
+                });
+            if view.group_by != previous {
+                view.collapsed_groups.clear();
+            }
+            let fit = ui
+                .small_button("Fit columns")
+                .on_hover_text("Size columns to their content; right-click to reset widths");
+            if fit.clicked() {
+                let indices = data.filtered_indices(&view.filter);
+                view.column_widths.extend(fit_column_widths(ui, data, &indices));
+                view.reset_widths = true;
+                actions.column_widths_changed = true;
+            }
+            fit.context_menu(|ui| {
+                if ui.button("Reset widths").clicked() {
+                    view.column_widths.clear();
+                    view.reset_widths = true;
+                    actions.column_widths_changed = true;
+                    ui.close();
+                }
+            });
+            ui.label("Pin:");
+            if ui
+                .add(egui::DragValue::new(&mut view.frozen_columns).range(0..=data.columns.len()))
+                .on_hover_text("Number of leftmost columns kept visible while scrolling right")
+                .changed()
+            {
+                actions.frozen_columns_changed = true;
+            }
+            if ui
+                .small_button("Pop out")
+                .on_hover_text("Open a snapshot of this table in its own window")
+                .clicked()
+            {
+                actions.pop_out = true;
+            }
+            indices = data.filtered_indices(&view.filter);
+            if !view.filter.is_empty() {
+                ui.weak(format!("{} / {} rows", indices.len(), data.rows.len()));
+            }
+            // 复制当前筛选后的行
+            if ui.small_button("Copy TSV").clicked() {
+                actions.copied_value = Some(data.filtered(&indices).to_tsv_string());
+            }
+            if ui.small_button("Copy CSV").clicked() {
+                actions.copied_value = Some(data.filtered(&indices).to_csv_string());
+            }
+            if ui.small_button("Copy JSON").clicked() {
+                actions.copied_value = Some(data.filtered(&indices).to_json_string());
+            }
+            // 按表格显示顺序复制列名，便于编写 `select` 或 `return`
+            if ui
+                .small_button("Copy column names")
+                .on_hover_text("Copy the column names as a comma-separated list")
+                .clicked()
+            {
+                actions.copied_value = Some(data.columns.join(", "));
+            }
+            // 默认使用 id 列，没有 id 列时从菜单中选择键列
+            let mut list_column = None;
+            if data.columns.iter().any(|c| c == "id") {
+                let response = ui
+                    .small_button("Copy WAQL list")
+                    .on_hover_text("Quoted ids for `$ from object ...`; right-click to pick a column");
+                if response.clicked() {
+                    list_column = Some("id");
+                }
+                response.context_menu(|ui| column_menu(ui, &data.columns, &mut list_column));
+            } else {
+                ui.menu_button("Copy WAQL list", |ui| {
+                    column_menu(ui, &data.columns, &mut list_column)
+                });
+            }
+            if let Some(column) = list_column {
+                let filtered = data.filtered(&indices);
+                let values = filtered.distinct_values(column);
+                let list: Vec<String> = values.iter().map(|value| quote_string(value)).collect();
+                actions.copied_list = Some((list.join(", "), list.len()));
+            }
+        });
+    }
+
+    // 有冻结列时表格自行处理横向滚动
+    let pinned = !has_error && table_data.is_some() && view.frozen_columns > 0;
+    egui::ScrollArea::new([!pinned, true])
+        .auto_shrink([false; 2])
+        .show(ui, |ui| {
+            if has_error {
+                // 显示错误信息
+                let palette = StatusPalette::get(ui.ctx());
+                ui.colored_label(palette.error, palette.text(false, result));
+            } else if let Some(data) = table_data {
+                // 显示表格，列信息菜单关闭后丢弃缓存的统计，下次打开时按当前结果重新计算
+                view.column_info_open = false;
+                let table_actions = render_table(ui, result, data, &indices, view, rules);
+                if !view.column_info_open {
+                    view.column_info = None;
+                }
+                actions.copied_value = table_actions.copied_value;
+                actions.show_children = table_actions.show_children;
+                actions.open_path = table_actions.open_path;
+                actions.reveal_path = table_actions.reveal_path;
+            } else if view.result_view == ResultView::Tree {
+                // 显示树形视图
+                match view.parsed_json.parse(result) {
+                    Some(value) => render_json_children(ui, "", value, &mut actions),
+                    None => {
+                        ui.colored_label(egui::Color32::YELLOW, "The result is not valid JSON");
+                    }
+                }
+            } else {
+                // 显示原始 JSON，过大时截断显示
+                if let Some(depth) = view.json_max_depth {
+                    ui.weak(format!(
+                        "Values nested deeper than {} levels are collapsed — export to see all",
+                        depth
+                    ));
+                }
+                let text = raw_view_text(result, view);
+                let (mut shown, truncated) = truncate_for_display(text, RAW_VIEW_LIMIT);
+                if truncated {
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!(
+                            "Showing first {} KB of {} KB — export to see all",
+                            RAW_VIEW_LIMIT / 1024,
+                            text.len() / 1024
+                        ),
+                    );
+                }
+                // 只读文本框：自动换行，可以选择和复制文本，按当前主题高亮
+                ui.add(
+                    TextEdit::multiline(&mut shown)
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(f32::INFINITY)
+                        .layouter(&mut |ui: &egui::Ui, text: &dyn TextBuffer, wrap_width| {
+                            let mut layout_job = LayoutJob::default();
+                            let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+                            let text = text.as_str();
+                            for (kind, range) in tokenize_json(text) {
+                                let color = token_color(theme, kind);
+                                let format = TextFormat::simple(font_id.clone(), color);
+                                layout_job.append(&text[range], 0.0, format);
+                            }
+                            layout_job.wrap.max_width = wrap_width;
+                            ui.fonts_mut(|f| f.layout_job(layout_job))
+                        }),
+                );
+            }
+        });
+
+    actions
+}
+
+/// 按内容计算列宽
+///
+/// 均匀抽取最多 [`FIT_SAMPLE_ROWS`] 行，用当前字体测量表头和单元格文本的宽度，
+/// 限制在 [`FIT_MIN_WIDTH`] 和 [`FIT_MAX_WIDTH`] 之间，数值列的上限为 [`FIT_MAX_NUMERIC_WIDTH`]
+fn fit_column_widths(
+    ui: &egui::Ui,
+    data: &TableData,
+    indices: &[usize],
+) -> BTreeMap<String, f32> {
+    let font = egui::TextStyle::Body.resolve(ui.style());
+    let measure = |text: &str| {
+        let text: String = text.chars().take(FIT_SAMPLE_CHARS).collect();
+        ui.painter()
+            .layout_no_wrap(text, font.clone(), egui::Color32::PLACEHOLDER)
+            .size()
+            .x
+    };
+    let padding = ui.spacing().item_spacing.x * 2.0;
+    // GUID 单元格悬停时显示复制按钮
+    let copy_button = ui.spacing().interact_size.y + ui.spacing().item_spacing.x;
+    let step = indices.len().div_ceil(FIT_SAMPLE_ROWS).max(1);
+
+    data.columns
+        .iter()
+        .map(|column| {
+            let widest = indices
+                .iter()
+                .step_by(step)
+                .filter_map(|&i| data.rows.get(i)?.get(column))
+                .map(|value| {
+                    let extra = if is_guid(value) { copy_button } else { 0.0 };
+                    measure(value) + extra
+                })
+                .fold(measure(column), f32::max);
+            let max = if data.is_numeric_column(column) {
+                FIT_MAX_NUMERIC_WIDTH
+            } else {
+                FIT_MAX_WIDTH
+            };
+            (column.clone(), (widest + padding).clamp(FIT_MIN_WIDTH, max))
+        })
+        .collect()
+}
+
+/// 列出所有列供选择，点击的列写入 `selected`
+fn column_menu<'a>(ui: &mut egui::Ui, columns: &'a [String], selected: &mut Option<&'a str>) {
+    for column in columns {
+        if ui.button(column).clicked() {
+            *selected = Some(column);
+        }
+    }
+}
+
+/// 原始 JSON 视图显示的文本，设置了最大层数时折叠更深的对象和数组
+///
+/// 结果不是合法的 JSON（如错误信息）时原样显示
+fn raw_view_text<'a>(result: &'a str, view: &'a mut TableViewState) -> &'a str {
+    let Some(depth) = view.json_max_depth else {
+        return result;
+    };
+    let cache = &mut view.parsed_json;
+    cache.parse(result);
+    let Some(value) = &cache.value else {
+        return result;
+    };
+    if cache.collapsed.as_ref().is_none_or(|(d, _)| *d != depth) {
+        cache.collapsed = Some((depth, to_string_pretty_depth(value, depth)));
+    }
+    cache.collapsed.as_ref().map_or(result, |(_, text)| text)
+}
+
+/// 渲染 JSON 对象或数组的子节点
+///
+/// 子节点折叠时不渲染其内容；元素超过 [`TREE_PAGE_SIZE`] 个的数组按页分组显示
+fn render_json_children(
+    ui: &mut egui::Ui,
+    path: &str,
+    value: &serde_json::Value,
+    actions: &mut ResultActions,
+) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, item) in map {
+                render_json_node(ui, key, &json_child_path(path, key), item, actions);
+            }
+        }
+        serde_json::Value::Array(items) if items.len() > TREE_PAGE_SIZE => {
+            for start in (0..items.len()).step_by(TREE_PAGE_SIZE) {
+                let end = (start + TREE_PAGE_SIZE).min(items.len());
+                egui::CollapsingHeader::new(format!("[{} … {}]", start, end - 1))
+                    .id_salt((path, start))
+                    .show(ui, |ui| {
+                        for (index, item) in items.iter().enumerate().take(end).skip(start) {
+                            let child = format!("{}[{}]", path, index);
+                            render_json_node(ui, &format!("[{}]", index), &child, item, actions);
+                        }
+                    });
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let child = format!("{}[{}]", path, index);
+                render_json_node(ui, &format!("[{}]", index), &child, item, actions);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 渲染 JSON 树中的一个节点，右键可以复制节点的值或路径
+fn render_json_node(
+    ui: &mut egui::Ui,
+    key: &str,
+    path: &str,
+    value: &serde_json::Value,
+    actions: &mut ResultActions,
+) {
+    let summary = match value {
+        serde_json::Value::Object(map) => Some(format!("{} {{{}}}", key, map.len())),
+        serde_json::Value::Array(items) => Some(format!("{} [{}]", key, items.len())),
+        _ => None,
+    };
+    let response = match summary {
+        Some(summary) => {
+            egui::CollapsingHeader::new(summary)
+                .id_salt(path)
+                .show(ui, |ui| render_json_children(ui, path, value, actions))
+                .header_response
+        }
+        None => ui.label(format!("{}: {}", key, value)),
+    };
+    response.context_menu(|ui| {
+        if ui.button("Copy value").clicked() {
+            actions.copied_value = Some(match value {
+                serde_json::Value::String(text) => text.clone(),
+                other => serde_json::to_string_pretty(other).unwrap_or_default(),
+            });
+            ui.close();
+        }
+        if ui.button("Copy path").clicked() {
+            actions.copied_value = Some(path.to_string());
+            ui.close();
+        }
+    });
+}
+
+/// 子节点的路径：标识符形式的键用 `.key`，其他键用 `["key"]`
+fn json_child_path(path: &str, key: &str) -> String {
+    let identifier = !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    match (identifier, path.is_empty()) {
+        (true, true) => key.to_string(),
+        (true, false) => format!("{}.{}", path, key),
+        (false, _) => format!("{}[{}]", path, serde_json::Value::from(key)),
+    }
+}
+
+/// 截断过长的文本用于显示
+///
+/// 在不超过 `limit` 字节的最近字符边界处截断，返回截断后的文本和是否发生截断
+fn truncate_for_display(text: &str, limit: usize) -> (&str, bool) {
+    if text.len() <= limit {
+        return (text, false);
+    }
+    let mut end = limit;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&text[..end], true)
+}
+
+/// 复杂单元格（数组或对象）的悬停提示：格式化后的完整 JSON，过大时截断并附说明
+fn cell_tooltip(value: &serde_json::Value) -> Option<String> {
+    if !value.is_array() && !value.is_object() {
+        return None;
+    }
+    let pretty = serde_json::to_string_pretty(value).ok()?;
+    let (shown, truncated) = truncate_for_display(&pretty, CELL_TOOLTIP_LIMIT);
+    if !truncated {
+        return Some(pretty);
+    }
+    Some(format!(
+        "{shown}\n… (truncated, {} KB total — export to see all)",
+        pretty.len() / 1024
+    ))
+}
+
+/// 结果表格视图状态
+#[derive(Default)]
+pub struct TableViewState {
+    /// 行筛选文本
+    pub filter: String,
+    /// 是否显示汇总行
+    pub show_aggregates: bool,
+    /// 每个数值列选择的聚合方式
+    pub aggregates: HashMap<String, Aggregate>,
+    /// 结果中值为数组的顶层键
+    pub array_keys: Vec<String>,
+    /// 当前用于生成表格的顶层键
+    pub table_key: String,
+    /// 最近一次按键更新中发生变化的单元格（行索引和列名）
+    pub flashed: HashSet<(usize, String)>,
+    /// 变化单元格高亮的结束时间
+    pub flash_until: f64,
+    /// 空单元格和 null 值的显示方式
+    pub null_display: NullDisplay,
+    /// 是否在表头显示列类型
+    pub show_type_badges: bool,
+    /// 横向滚动时保持可见的最左侧列数
+    pub frozen_columns: usize,
+    /// 有冻结列时两侧表格共用的纵向滚动位置
+    pub scroll_offset: f32,
+    /// 保存的列宽（按列名），没有保存的列自动调整宽度
+    pub column_widths: BTreeMap<String, f32>,
+    /// 下一次渲染时是否丢弃表格记住的列宽，改用 `column_widths`
+    pub reset_widths: bool,
+    /// 用于分组显示的列，`None` 表示不分组
+    pub group_by: Option<String>,
+    /// 已折叠的分组值
+    pub collapsed_groups: HashSet<String>,
+    /// 原始 JSON 视图中展开的最大嵌套层数，`None` 表示不限制
+    pub json_max_depth: Option<usize>,
+    /// 结果的显示方式
+    pub result_view: ResultView,
+    /// 解析后的结果，用于树形视图和折叠的原始 JSON 视图
+    parsed_json: ParsedJson,
+    /// 表头显示的列类型，结果不变时复用
+    schema: SchemaCache,
+    /// 表头菜单中显示的列名和列信息，菜单打开期间缓存
+    column_info: Option<(String, ColumnInfo)>,
+    /// 本帧是否显示了列信息菜单
+    column_info_open: bool,
+}
+
+impl TableViewState {
+    /// 使用保存的冻结列数和列宽创建视图状态，其余为默认值
+    pub fn with_layout(frozen_columns: usize, column_widths: BTreeMap<String, f32>) -> Self {
+        Self {
+            frozen_columns,
+            column_widths,
+            ..Self::default()
+        }
+    }
+}
+
+/// 表头菜单中显示的列信息
+struct ColumnInfo {
+    /// 数量、不重复值数量和数值列的范围
+    stats: ColumnStats,
+    /// 最常见的值和出现次数
+    top: Vec<(String, usize)>,
+}
+
+/// 结果的显示方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResultView {
+    /// 表格，结果不是表格时显示原始 JSON
+    #[default]
+    Table,
+    /// 原始 JSON 文本
+    Raw,
+    /// 可折叠的 JSON 树
+    Tree,
+}
+
+impl ResultView {
+    /// 所有显示方式
+    pub const ALL: [ResultView; 3] = [ResultView::Table, ResultView::Raw, ResultView::Tree];
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            ResultView::Table => "Table",
+            ResultView::Raw => "Raw",
+            ResultView::Tree => "Tree",
+        }
+    }
+}
+
+/// 解析后的结果 JSON 缓存，结果文本不变时复用
+#[derive(Default)]
+struct ParsedJson {
+    /// 生成缓存时的结果文本
+    source: String,
+    /// 解析后的值，结果不是合法的 JSON 时为 `None`
+    value: Option<serde_json::Value>,
+    /// 折叠的层数和折叠后的原始 JSON 文本
+    collapsed: Option<(usize, String)>,
+}
+
+impl ParsedJson {
+    /// 解析结果文本，文本与上一次相同时直接返回缓存的值
+    fn parse(&mut self, result: &str) -> Option<&serde_json::Value> {
+        if self.source != result {
+            self.source = result.to_string();
+            self.value = serde_json::from_str(result).ok();
+            self.collapsed = None;
+        }
+        self.value.as_ref()
+    }
+}
+
+/// 推断出的列类型缓存，结果、表格来源键和表格结构不变时复用
+#[derive(Default)]
+struct SchemaCache {
+    /// 生成缓存时的结果文本
+    source: String,
+    /// 生成缓存时的表格来源键
+    table_key: String,
+    /// 生成缓存时的列名
+    columns: Vec<String>,
+    /// 生成缓存时的行数
+    rows: usize,
+    /// 推断出的列类型
+    schema: Vec<ColumnSchema>,
+}
+
+impl SchemaCache {
+    /// 返回表格的列类型，与上一次的结果和表格结构相同时直接返回缓存的值
+    fn infer(&mut self, result: &str, table_key: &str, data: &TableData) -> &[ColumnSchema] {
+        if self.source != result
+            || self.table_key != table_key
+            || self.columns != data.columns
+            || self.rows != data.rows.len()
+        {
+            self.source = result.to_string();
+            self.table_key = table_key.to_string();
+            self.columns = data.columns.clone();
+            self.rows = data.rows.len();
+            self.schema = data.infer_schema();
+        }
+        &self.schema
+    }
+}
+
+impl TableViewState {
+    /// 高亮最近发生变化的单元格，持续 [`FLASH_SECS`] 秒
+    pub fn flash(&mut self, ctx: &egui::Context, cells: Vec<(usize, String)>) {
+        self.flashed = cells.into_iter().collect();
+        self.flash_until = ctx.input(|i| i.time) + FLASH_SECS;
+    }
+}
+
+/// 格式化汇总数值，整数不显示小数部分
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{:.3}", value)
+    }
+}
+
+/// 渲染汇总行中的单元格
+///
+/// 数值列显示可选择的聚合结果，其他列显示非空单元格数量
+fn render_aggregate_cell(
+    ui: &mut egui::Ui,
+    data: &TableData,
+    column: &str,
+    indices: &[usize],
+    view: &mut TableViewState,
+) {
+    if !data.is_numeric_column(column) {
+        ui.weak(format!("count {}", data.non_empty_count(column, indices)));
+        return;
+    }
+
+    let aggregate = view.aggregates.entry(column.to_string()).or_default();
+    egui::ComboBox::from_id_salt(("aggregate", column))
+        .selected_text(aggregate.label())
+        .width(60.0)
+        .show_ui(ui, |ui| {
+            for option in Aggregate::ALL {
+                ui.selectable_value(aggregate, option, option.label());
+            }
+        });
+    let value = data
+        .aggregate(column, indices, *aggregate)
+        .map(format_number)
+        .unwrap_or_else(|| "-".to_string());
+    ui.strong(value);
+}
+
+/// 获取单元格匹配的第一条着色规则的颜色
+fn rule_color(
+    rules: &[CellRule],
+    column: &str,
+    value: &serde_json::Value,
+) -> Option<egui::Color32> {
+    rules
+        .iter()
+        .find(|rule| rule.column == column && rule.matches(value))
+        .map(|rule| egui::Color32::from_rgb(rule.color[0], rule.color[1], rule.color[2]))
+}
 
-        for (index, keyword) in config.custom_keywords.iter().enumerate() {
-            ui.horizontal(|ui| {
-                ui.label(keyword);
-                if ui.button("❌").clicked() {
-                    actions.remove_keyword_index = Some(index);
-                }
-            });
+/// 渲染数据表格，`indices` 为筛选后显示的行
+///
+/// 鼠标悬停在 GUID 单元格上时显示复制按钮，匹配着色规则的单元格使用规则的颜色
+fn render_table(
+    ui: &mut egui::Ui,
+    result: &str,
+    data: &TableData,
+    indices: &[usize],
+    view: &mut TableViewState,
+    rules: &[CellRule],
+) -> ResultActions {
+    let mut actions = ResultActions::default();
+
+    // 变化单元格的高亮随时间淡出
+    let now = ui.input(|i| i.time);
+    let flash = ((view.flash_until - now) / FLASH_SECS).clamp(0.0, 1.0) as f32;
+    if flash > 0.0 {
+        ui.ctx().request_repaint();
+    }
+
+    let schema = if view.show_type_badges {
+        view.schema.infer(result, &view.table_key, data).to_vec()
+    } else {
+        Vec::new()
+    };
+    let groups = view
+        .group_by
+        .as_ref()
+        .filter(|column| data.columns.contains(column))
+        .map(|column| data.group_indices(column, indices));
+    let part = TablePart {
+        data,
+        indices,
+        rules,
+        schema: &schema,
+        flash,
+        reset_widths: std::mem::take(&mut view.reset_widths),
+        groups: groups.as_deref(),
+    };
+
+    // 冻结的列和序号列放在左侧，其余列在右侧单独横向滚动，两侧同步纵向滚动
+    let frozen = view.frozen_columns.min(data.columns.len());
+    if frozen == 0 {
+        render_table_part(ui, &part, view, &data.columns, true, None, &mut actions);
+        return actions;
+    }
+    let (pinned, rest) = data.columns.split_at(frozen);
+    let offset = view.scroll_offset;
+    ui.horizontal_top(|ui| {
+        let left = render_table_part(ui, &part, view, pinned, true, Some(offset), &mut actions);
+        if rest.is_empty() {
+            view.scroll_offset = left;
+            return;
         }
+        ui.separator();
+        let right = egui::ScrollArea::horizontal()
+            .id_salt("table_unpinned")
+            .show(ui, |ui| {
+                render_table_part(ui, &part, view, rest, false, Some(offset), &mut actions)
+            })
+            .inner;
+        view.scroll_offset = if (left - offset).abs() > 0.5 { left } else { right };
     });
-
     actions
 }
 
-/// 配置面板操作结果
-#[derive(Default)]
-pub struct ConfigPanelActions {
-    /// 是否需要保存配置
-    pub save_config: bool,
-    /// 需要删除的查询索引
-    pub remove_query_index: Option<usize>,
-    /// 需要删除的关键词索引
-    pub remove_keyword_index: Option<usize>,
-    /// 字体大小是否改变
-    pub fontsize_changed: bool,
+/// 渲染表格的一部分列时共用的数据
+struct TablePart<'a> {
+    data: &'a TableData,
+    /// 筛选后显示的行
+    indices: &'a [usize],
+    rules: &'a [CellRule],
+    /// 推断出的列类型，不显示类型标记时为空
+    schema: &'a [ColumnSchema],
+    /// 变化单元格高亮的强度，0 表示不高亮
+    flash: f32,
+    /// 是否丢弃表格记住的列宽
+    reset_widths: bool,
+    /// 按列分组后的行（分组值和组内行），不分组时为 `None`
+    groups: Option<&'a [(String, Vec<usize>)]>,
 }
 
-/// 渲染控制按钮栏
-pub fn render_control_buttons(
+/// 渲染表格中的指定列，`with_index` 为 `true` 时在最前面显示序号列
+///
+/// `scroll_offset` 为需要同步到的纵向滚动位置，返回渲染后的纵向滚动位置
+fn render_table_part(
     ui: &mut egui::Ui,
-    has_code: bool,
-    has_results: bool,
-    has_table_data: bool,
-    show_config_panel: &mut bool,
-    status_message: &str,
-    has_error: bool,
-) -> ControlButtonActions {
-    let mut actions = ControlButtonActions::default();
+    part: &TablePart,
+    view: &mut TableViewState,
+    columns: &[String],
+    with_index: bool,
+    scroll_offset: Option<f32>,
+    actions: &mut ResultActions,
+) -> f32 {
+    use egui_extras::{Column, TableBuilder};
 
-    ui.horizontal(|ui| {
-        // 运行按钮
-        if ui.add_enabled(has_code, egui::Button::new("Run WAQL")).clicked() {
-            actions.run_query = true;
-        }
+    let mut table = TableBuilder::new(ui)
+        .id_salt(("result_table", with_index))
+        .striped(true)
+        .resizable(true)
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .min_scrolled_height(0.0);
+    if let Some(offset) = scroll_offset {
+        table = table.vertical_scroll_offset(offset);
+    }
+    if part.reset_widths {
+        table.reset();
+    }
+    if with_index {
+        table = table.column(Column::auto()); // 序号列
+    }
+    let table = columns.iter().fold(table, |t, col| {
+        t.column(match view.column_widths.get(col) {
+            Some(&width) => Column::initial(width).at_least(FIT_MIN_WIDTH),
+            None => Column::auto(),
+        })
+    });
 
-        // 保存按钮
-        if ui.add_enabled(has_code, egui::Button::new("Save WAQL")).clicked() {
-            actions.save_query = true;
-        }
+    let output = table
+        .header(20.0, |mut header| {
+            if with_index {
+                header.col(|ui| {
+                    ui.strong("#");
+                });
+            }
+            for col in columns {
+                header.col(|ui| {
+                    ui.add(
+                        egui::Label::new(egui::RichText::new(col).strong())
+                            .sense(egui::Sense::click()),
+                    )
+                    .on_hover_text("Right-click for column info")
+                    .context_menu(|ui| render_column_info(ui, part.data, view, col));
+                    if let Some(column) = part.schema.iter().find(|c| &c.name == col) {
+                        ui.label(egui::RichText::new(column.kind.badge()).small().weak())
+                            .on_hover_text("Inferred column type");
+                    }
+                });
+            }
+        })
+        .body(|body| {
+            // 只布局可见区域内的行，大结果集滚动时不必每帧遍历全部行
+            let entries = table_entries(part, view);
+            let heights = entries.iter().map(|entry| match entry {
+                TableEntry::Aggregate => 24.0,
+                _ => 18.0,
+            });
+            body.heterogeneous_rows(heights, |mut row_ui| match entries[row_ui.index()] {
+                TableEntry::Group(group) => {
+                    let (value, rows) = &part.groups.unwrap_or_default()[group];
+                    render_group_entry(&mut row_ui, view, value, rows.len(), columns, with_index)
+                }
+                TableEntry::Row(index) => render_data_row(
+                    &mut row_ui,
+                    part,
+                    view,
+                    columns,
+                    with_index,
+                    index,
+                    actions,
+                ),
+                TableEntry::Aggregate => {
+                    render_aggregate_row(&mut row_ui, part, view, columns, with_index)
+                }
+            });
+        });
+    output.state.offset.y
+}
 
-        // 导出 CSV 按钮
-        if ui.add_enabled(has_table_data, egui::Button::new("Export CSV")).clicked() {
-            actions.export_csv = true;
-        }
+/// 渲染表头菜单中的列信息：非空数量、不重复值数量和最常见的值，数值列还显示最小值和最大值
+///
+/// 统计在菜单打开时计算一次，菜单打开期间不再重新计算
+fn render_column_info(
+    ui: &mut egui::Ui,
+    data: &TableData,
+    view: &mut TableViewState,
+    column: &str,
+) {
+    view.column_info_open = true;
+    if view.column_info.as_ref().is_none_or(|(name, _)| name != column) {
+        let info = ColumnInfo {
+            stats: data.column_stats(column),
+            top: data.top_values(column, COLUMN_INFO_TOP_VALUES),
+        };
+        view.column_info = Some((column.to_string(), info));
+    }
+    let Some((_, info)) = &view.column_info else {
+        return;
+    };
 
-        // 清空按钮
-        if ui.add_enabled(has_results, egui::Button::new("Clear Results")).clicked() {
-            actions.clear_results = true;
+    ui.strong(column);
+    egui::Grid::new("column_info").num_columns(2).show(ui, |ui| {
+        ui.label("Non-empty");
+        ui.label(format!("{} / {}", info.stats.count, data.rows.len()));
+        ui.end_row();
+        ui.label("Distinct");
+        ui.label(info.stats.distinct.to_string());
+        ui.end_row();
+        if let (Some(min), Some(max)) = (info.stats.min, info.stats.max) {
+            ui.label("Min");
+            ui.label(format_number(min));
+            ui.end_row();
+            ui.label("Max");
+            ui.label(format_number(max));
+            ui.end_row();
         }
-
+    });
+    if !info.top.is_empty() {
         ui.separator();
+        ui.weak("Most frequent");
+        egui::Grid::new("column_info_top").num_columns(2).show(ui, |ui| {
+            for (value, count) in &info.top {
+                ui.add(egui::Label::new(value).truncate());
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+    }
+}
 
-        // 显示/隐藏配置按钮
-        let config_button_text = if *show_config_panel {
-            "Hide Config"
-        } else {
-            "Show Config"
-        };
-        if ui.button(config_button_text).clicked() {
-            *show_config_panel = !*show_config_panel;
+/// 表格主体中的一行
+#[derive(Clone, Copy)]
+enum TableEntry {
+    /// 分组标题，值为分组序号
+    Group(usize),
+    /// 数据行，值为行序号
+    Row(usize),
+    /// 汇总行
+    Aggregate,
+}
+
+/// 按显示顺序列出表格主体的所有行
+///
+/// 分组显示时每组前有标题行，折叠的组只保留标题；汇总行位于最后
+fn table_entries(part: &TablePart, view: &TableViewState) -> Vec<TableEntry> {
+    let mut entries = Vec::with_capacity(part.indices.len() + 1);
+    match part.groups {
+        Some(groups) => {
+            for (group, (value, rows)) in groups.iter().enumerate() {
+                entries.push(TableEntry::Group(group));
+                if !view.collapsed_groups.contains(value) {
+                    entries.extend(rows.iter().map(|&index| TableEntry::Row(index)));
+                }
+            }
         }
+        None => entries.extend(part.indices.iter().map(|&index| TableEntry::Row(index))),
+    }
+    if view.show_aggregates {
+        entries.push(TableEntry::Aggregate);
+    }
+    entries
+}
 
-        // 状态消息显示
-        if !status_message.is_empty() {
-            ui.separator();
-            let color = if has_error {
-                egui::Color32::RED
+/// 渲染可点击折叠的分组标题行，点击后切换该组的折叠状态
+fn render_group_entry(
+    row_ui: &mut egui_extras::TableRow<'_, '_>,
+    view: &mut TableViewState,
+    value: &str,
+    count: usize,
+    columns: &[String],
+    with_index: bool,
+) {
+    let collapsed = view.collapsed_groups.contains(value);
+    let shown = if value.is_empty() {
+        view.null_display.placeholder()
+    } else {
+        value
+    };
+    let title = format!("{} ({})", shown, count);
+    if render_group_row(row_ui, &title, collapsed, columns.len(), with_index)
+        && !view.collapsed_groups.remove(value)
+    {
+        view.collapsed_groups.insert(value.to_string());
+    }
+}
+
+/// 渲染一行数据
+fn render_data_row(
+    row_ui: &mut egui_extras::TableRow<'_, '_>,
+    part: &TablePart,
+    view: &TableViewState,
+    columns: &[String],
+    with_index: bool,
+    index: usize,
+    actions: &mut ResultActions,
+) {
+    let data = part.data;
+    let flash = part.flash;
+    let row = &data.rows[index];
+    // 右键序号列打开行操作菜单
+    if with_index {
+        row_ui.col(|ui| {
+            let guid = data.row_guid(index);
+            ui.add(egui::Label::new((index + 1).to_string()).sense(egui::Sense::click()))
+                .context_menu(|ui| {
+                    if ui
+                        .add_enabled(guid.is_some(), egui::Button::new("Show children"))
+                        .on_disabled_hover_text("This row has no object GUID")
+                        .clicked()
+                    {
+                        actions.show_children = guid.map(str::to_string);
+                        ui.close();
+                    }
+                });
+        });
+    }
+    for col in columns {
+        row_ui.col(|ui| {
+            let value = row.get(col).map(|s| s.as_str()).unwrap_or("");
+            let typed = data.typed_rows.get(index).and_then(|r| r.get(col));
+            if flash > 0.0 && view.flashed.contains(&(index, col.clone())) {
+                let color = egui::Color32::from_rgb(200, 160, 40);
+                ui.painter()
+                    .rect_filled(ui.max_rect(), 0.0, color.gamma_multiply(flash * 0.6));
+            }
+            let is_null = value.is_empty() || typed.is_some_and(|v| v.is_null());
+            let response = if is_null {
+                ui.weak(view.null_display.placeholder())
+            } else if is_url(value) || is_file_path(value) {
+                render_link_cell(ui, value, actions)
             } else {
-                egui::Color32::GREEN
+                match typed.and_then(|typed| rule_color(part.rules, col, typed)) {
+                    Some(color) => {
+                        ui.colored_label(StatusPalette::get(ui.ctx()).adapt(color), value)
+                    }
+                    None => ui.label(value),
+                }
             };
-            ui.colored_label(color, status_message);
+            // 数组和对象的提示在悬停时才格式化，避免每帧对所有可见单元格序列化
+            if let Some(typed) = typed.filter(|v| v.is_array() || v.is_object()) {
+                response.on_hover_ui(|ui| {
+                    if let Some(tooltip) = cell_tooltip(typed) {
+                        ui.label(egui::RichText::new(tooltip).monospace());
+                    }
+                });
+            }
+            if is_guid(value)
+                && ui.ui_contains_pointer()
+                && ui.small_button("📋").on_hover_text("Copy GUID").clicked()
+            {
+                actions.copied_value = Some(value.to_string());
+            }
+        });
+    }
+}
+
+/// 渲染网址或文件路径单元格
+///
+/// 网址直接在浏览器中打开；点击文件路径用系统默认程序打开，右键可以在文件管理器中显示
+fn render_link_cell(
+    ui: &mut egui::Ui,
+    value: &str,
+    actions: &mut ResultActions,
+) -> egui::Response {
+    if is_url(value) {
+        return ui.hyperlink(value);
+    }
+    let response = ui.link(value);
+    if response.clicked() {
+        actions.open_path = Some(value.to_string());
+    }
+    response.context_menu(|ui| {
+        if ui.button("Open").clicked() {
+            actions.open_path = Some(value.to_string());
+            ui.close();
+        }
+        if ui.button("Show in file manager").clicked() {
+            actions.reveal_path = Some(value.to_string());
+            ui.close();
+        }
+        if ui.button("Copy path").clicked() {
+            actions.copied_value = Some(value.to_string());
+            ui.close();
         }
     });
+    response
+}
 
-    actions
+/// 渲染分组标题行，返回是否点击了标题
+///
+/// 标题只显示在带序号列的部分，另一部分渲染空行以保持两侧行对齐
+fn render_group_row(
+    row_ui: &mut egui_extras::TableRow<'_, '_>,
+    title: &str,
+    collapsed: bool,
+    columns: usize,
+    with_index: bool,
+) -> bool {
+    let mut clicked = false;
+    if with_index {
+        let arrow = if collapsed { "▸" } else { "▾" };
+        row_ui.col(|ui| {
+            clicked |= ui.add(egui::Label::new(arrow).sense(egui::Sense::click())).clicked();
+        });
+    }
+    for i in 0..columns {
+        row_ui.col(|ui| {
+            if with_index && i == 0 {
+                let label = egui::Label::new(egui::RichText::new(title).strong())
+                    .sense(egui::Sense::click());
+                clicked |= ui.add(label).clicked();
+            }
+        });
+    }
+    clicked
 }
 
-/// 控制按钮操作结果
+/// 渲染汇总行（基于当前筛选结果）
+fn render_aggregate_row(
+    row_ui: &mut egui_extras::TableRow<'_, '_>,
+    part: &TablePart,
+    view: &mut TableViewState,
+    columns: &[String],
+    with_index: bool,
+) {
+    if with_index {
+        row_ui.col(|ui| {
+            ui.strong("Σ");
+        });
+    }
+    for col in columns {
+        row_ui.col(|ui| {
+            render_aggregate_cell(ui, part.data, col, part.indices, view);
+        });
+    }
+}
+
+/// 快照操作结果
 #[derive(Default)]
-pub struct ControlButtonActions {
-    /// 是否运行查询
-    pub run_query: bool,
-    /// 是否保存查询
-    pub save_query: bool,
-    /// 是否导出 CSV
-    pub export_csv: bool,
-    /// 是否清空结果
-    pub clear_results: bool,
+pub struct SnapshotActions {
+    /// 是否将当前表格保存为快照
+    pub take: bool,
+    /// 是否显示与快照的比较结果
+    pub show_diff: bool,
+    /// 是否丢弃快照
+    pub clear: bool,
 }
 
-/// 渲染结果显示区域
-pub fn render_results(
+/// 渲染快照按钮栏
+///
+/// `snapshot` 为已有快照的说明（查询语句和保存时间）
+pub fn render_snapshot_bar(
     ui: &mut egui::Ui,
-    result: &str,
-    table_data: &Option<TableData>,
-    has_error: bool,
-) {
-    egui::ScrollArea::both()
-        .auto_shrink([false; 2])
-        .show(ui, |ui| {
-            if has_error {
-                // 显示错误信息
-                ui.colored_label(egui::Color32::RED, result);
-            } else if let Some(data) = table_data {
-                // 显示表格
-                render_table(ui, data);
-            } else {
-                // 显示原始 JSON
-                ui.label(result);
+    has_table: bool,
+    snapshot: Option<&str>,
+) -> SnapshotActions {
+    let mut actions = SnapshotActions::default();
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(has_table, egui::Button::new("Snapshot"))
+            .on_hover_text("Keep the current table to compare against later")
+            .clicked()
+        {
+            actions.take = true;
+        }
+        if let Some(description) = snapshot {
+            if ui
+                .add_enabled(has_table, egui::Button::new("Diff vs snapshot"))
+                .clicked()
+            {
+                actions.show_diff = true;
             }
-        });
+            if ui.small_button("✖").on_hover_text("Discard snapshot").clicked() {
+                actions.clear = true;
+            }
+            ui.weak(description);
+        }
+    });
+    actions
 }
 
-/// 渲染数据表格
-fn render_table(ui: &mut egui::Ui, data: &TableData) {
-    use egui_extras::{Column, TableBuilder};
+/// 定时重新执行查询（监视）的状态
+pub struct WatchState {
+    /// 是否正在监视
+    pub running: bool,
+    /// 正在监视的查询语句
+    pub query: String,
+    /// 重新执行的间隔（秒）
+    pub interval_secs: u32,
+    /// 是否按键列合并结果，而不是替换整个表格
+    pub keyed: bool,
+    /// 按键合并时使用的键列
+    pub key_column: String,
+    /// 下一次执行的时间
+    pub next_at: f64,
+}
 
-    let table = TableBuilder::new(ui)
-        .striped(true)
-        .resizable(true)
-        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
-        .column(Column::auto()) // 序号列
-        .min_scrolled_height(0.0);
+impl Default for WatchState {
+    fn default() -> Self {
+        Self {
+            running: false,
+            query: String::new(),
+            interval_secs: 5,
+            keyed: false,
+            key_column: "id".to_string(),
+            next_at: 0.0,
+        }
+    }
+}
 
-    let table = data.columns.iter().fold(table, |t, _| t.column(Column::auto()));
+/// 最近一次编辑查询或操作表格后，自动刷新暂停的时间（秒）
+pub const AUTO_REFRESH_RESUME_SECS: f64 = 3.0;
 
-    table
-        .header(20.0, |mut header| {
-            header.col(|ui| {
-                ui.strong("#");
-            });
-            for col in &data.columns {
-                header.col(|ui| {
-                    ui.strong(col);
-                });
+/// 定时自动刷新结果的状态
+#[derive(Default)]
+pub struct AutoRefreshState {
+    /// 下一次刷新的时间，0 表示尚未安排
+    pub next_at: f64,
+    /// 最近一次编辑查询或操作表格的时间
+    pub last_activity: Option<f64>,
+}
+
+impl AutoRefreshState {
+    /// 记录一次编辑或表格操作
+    pub fn touch(&mut self, now: f64) {
+        self.last_activity = Some(now);
+    }
+
+    /// 是否因为最近的编辑或表格操作而暂停
+    pub fn is_paused(&self, now: f64) -> bool {
+        self.last_activity.is_some_and(|time| now - time < AUTO_REFRESH_RESUME_SECS)
+    }
+}
+
+/// 渲染自动刷新的倒计时
+pub fn render_auto_refresh_status(ui: &mut egui::Ui, state: &AutoRefreshState, query: &str) {
+    let now = ui.input(|i| i.time);
+    if state.is_paused(now) {
+        ui.weak(format!("⟳ Auto-refresh of `{}` paused while editing", query));
+    } else {
+        let remaining = (state.next_at - now).max(0.0);
+        ui.weak(format!("⟳ Auto-refreshing `{}` · next in {:.0} s", query, remaining.ceil()));
+    }
+}
+
+/// 监视操作结果
+#[derive(Default)]
+pub struct WatchActions {
+    /// 是否开始监视当前查询
+    pub start: bool,
+    /// 是否停止监视
+    pub stop: bool,
+}
+
+/// 渲染监视按钮栏
+///
+/// `columns` 为当前表格的列，用于选择按键合并的键列
+pub fn render_watch_bar(
+    ui: &mut egui::Ui,
+    state: &mut WatchState,
+    columns: &[String],
+) -> WatchActions {
+    let mut actions = WatchActions::default();
+    ui.horizontal(|ui| {
+        if state.running {
+            if ui.button("Stop watching").clicked() {
+                actions.stop = true;
             }
-        })
-        .body(|mut body| {
-            for (index, row) in data.rows.iter().enumerate() {
-                body.row(18.0, |mut row_ui| {
-                    row_ui.col(|ui| {
-                        ui.label((index + 1).to_string());
-                    });
-                    for col in &data.columns {
-                        row_ui.col(|ui| {
-                            ui.label(row.get(col).map(|s| s.as_str()).unwrap_or(""));
-                        });
+            let remaining = (state.next_at - ui.input(|i| i.time)).max(0.0);
+            ui.weak(format!(
+                "Watching `{}` · next run in {:.0} s",
+                state.query,
+                remaining.ceil()
+            ));
+            return;
+        }
+        if ui
+            .button("Watch")
+            .on_hover_text("Re-run the current query periodically")
+            .clicked()
+        {
+            actions.start = true;
+        }
+        ui.label("every");
+        ui.add(egui::DragValue::new(&mut state.interval_secs).range(1..=3600).suffix(" s"));
+        ui.checkbox(&mut state.keyed, "Keyed update")
+            .on_hover_text("Update rows in place by the key column and flash changed cells");
+        if state.keyed {
+            egui::ComboBox::from_id_salt("watch_key_column")
+                .selected_text(state.key_column.as_str())
+                .show_ui(ui, |ui| {
+                    for column in columns {
+                        ui.selectable_value(&mut state.key_column, column.clone(), column);
                     }
                 });
+        }
+    });
+    actions
+}
+
+/// 行在比较结果中显示的名称：`name` 列，没有时使用 GUID 或行号
+fn row_label(data: &TableData, index: usize) -> String {
+    data.rows
+        .get(index)
+        .and_then(|row| row.get("name"))
+        .filter(|name| !name.is_empty())
+        .map(String::as_str)
+        .or_else(|| data.row_guid(index))
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("#{}", index + 1))
+}
+
+/// 渲染当前结果与快照的比较窗口
+pub fn render_diff_window(
+    ctx: &egui::Context,
+    open: &mut bool,
+    current: &TableData,
+    snapshot: &TableData,
+) {
+    egui::Window::new("Diff vs snapshot")
+        .open(open)
+        .default_size([500.0, 400.0])
+        .show(ctx, |ui| {
+            let diff = current.diff(snapshot);
+            let count = |f: fn(&RowDiff) -> bool| diff.iter().filter(|d| f(d)).count();
+            ui.label(format!(
+                "+{} added   −{} removed   ~{} changed",
+                count(|d| matches!(d, RowDiff::Added(_))),
+                count(|d| matches!(d, RowDiff::Removed(_))),
+                count(|d| matches!(d, RowDiff::Changed { .. })),
+            ));
+            ui.separator();
+            if diff.is_empty() {
+                ui.weak("No differences");
+                return;
             }
+
+            egui::ScrollArea::both().auto_shrink([false; 2]).show(ui, |ui| {
+                egui::Grid::new("diff").striped(true).num_columns(3).show(ui, |ui| {
+                    for row in &diff {
+                        match row {
+                            RowDiff::Added(index) => {
+                                ui.colored_label(StatusPalette::get(ui.ctx()).success, "+");
+                                ui.label(row_label(current, *index));
+                                ui.weak("added");
+                            }
+                            RowDiff::Removed(index) => {
+                                ui.colored_label(StatusPalette::get(ui.ctx()).error, "−");
+                                ui.label(row_label(snapshot, *index));
+                                ui.weak("removed");
+                            }
+                            RowDiff::Changed {
+                                index,
+                                snapshot_index,
+                                columns,
+                            } => {
+                                ui.colored_label(egui::Color32::YELLOW, "~");
+                                ui.label(row_label(current, *index));
+                                ui.vertical(|ui| {
+                                    for column in columns {
+                                        let value = |data: &TableData, i: usize| {
+                                            data.rows[i].get(column).cloned().unwrap_or_default()
+                                        };
+                                        ui.label(format!(
+                                            "{}: {} → {}",
+                                            column,
+                                            value(snapshot, *snapshot_index),
+                                            value(current, *index)
+                                        ));
+                                    }
+                                });
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
         });
 }
+
+/// 状态栏信息
+#[derive(Default)]
+pub struct StatusInfo {
+    /// 最近一次查询耗时
+    pub query_latency: Option<Duration>,
+    /// 最近一次查询的行数
+    pub row_count: Option<usize>,
+    /// 最近一次查询结果的大小（字节）
+    pub result_bytes: usize,
+    /// 最近一次检测连接的延迟
+    pub ping_latency: Option<Duration>,
+    /// 断开时距离下一次自动重连的时间
+    pub reconnect_in: Option<Duration>,
+    /// 连接状态
+    pub connection: ConnectionState,
+    /// 当前连接的项目信息
+    pub project: Option<ProjectInfo>,
+}
+
+/// 状态栏操作结果
+#[derive(Default)]
+pub struct StatusBarActions {
+    /// 是否检测连接
+    pub ping: bool,
+    /// 是否立即重连
+    pub reconnect: bool,
+    /// 需要切换到的连接名称
+    pub switch_connection: Option<String>,
+}
+
+/// 格式化字节数
+fn format_bytes(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+/// 格式化耗时
+fn format_duration(duration: Duration) -> String {
+    format!("{} ms", duration.as_millis())
+}
+
+/// 渲染底部状态栏
+///
+/// `recent_connections` 为最近使用的连接名称，用于快速切换
+pub fn render_status_bar(
+    ui: &mut egui::Ui,
+    status: &StatusInfo,
+    active_connection: &str,
+    recent_connections: &[&str],
+) -> StatusBarActions {
+    let mut actions = StatusBarActions::default();
+
+    ui.horizontal(|ui| {
+        let palette = StatusPalette::get(ui.ctx());
+        let (color, text) = match status.connection {
+            ConnectionState::Unknown => (egui::Color32::GRAY, "Not connected"),
+            ConnectionState::Connected => (palette.success, "Connected"),
+            ConnectionState::Failed => (palette.error, "Connection failed"),
+            ConnectionState::Offline => (egui::Color32::YELLOW, "Offline demo"),
+            ConnectionState::Idle => (egui::Color32::GRAY, "Idle (disconnected)"),
+        };
+        let icon = match status.connection {
+            ConnectionState::Connected if palette.icons => "✔",
+            ConnectionState::Failed if palette.icons => "✖",
+            ConnectionState::Idle => "○",
+            _ => "●",
+        };
+        ui.colored_label(color, icon);
+        if status.connection == ConnectionState::Idle {
+            ui.small(text).on_hover_text("Reconnects on the next query");
+        } else {
+            ui.small(text);
+        }
+        if let Some(ping) = status.ping_latency {
+            ui.small(format!("ping {}", format_duration(ping)));
+        }
+        if ui.small_button("Ping").clicked() {
+            actions.ping = true;
+        }
+        if let Some(remaining) = status.reconnect_in {
+            ui.small(format!("Reconnecting in {}s", remaining.as_secs_f64().ceil() as u64));
+            if ui.small_button("Reconnect now").clicked() {
+                actions.reconnect = true;
+            }
+        }
+
+        if let Some(project) = &status.project {
+            ui.separator();
+            let mut text = format!("Project: {}", project.name);
+            for current in [&project.current_platform, &project.current_language]
+                .into_iter()
+                .flatten()
+            {
+                text.push_str(" · ");
+                text.push_str(current);
+            }
+            ui.small(text);
+        }
+
+        ui.separator();
+        egui::ComboBox::from_id_salt("recent_connections")
+            .selected_text(active_connection)
+            .show_ui(ui, |ui| {
+                if recent_connections.is_empty() {
+                    ui.weak("No recent connections");
+                }
+                for name in recent_connections {
+                    if ui
+                        .selectable_label(*name == active_connection, *name)
+                        .clicked()
+                        && *name != active_connection
+                    {
+                        actions.switch_connection = Some(name.to_string());
+                    }
+                }
+            });
+
+        ui.separator();
+        let latency = status
+            .query_latency
+            .map(format_duration)
+            .unwrap_or_else(|| "-".to_string());
+        ui.small(format!("Latency: {}", latency));
+
+        ui.separator();
+        let rows = status
+            .row_count
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        ui.small(format!("Rows: {}", rows));
+
+        ui.separator();
+        ui.small(format!("Size: {}", format_bytes(status.result_bytes)));
+    });
+
+    actions
+}
\ No newline at end of file
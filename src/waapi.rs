@@ -0,0 +1,687 @@
+//! WAAPI HTTP 客户端模块
+//!
+//! 通过 WAAPI 的 HTTP 接口调用 Wwise，支持为反向代理配置认证令牌和自定义请求头
+//!
+//! 每个请求都是一次独立的 `POST /waapi`，反向代理可以逐个检查 `Authorization` 请求头，
+//! 认证失败时返回 HTTP 401。客户端需要为每个请求设置请求头并读取响应状态码，
+//! 才能将 401 报告为 [`WaapiError::Unauthorized`]，因此直接使用 `ureq` 发送请求，
+//! 不再通过 `waapi-rs` 客户端
+
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use ureq::Agent;
+
+/// 默认 WAAPI 主机
+pub const DEFAULT_HOST: &str = "127.0.0.1";
+
+/// 默认 WAAPI HTTP 端口
+pub const DEFAULT_PORT: u16 = 8090;
+
+/// 默认请求超时时间
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 响应正文的最大字节数
+///
+/// ureq 读取正文时默认限制为 10 MB，大型项目的查询结果可能超过该大小
+const MAX_RESPONSE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// WAQL 查询使用的 WAAPI 接口
+pub const WAQL_URI: &str = "ak.wwise.core.object.get";
+
+/// 获取项目信息的 WAAPI 接口
+pub const PROJECT_INFO_URI: &str = "ak.wwise.core.getProjectInfo";
+
+/// 获取 Wwise 版本等信息的 WAAPI 接口
+pub const WWISE_INFO_URI: &str = "ak.wwise.core.getInfo";
+
+/// 获取项目支持的对象类型的 WAAPI 接口
+pub const OBJECT_TYPES_URI: &str = "ak.wwise.core.object.getTypes";
+
+/// 不会修改项目的 WAAPI 接口方法名前缀（如 `get`、`getInfo`、`isPropertyEnabled`）
+const READ_ONLY_METHOD_PREFIXES: [&str; 2] = ["get", "is"];
+
+/// 判断 WAAPI 接口是否可能修改项目
+///
+/// 按接口最后一段方法名判断，以 `get`、`is` 开头的视为只读，其余都视为修改操作
+pub fn is_mutating_uri(uri: &str) -> bool {
+    let method = uri.rsplit('.').next().unwrap_or(uri);
+    !READ_ONLY_METHOD_PREFIXES
+        .iter()
+        .any(|prefix| method.starts_with(prefix))
+}
+
+/// 客户端连接配置
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfig {
+    /// WAAPI 主机
+    pub host: String,
+    /// WAAPI HTTP 端口
+    pub port: u16,
+    /// 认证令牌，设置后以 `Authorization: Bearer <token>` 请求头发送
+    pub auth_token: Option<String>,
+    /// 每个请求附加的自定义请求头（名称和值），用于代理等特殊部署
+    pub headers: Vec<(String, String)>,
+    /// 请求超时时间
+    pub timeout: Duration,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_HOST.to_string(),
+            port: DEFAULT_PORT,
+            auth_token: None,
+            headers: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl ClientConfig {
+    /// WAAPI HTTP 接口地址
+    pub fn url(&self) -> String {
+        format!("http://{}:{}/waapi", self.host, self.port)
+    }
+}
+
+/// WAAPI 调用错误
+#[derive(Debug, Clone, PartialEq)]
+pub enum WaapiError {
+    /// 认证失败（HTTP 401），通常是代理要求的令牌缺失或无效
+    Unauthorized,
+    /// WAAPI 返回的错误
+    Server {
+        /// HTTP 状态码
+        status: u16,
+        /// 错误信息
+        message: String,
+    },
+    /// 无法建立连接
+    Connection(String),
+    /// 连接被拒绝，通常是 Wwise 未运行或未启用 WAAPI
+    Unreachable {
+        /// WAAPI 主机
+        host: String,
+        /// WAAPI HTTP 端口
+        port: u16,
+    },
+    /// 请求超时
+    Timeout,
+    /// 响应不是预期的 JSON
+    InvalidResponse(String),
+    /// 当前传输不支持的操作（如 HTTP 传输上的主题订阅）
+    #[allow(dead_code)]
+    Unsupported(String),
+}
+
+impl fmt::Display for WaapiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WaapiError::Unauthorized => write!(f, "认证失败：请检查 WAAPI 认证令牌"),
+            WaapiError::Server { status, message } => write!(f, "{} (HTTP {})", message, status),
+            WaapiError::Connection(e) => write!(f, "连接失败: {}", e),
+            WaapiError::Unreachable { host, port } => write!(
+                f,
+                "无法连接到 WAAPI ({}:{})：请确认 Wwise 已运行，并在 User Preferences 中启用了 WAAPI",
+                host, port
+            ),
+            WaapiError::Timeout => write!(f, "请求超时"),
+            WaapiError::InvalidResponse(e) => write!(f, "无效的响应: {}", e),
+            WaapiError::Unsupported(e) => write!(f, "不支持的操作: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for WaapiError {}
+
+/// Wwise 项目信息
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ProjectInfo {
+    /// 项目名称
+    pub name: String,
+    /// 项目中配置的平台名称
+    pub platforms: Vec<String>,
+    /// 项目中配置的语言名称
+    pub languages: Vec<String>,
+    /// 参考语言（默认语言）
+    pub default_language: Option<String>,
+    /// Wwise 中当前选择的平台
+    pub current_platform: Option<String>,
+    /// Wwise 中当前选择的语言
+    pub current_language: Option<String>,
+}
+
+impl ProjectInfo {
+    /// 解析 `ak.wwise.core.getProjectInfo` 的响应
+    ///
+    /// 平台和语言以 `{ "id": ..., "name": ... }` 对象列表返回，
+    /// 默认语言和当前平台/语言通过 ID 对应到名称
+    ///
+    /// # Errors
+    ///
+    /// 响应中缺少项目名称时返回 [`WaapiError::InvalidResponse`]
+    pub fn from_value(value: &Value) -> Result<Self, WaapiError> {
+        let name = value
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| WaapiError::InvalidResponse("缺少项目名称".to_string()))?;
+
+        let platforms = named_entries(value, "platforms");
+        let languages = named_entries(value, "languages");
+
+        let name_of = |entries: &[(String, String)], id_key: &str| -> Option<String> {
+            let id = value.get(id_key).and_then(Value::as_str)?;
+            entries
+                .iter()
+                .find(|(entry_id, _)| entry_id.eq_ignore_ascii_case(id))
+                .map(|(_, name)| name.clone())
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            default_language: name_of(&languages, "referenceLanguageId"),
+            current_platform: name_of(&platforms, "currentPlatformId"),
+            current_language: name_of(&languages, "currentLanguageId"),
+            platforms: platforms.into_iter().map(|(_, name)| name).collect(),
+            languages: languages.into_iter().map(|(_, name)| name).collect(),
+        })
+    }
+}
+
+/// 从 `ak.wwise.core.getInfo` 的响应中读取 Wwise 版本名称（如 `v2023.1.0 Build 8367`）
+///
+/// # Errors
+///
+/// 响应中缺少版本信息时返回 [`WaapiError::InvalidResponse`]
+pub fn wwise_version(value: &Value) -> Result<String, WaapiError> {
+    value
+        .get("version")
+        .and_then(|version| version.get("displayName"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| WaapiError::InvalidResponse("缺少版本信息".to_string()))
+}
+
+/// 从 `ak.wwise.core.object.getTypes` 的响应中读取对象类型名称（如 `Sound`）
+///
+/// # Errors
+///
+/// 响应中缺少类型列表时返回 [`WaapiError::InvalidResponse`]
+pub fn object_type_names(value: &Value) -> Result<Vec<String>, WaapiError> {
+    let items = value
+        .get("return")
+        .and_then(Value::as_array)
+        .ok_or_else(|| WaapiError::InvalidResponse("缺少类型列表".to_string()))?;
+    Ok(items
+        .iter()
+        .filter_map(|item| item.get("name").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect())
+}
+
+/// 找出 JSON 文本中在同一个对象内重复出现的键，按字母顺序返回，每个键只返回一次
+///
+/// 解析为 [`Value`] 时重复的键只保留最后一个值，解析后无法再发现重复，
+/// 因此需要在原始文本上检查。文本不是合法的 JSON 时返回解析失败前找到的键
+pub fn duplicate_keys(text: &str) -> Vec<String> {
+    let mut found = BTreeSet::new();
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let _ = DuplicateKeys(&mut found).deserialize(&mut deserializer);
+    found.into_iter().collect()
+}
+
+/// 遍历 JSON 值并记录对象中重复的键
+struct DuplicateKeys<'a>(&'a mut BTreeSet<String>);
+
+impl<'de> DeserializeSeed<'de> for DuplicateKeys<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for DuplicateKeys<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("JSON 值")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let found = self.0;
+        while seq.next_element_seed(DuplicateKeys(&mut *found))?.is_some() {}
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        let found = self.0;
+        let mut keys = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            map.next_value_seed(DuplicateKeys(&mut *found))?;
+            if let Some(key) = keys.replace(key) {
+                found.insert(key);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 读取 `[{ "id": ..., "name": ... }]` 形式的列表，返回 `(id, name)`
+fn named_entries(value: &Value, key: &str) -> Vec<(String, String)> {
+    value
+        .get(key)
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| {
+                    let name = item.get("name").and_then(Value::as_str)?;
+                    let id = item.get("id").and_then(Value::as_str).unwrap_or("");
+                    Some((id.to_string(), name.to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// WAAPI HTTP 客户端
+pub struct WaapiHttpClient {
+    config: Arc<ClientConfig>,
+    /// HTTP 代理，克隆的代理共用同一个连接池
+    agent: Agent,
+    /// 最近一次响应中重复的键
+    duplicate_keys: Vec<String>,
+}
+
+impl Default for WaapiHttpClient {
+    fn default() -> Self {
+        Self::new(ClientConfig::default())
+    }
+}
+
+impl WaapiHttpClient {
+    /// 使用指定配置创建客户端
+    pub fn new(config: ClientConfig) -> Self {
+        let agent = Self::build_agent(&config);
+        Self {
+            config: Arc::new(config),
+            agent,
+            duplicate_keys: Vec::new(),
+        }
+    }
+
+    /// 创建共用同一配置和连接池的客户端，用于在后台线程中发送请求
+    pub fn share(&self) -> Self {
+        Self {
+            config: Arc::clone(&self.config),
+            agent: self.agent.clone(),
+            duplicate_keys: Vec::new(),
+        }
+    }
+
+    /// 取出最近一次响应中在同一个对象内重复出现的键，见 [`duplicate_keys`]
+    pub fn take_duplicate_keys(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.duplicate_keys)
+    }
+
+    /// 创建 HTTP 代理，非 2xx 状态码不作为传输错误，以便读取 WAAPI 的错误信息
+    fn build_agent(config: &ClientConfig) -> Agent {
+        let agent_config = Agent::config_builder()
+            .timeout_global(Some(config.timeout))
+            .http_status_as_error(false)
+            .build();
+        Agent::new_with_config(agent_config)
+    }
+
+    /// 调用 WAAPI 接口
+    ///
+    /// 认证失败时使用新的连接重试一次，避免代理复用已失效的认证连接
+    ///
+    /// # Errors
+    ///
+    /// 连接失败、超时、认证失败或 WAAPI 返回错误时返回 [`WaapiError`]
+    pub fn call(
+        &mut self,
+        uri: &str,
+        args: Value,
+        options: Option<Value>,
+    ) -> Result<Value, WaapiError> {
+        let mut body = json!({ "uri": uri, "args": args });
+        if let Some(options) = options {
+            body["options"] = options;
+        }
+
+        match self.send(&body) {
+            Err(WaapiError::Unauthorized) if self.config.auth_token.is_some() => {
+                self.agent = Self::build_agent(&self.config);
+                self.send(&body)
+            }
+            result => result,
+        }
+    }
+
+    /// 执行 WAQL 查询
+    ///
+    /// # Errors
+    ///
+    /// 调用失败或响应不是 JSON 对象时返回错误
+    pub fn waql_query(
+        &mut self,
+        query: &str,
+        options: Option<Value>,
+    ) -> Result<Map<String, Value>, WaapiError> {
+        match self.call(WAQL_URI, json!({ "waql": query }), options)? {
+            Value::Object(result) => Ok(result),
+            other => Err(WaapiError::InvalidResponse(format!(
+                "期望 JSON 对象，实际为 {}",
+                other
+            ))),
+        }
+    }
+
+    /// 发送请求并解析响应，同时记录响应中重复的键
+    fn send(&mut self, body: &Value) -> Result<Value, WaapiError> {
+        let mut request = self.agent.post(self.config.url());
+        if let Some(token) = &self.config.auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        for (name, value) in &self.config.headers {
+            request = request.header(name, value);
+        }
+
+        let mut response = request
+            .send_json(body)
+            .map_err(|e| map_transport_error(e, &self.config))?;
+        let status = response.status().as_u16();
+        let text = match response
+            .body_mut()
+            .with_config()
+            .limit(MAX_RESPONSE_BYTES)
+            .read_to_string()
+        {
+            Ok(text) => Ok(text),
+            Err(e) if is_timeout(&e) => return Err(WaapiError::Timeout),
+            Err(e) => Err(e.to_string()),
+        };
+        self.duplicate_keys = text.as_deref().map(duplicate_keys).unwrap_or_default();
+        let value = text
+            .and_then(|text| serde_json::from_str::<Value>(&text).map_err(|e| e.to_string()));
+
+        if status == 401 {
+            return Err(WaapiError::Unauthorized);
+        }
+        if !(200..300).contains(&status) {
+            let message = value
+                .ok()
+                .and_then(|v| v.get("message").and_then(Value::as_str).map(str::to_string))
+                .unwrap_or_else(|| format!("HTTP {}", status));
+            return Err(WaapiError::Server { status, message });
+        }
+
+        value.map_err(WaapiError::InvalidResponse)
+    }
+}
+
+/// 将传输层错误转换为 WAAPI 错误
+///
+/// 连接被拒绝时转换为 [`WaapiError::Unreachable`]，提示检查 Wwise 的 WAAPI 设置
+fn map_transport_error(error: ureq::Error, config: &ClientConfig) -> WaapiError {
+    match error {
+        ureq::Error::Timeout(_) => WaapiError::Timeout,
+        ureq::Error::ConnectionFailed => unreachable_error(config),
+        ureq::Error::Io(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+            unreachable_error(config)
+        }
+        ureq::Error::StatusCode(401) => WaapiError::Unauthorized,
+        ureq::Error::StatusCode(status) => WaapiError::Server {
+            status,
+            message: format!("HTTP {}", status),
+        },
+        other => WaapiError::Connection(other.to_string()),
+    }
+}
+
+/// 是否为请求或读取响应正文时的超时
+fn is_timeout(error: &ureq::Error) -> bool {
+    match error {
+        ureq::Error::Timeout(_) => true,
+        ureq::Error::Io(e) => e.kind() == std::io::ErrorKind::TimedOut,
+        _ => false,
+    }
+}
+
+/// 连接被拒绝时的错误
+fn unreachable_error(config: &ClientConfig) -> WaapiError {
+    WaapiError::Unreachable {
+        host: config.host.clone(),
+        port: config.port,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_config() {
+        let config = ClientConfig::default();
+        assert_eq!(config.url(), "http://127.0.0.1:8090/waapi");
+        assert!(config.auth_token.is_none());
+        assert!(config.headers.is_empty());
+    }
+
+    /// 在本地端口上启动只处理一个请求的 HTTP 服务，先发送响应头，再调用 `write_body` 发送正文
+    fn serve_once(
+        content_length: usize,
+        write_body: impl FnOnce(&mut std::net::TcpStream) + Send + 'static,
+    ) -> ClientConfig {
+        use std::io::{BufRead, BufReader, Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut body_len = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(len) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    body_len = len.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            reader.read_exact(&mut vec![0; body_len]).unwrap();
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n",
+                content_length
+            );
+            stream.write_all(head.as_bytes()).unwrap();
+            write_body(&mut stream);
+        });
+        ClientConfig {
+            port,
+            ..ClientConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_large_response() {
+        use std::io::Write;
+
+        let item = r#"{"name":"Footstep_Concrete_01","type":"Sound"},"#;
+        let count = 11 * 1024 * 1024 / item.len() + 1;
+        let mut body = format!(r#"{{"return":[{}"#, item.repeat(count));
+        body.pop();
+        body.push_str("]}");
+        assert!(body.len() > 10 * 1024 * 1024);
+
+        let config = serve_once(body.len(), move |stream| {
+            stream.write_all(body.as_bytes()).unwrap();
+        });
+        let mut client = WaapiHttpClient::new(config);
+        let result = client.waql_query("$ from type Sound", None).unwrap();
+        assert_eq!(result["return"].as_array().unwrap().len(), count);
+    }
+
+    #[test]
+    fn test_body_timeout() {
+        use std::io::Write;
+
+        let config = serve_once(100, |stream| {
+            stream.write_all(br#"{"return": ["#).unwrap();
+            std::thread::sleep(Duration::from_secs(2));
+        });
+        let mut client = WaapiHttpClient::new(ClientConfig {
+            timeout: Duration::from_millis(300),
+            ..config
+        });
+        assert_eq!(client.waql_query("$ from type Sound", None), Err(WaapiError::Timeout));
+    }
+
+    #[test]
+    fn test_share_client() {
+        let mut client = WaapiHttpClient::new(ClientConfig::default());
+        client.duplicate_keys = vec!["name".to_string()];
+        let shared = client.share();
+        assert!(Arc::ptr_eq(&client.config, &shared.config));
+        assert!(shared.duplicate_keys.is_empty());
+    }
+
+    #[test]
+    fn test_is_mutating_uri() {
+        assert!(!is_mutating_uri(WAQL_URI));
+        assert!(!is_mutating_uri(WWISE_INFO_URI));
+        assert!(!is_mutating_uri("ak.wwise.core.object.isPropertyEnabled"));
+        assert!(is_mutating_uri("ak.wwise.core.object.setProperty"));
+        assert!(is_mutating_uri("ak.wwise.core.object.create"));
+        assert!(is_mutating_uri("ak.wwise.ui.commands.execute"));
+    }
+
+    #[test]
+    fn test_map_status_errors() {
+        let config = ClientConfig::default();
+        assert_eq!(
+            map_transport_error(ureq::Error::StatusCode(401), &config),
+            WaapiError::Unauthorized
+        );
+        assert!(matches!(
+            map_transport_error(ureq::Error::StatusCode(500), &config),
+            WaapiError::Server { status: 500, .. }
+        ));
+    }
+
+    #[test]
+    fn test_map_connection_refused() {
+        let config = ClientConfig::default();
+        let refused = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+        let error = map_transport_error(ureq::Error::Io(refused), &config);
+        assert_eq!(
+            error,
+            WaapiError::Unreachable {
+                host: DEFAULT_HOST.to_string(),
+                port: DEFAULT_PORT,
+            }
+        );
+        let message = error.to_string();
+        assert!(message.contains("127.0.0.1:8090"));
+        assert!(message.contains("WAAPI"));
+
+        let timeout = map_transport_error(ureq::Error::Timeout(ureq::Timeout::Global), &config);
+        assert_eq!(timeout, WaapiError::Timeout);
+        assert_ne!(timeout.to_string(), message);
+    }
+
+    #[test]
+    fn test_project_info_from_value() {
+        let value = json!({
+            "name": "Demo",
+            "platforms": [
+                {"id": "{P1}", "name": "Windows"},
+                {"id": "{P2}", "name": "Switch"}
+            ],
+            "languages": [
+                {"id": "{L1}", "name": "SFX"},
+                {"id": "{L2}", "name": "English(US)"}
+            ],
+            "referenceLanguageId": "{L2}",
+            "currentPlatformId": "{p2}",
+            "currentLanguageId": "{L9}"
+        });
+        let info = ProjectInfo::from_value(&value).unwrap();
+        assert_eq!(info.name, "Demo");
+        assert_eq!(info.platforms, vec!["Windows", "Switch"]);
+        assert_eq!(info.languages, vec!["SFX", "English(US)"]);
+        assert_eq!(info.default_language.as_deref(), Some("English(US)"));
+        assert_eq!(info.current_platform.as_deref(), Some("Switch"));
+        assert!(info.current_language.is_none());
+
+        assert!(ProjectInfo::from_value(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_wwise_version() {
+        let value = json!({
+            "displayName": "Wwise",
+            "version": {"displayName": "v2023.1.0 Build 8367", "year": 2023}
+        });
+        assert_eq!(wwise_version(&value).unwrap(), "v2023.1.0 Build 8367");
+        assert!(wwise_version(&json!({"version": {}})).is_err());
+    }
+
+    #[test]
+    fn test_object_type_names() {
+        let value = json!({
+            "return": [
+                {"classId": 16, "name": "Sound", "type": "WObject"},
+                {"classId": 32, "name": "Event", "type": "WObject"}
+            ]
+        });
+        assert_eq!(object_type_names(&value).unwrap(), vec!["Sound", "Event"]);
+        assert!(object_type_names(&json!({})).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_keys() {
+        let text = r#"{"return": [{"name": "a", "id": 1, "name": "b"}, {"x": {"y": 1, "y": 2}}]}"#;
+        assert_eq!(duplicate_keys(text), vec!["name", "y"]);
+        assert!(duplicate_keys(r#"{"return": [{"name": "a"}, {"name": "b"}]}"#).is_empty());
+        assert!(duplicate_keys("not json").is_empty());
+    }
+
+    #[test]
+    fn test_unauthorized_message() {
+        assert!(WaapiError::Unauthorized.to_string().contains("令牌"));
+    }
+}
@@ -0,0 +1,230 @@
+//! 代码补全
+//!
+//! 提取光标处的标识符并根据前缀匹配补全候选词
+
+use std::collections::BTreeSet;
+use std::ops::Range;
+
+use egui_code_editor::Syntax;
+
+/// 光标处的标识符
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaretToken<'a> {
+    /// 标识符在文本中的字节范围
+    pub range: Range<usize>,
+    /// 从标识符开头到光标处的文本，用于匹配补全
+    pub prefix: &'a str,
+}
+
+/// 判断字符是否属于标识符
+///
+/// 访问器名称中包含 `:`（如 `sound:originalWavFilePath`），因此视为标识符的一部分；
+/// 空白和 `@`、`.`、`=`、`"` 等 WAQL 运算符均作为分隔符
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '_' || ch == ':'
+}
+
+/// 提取光标处（或光标之前紧邻）的标识符
+///
+/// `caret` 为字符索引。标识符向前延伸到分隔符为止，向后延伸到标识符结束，
+/// 以便补全时替换整个单词
+pub fn token_at_caret(text: &str, caret: usize) -> CaretToken<'_> {
+    let caret_byte = text
+        .char_indices()
+        .nth(caret)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+
+    let start = text[..caret_byte]
+        .char_indices()
+        .rev()
+        .take_while(|(_, ch)| is_identifier_char(*ch))
+        .last()
+        .map(|(i, _)| i)
+        .unwrap_or(caret_byte);
+
+    let end = text[caret_byte..]
+        .char_indices()
+        .find(|(_, ch)| !is_identifier_char(*ch))
+        .map(|(i, _)| caret_byte + i)
+        .unwrap_or(text.len());
+
+    CaretToken {
+        range: start..end,
+        prefix: &text[start..caret_byte],
+    }
+}
+
+/// 补全词库
+#[derive(Debug, Clone, Default)]
+pub struct WordCompleter {
+    words: BTreeSet<String>,
+}
+
+impl WordCompleter {
+    /// 使用语法定义中的关键字、类型和特殊符号创建词库
+    pub fn from_syntax(syntax: &Syntax) -> Self {
+        let mut completer = Self::default();
+        for word in syntax
+            .keywords
+            .iter()
+            .chain(syntax.types.iter())
+            .chain(syntax.special.iter())
+        {
+            completer.push_word(word);
+        }
+        completer
+    }
+
+    /// 添加补全词
+    ///
+    /// 不包含字母或数字的词（如 `$`）不会被添加
+    pub fn push_word(&mut self, word: &str) {
+        if word.chars().any(char::is_alphanumeric) {
+            self.words.insert(word.to_string());
+        }
+    }
+
+    /// 获取匹配前缀的补全候选（忽略大小写）
+    ///
+    /// 与前缀完全相同的词不会作为候选
+    pub fn suggestions(&self, prefix: &str, max: usize) -> Vec<&str> {
+        self.suggestions_preferring(prefix, max, |_| false)
+    }
+
+    /// 获取补全候选，满足 `prefer` 的词排在前面
+    ///
+    /// 两组内部仍按字母顺序排列
+    pub fn suggestions_preferring(
+        &self,
+        prefix: &str,
+        max: usize,
+        prefer: impl Fn(&str) -> bool,
+    ) -> Vec<&str> {
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+        let prefix = prefix.to_lowercase();
+        Self::ranked(
+            self.words
+                .iter()
+                .filter(|word| word.len() > prefix.len() && word.to_lowercase().starts_with(&prefix))
+                .map(String::as_str),
+            max,
+            prefer,
+        )
+    }
+
+    /// 获取所有补全词，满足 `prefer` 的词排在前面
+    ///
+    /// 用于强制打开补全时光标处还没有输入任何字符的情况
+    pub fn all_preferring(&self, max: usize, prefer: impl Fn(&str) -> bool) -> Vec<&str> {
+        Self::ranked(self.words.iter().map(String::as_str), max, prefer)
+    }
+
+    /// 将满足 `prefer` 的词排在前面并截断到 `max` 个
+    fn ranked<'a>(
+        words: impl Iterator<Item = &'a str>,
+        max: usize,
+        prefer: impl Fn(&str) -> bool,
+    ) -> Vec<&'a str> {
+        let (mut preferred, others): (Vec<&str>, Vec<&str>) = words.partition(|word| prefer(word));
+        preferred.extend(others);
+        preferred.truncate(max);
+        preferred
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_at_end_of_text() {
+        let token = token_at_caret("$ from type Sou", 15);
+        assert_eq!(token.prefix, "Sou");
+        assert_eq!(token.range, 12..15);
+    }
+
+    #[test]
+    fn test_token_in_middle_of_word() {
+        // 光标位于 "Sou|nd" 中间
+        let token = token_at_caret("$ from type Sound where", 15);
+        assert_eq!(token.prefix, "Sou");
+        assert_eq!(token.range, 12..17);
+    }
+
+    #[test]
+    fn test_token_after_at_sign() {
+        let token = token_at_caret("$ from type Sound where @Vol", 28);
+        assert_eq!(token.prefix, "Vol");
+        assert_eq!(token.range, 25..28);
+    }
+
+    #[test]
+    fn test_token_after_dot() {
+        let token = token_at_caret("$ this where parent.na", 22);
+        assert_eq!(token.prefix, "na");
+    }
+
+    #[test]
+    fn test_token_after_separator_is_empty() {
+        assert_eq!(token_at_caret("$ from ", 7).prefix, "");
+        assert_eq!(token_at_caret("name = \"", 8).prefix, "");
+        assert_eq!(token_at_caret("", 0).prefix, "");
+    }
+
+    #[test]
+    fn test_token_keeps_accessor_namespace() {
+        let token = token_at_caret("sound:orig", 10);
+        assert_eq!(token.prefix, "sound:orig");
+    }
+
+    #[test]
+    fn test_token_with_multibyte_text() {
+        let token = token_at_caret("name : \"脚步\" and vol", 19);
+        assert_eq!(token.prefix, "vol");
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let mut completer = WordCompleter::default();
+        completer.push_word("Volume");
+        completer.push_word("VoiceVolume");
+        completer.push_word("$");
+
+        assert_eq!(completer.suggestions("vo", 10), vec!["VoiceVolume", "Volume"]);
+        assert_eq!(completer.suggestions("vo", 1).len(), 1);
+        assert!(completer.suggestions("Volume", 10).is_empty());
+        assert!(completer.suggestions("", 10).is_empty());
+        assert!(completer.suggestions("$", 10).is_empty());
+    }
+
+    #[test]
+    fn test_suggestions_preferring() {
+        let mut completer = WordCompleter::default();
+        completer.push_word("parent");
+        completer.push_word("panner");
+        completer.push_word("Path");
+
+        assert_eq!(
+            completer.suggestions_preferring("pa", 10, |w| w == "parent"),
+            vec!["parent", "Path", "panner"]
+        );
+        assert_eq!(completer.suggestions_preferring("pa", 1, |w| w == "parent"), vec!["parent"]);
+    }
+
+    #[test]
+    fn test_all_preferring() {
+        let mut completer = WordCompleter::default();
+        completer.push_word("parent");
+        completer.push_word("name");
+        completer.push_word("Volume");
+
+        assert_eq!(
+            completer.all_preferring(10, |w| w == "parent"),
+            vec!["parent", "Volume", "name"]
+        );
+        assert_eq!(completer.all_preferring(2, |_| false), vec!["Volume", "name"]);
+    }
+}
@@ -0,0 +1,49 @@
+//! 示例查询
+//!
+//! 编辑器为空时轮流显示的示例和模板菜单中的常用查询，帮助新用户通过例子了解 WAQL 语法
+
+/// 编辑器为空时轮流显示的示例查询
+pub const EXAMPLE_QUERIES: &[&str] = &[
+    "$ from type Event",
+    "$ from type Sound where name : \"foot\"",
+    "$ from type Sound where @Volume < -6",
+    "$ from type ActorMixer select children",
+    "$ from type Sound | name @Volume",
+    "$ from type Event select children | name type",
+];
+
+/// 模板菜单中内置的常用查询（名称和查询语句）
+pub const QUERY_TEMPLATES: &[(&str, &str)] = &[
+    ("All Events", "$ from type Event"),
+    ("Sounds with high volume", "$ from type Sound where @Volume > 0 | name @Volume"),
+    ("Sounds not referenced directly", "$ from type Sound where referencesTo.count = 0"),
+    ("Events without actions", "$ from type Event where childrenCount = 0"),
+    (
+        "Streamed sounds",
+        "$ from type Sound where @IsStreamingEnabled = true | name @IsStreamingEnabled",
+    ),
+    ("Work units with unsaved changes", "$ from type WorkUnit where workunitIsDirty = true"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waql::split_statements;
+
+    #[test]
+    fn test_examples_are_single_statements() {
+        for example in EXAMPLE_QUERIES {
+            assert!(example.starts_with("$ "), "{example}");
+            assert_eq!(split_statements(example), vec![0..example.len()], "{example}");
+        }
+    }
+
+    #[test]
+    fn test_templates_are_single_statements() {
+        for (i, (name, query)) in QUERY_TEMPLATES.iter().enumerate() {
+            assert!(query.starts_with("$ "), "{name}");
+            assert_eq!(split_statements(query), vec![0..query.len()], "{name}");
+            assert!(QUERY_TEMPLATES[..i].iter().all(|(other, _)| other != name), "{name}");
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! `where` 条件生成
+//!
+//! 为可视化筛选构建器生成 `where`/`and` 条件并追加到查询中，不解析已有的条件
+
+use super::quote::{quote_string, quote_value};
+use super::select::{is_clause_keyword, words};
+use crate::query_executor::split_query_options;
+
+/// 条件的比较方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WhereOp {
+    /// 等于
+    #[default]
+    Eq,
+    /// 不等于
+    Ne,
+    /// 小于
+    Lt,
+    /// 小于等于
+    Le,
+    /// 大于
+    Gt,
+    /// 大于等于
+    Ge,
+    /// 包含文本
+    Contains,
+}
+
+impl WhereOp {
+    /// 所有比较方式
+    pub const ALL: [WhereOp; 7] = [
+        WhereOp::Eq,
+        WhereOp::Ne,
+        WhereOp::Lt,
+        WhereOp::Le,
+        WhereOp::Gt,
+        WhereOp::Ge,
+        WhereOp::Contains,
+    ];
+
+    /// WAQL 中的运算符
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            WhereOp::Eq => "=",
+            WhereOp::Ne => "!=",
+            WhereOp::Lt => "<",
+            WhereOp::Le => "<=",
+            WhereOp::Gt => ">",
+            WhereOp::Ge => ">=",
+            WhereOp::Contains => ":",
+        }
+    }
+
+    /// 显示名称
+    pub fn label(&self) -> &'static str {
+        match self {
+            WhereOp::Contains => "contains",
+            other => other.symbol(),
+        }
+    }
+}
+
+/// 一个 `where` 条件，如 `name : "foot"`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WhereCondition {
+    /// 访问器或属性，属性以 `@` 开头
+    pub property: String,
+    /// 比较方式
+    pub op: WhereOp,
+    /// 比较的值
+    pub value: String,
+}
+
+impl WhereCondition {
+    /// 转换为 WAQL 条件文本
+    ///
+    /// 值按 [`quote_value`] 写入，包含文本时总是作为字符串
+    pub fn to_waql(&self) -> String {
+        let value = match self.op {
+            WhereOp::Contains => quote_string(self.value.trim()),
+            _ => quote_value(&self.value),
+        };
+        format!("{} {} {}", self.property.trim(), self.op.symbol(), value)
+    }
+}
+
+/// 将条件追加到查询中，返回新的查询
+///
+/// 查询最后一个子句是 `where` 时以 `and` 连接，否则新增 `where` 子句。
+/// 条件插入在 `|` 选项部分之前
+pub fn append_condition(query: &str, condition: &WhereCondition) -> String {
+    let (head, options) = match split_query_options(query) {
+        Some((head, options)) => (head, Some(options)),
+        None => (query, None),
+    };
+    let keyword = match words(head).into_iter().rev().find(|(_, w)| is_clause_keyword(w)) {
+        Some((_, word)) if word.eq_ignore_ascii_case("where") => "and",
+        _ => "where",
+    };
+    let mut result = format!("{} {} {}", head.trim_end(), keyword, condition.to_waql());
+    if let Some(options) = options {
+        result.push_str(" |");
+        result.push_str(options);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn condition(property: &str, op: WhereOp, value: &str) -> WhereCondition {
+        WhereCondition {
+            property: property.to_string(),
+            op,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_condition_to_waql() {
+        assert_eq!(condition("@Volume", WhereOp::Lt, "-6").to_waql(), "@Volume < -6");
+        assert_eq!(condition("isPlayable", WhereOp::Eq, "true").to_waql(), "isPlayable = true");
+        assert_eq!(condition("name", WhereOp::Contains, "12").to_waql(), r#"name : "12""#);
+        assert_eq!(
+            condition("notes", WhereOp::Ne, r#"say "hi""#).to_waql(),
+            r#"notes != "say \"hi\"""#
+        );
+    }
+
+    #[test]
+    fn test_append_condition() {
+        let name = condition("name", WhereOp::Contains, "foot");
+        assert_eq!(
+            append_condition("$ from type Sound", &name),
+            r#"$ from type Sound where name : "foot""#
+        );
+        assert_eq!(
+            append_condition("$ from type Sound where @Volume < 0 ", &name),
+            r#"$ from type Sound where @Volume < 0 and name : "foot""#
+        );
+        assert_eq!(
+            append_condition("$ from type Sound where @Volume < 0 select parent", &name),
+            r#"$ from type Sound where @Volume < 0 select parent where name : "foot""#
+        );
+        assert_eq!(
+            append_condition(r#"$ from type Sound where name : "a|b" | id name"#, &name),
+            r#"$ from type Sound where name : "a|b" and name : "foot" | id name"#
+        );
+    }
+}
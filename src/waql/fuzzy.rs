@@ -0,0 +1,119 @@
+//! 模糊匹配
+//!
+//! 提供简单的子序列模糊匹配算法，用于属性面板等需要快速检索的场景
+
+/// 模糊匹配结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FuzzyMatch {
+    /// 匹配得分，越高越相关
+    pub score: i32,
+    /// 候选字符串中被匹配的字符索引（按字符计）
+    pub indices: Vec<usize>,
+}
+
+/// 对候选字符串进行模糊匹配
+///
+/// 匹配规则为忽略大小写的子序列匹配：`pattern` 中的每个字符必须按顺序出现在
+/// `candidate` 中。连续匹配、单词起始位置匹配和前缀匹配会获得额外加分。
+///
+/// # Returns
+///
+/// 匹配成功时返回得分和匹配位置，否则返回 `None`。空模式匹配任意候选。
+pub fn fuzzy_match(pattern: &str, candidate: &str) -> Option<FuzzyMatch> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if pattern.is_empty() {
+        return Some(FuzzyMatch {
+            score: 0,
+            indices: Vec::new(),
+        });
+    }
+
+    let chars: Vec<char> = candidate.chars().collect();
+    let mut indices = Vec::with_capacity(pattern.len());
+    let mut score = 0;
+    let mut pattern_pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (index, ch) in chars.iter().enumerate() {
+        if pattern_pos == pattern.len() {
+            break;
+        }
+        if !ch.to_lowercase().eq(std::iter::once(pattern[pattern_pos])) {
+            continue;
+        }
+
+        score += 1;
+        if index == 0 {
+            // 前缀匹配
+            score += 8;
+        } else if is_word_start(&chars, index) {
+            // 单词起始位置（驼峰、分隔符之后）
+            score += 5;
+        }
+        match last_match {
+            Some(last) if last + 1 == index => score += 4,
+            Some(last) => score -= (index - last - 1).min(3) as i32,
+            None => {}
+        }
+
+        indices.push(index);
+        last_match = Some(index);
+        pattern_pos += 1;
+    }
+
+    if pattern_pos < pattern.len() {
+        return None;
+    }
+
+    // 越短的候选越精确
+    score -= (chars.len() as i32 - pattern.len() as i32).min(10) / 2;
+
+    Some(FuzzyMatch { score, indices })
+}
+
+/// 判断指定位置是否为单词起始位置
+fn is_word_start(chars: &[char], index: usize) -> bool {
+    let prev = chars[index - 1];
+    let current = chars[index];
+    !prev.is_alphanumeric() || (prev.is_lowercase() && current.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_pattern_matches_everything() {
+        let m = fuzzy_match("", "Volume").unwrap();
+        assert_eq!(m.score, 0);
+        assert!(m.indices.is_empty());
+    }
+
+    #[test]
+    fn test_subsequence_match() {
+        let m = fuzzy_match("vlm", "Volume").unwrap();
+        assert_eq!(m.indices, vec![0, 2, 4]);
+        assert!(fuzzy_match("xyz", "Volume").is_none());
+    }
+
+    #[test]
+    fn test_case_insensitive() {
+        assert!(fuzzy_match("VOL", "Volume").is_some());
+        assert!(fuzzy_match("ohp", "OutputBusHighpass").is_some());
+    }
+
+    #[test]
+    fn test_prefix_and_word_start_rank_higher() {
+        let prefix = fuzzy_match("vol", "Volume").unwrap();
+        let inner = fuzzy_match("vol", "OutputBusVolume").unwrap();
+        assert!(prefix.score > inner.score);
+
+        let camel = fuzzy_match("bv", "BusVolume").unwrap();
+        let scattered = fuzzy_match("bv", "bypassView").unwrap();
+        assert!(camel.score > scattered.score);
+    }
+}
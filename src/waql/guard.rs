@@ -0,0 +1,77 @@
+//! 大查询检查
+//!
+//! 以文本启发式方式判断查询是否可能返回大量对象，不做完整的语法分析
+
+use super::select::{is_clause_keyword, words};
+use crate::query_executor::split_query_options;
+
+/// 查询没有 `where` 和 `take` 子句，并且 `from type` 中有 `broad_types` 里的类型时返回该类型
+///
+/// 类型名不区分大小写，`|` 之后的选项部分不参与判断
+pub fn broad_query_type<'a>(query: &'a str, broad_types: &[String]) -> Option<&'a str> {
+    let head = split_query_options(query).map_or(query, |(head, _)| head);
+    let words = words(head);
+    if words
+        .iter()
+        .any(|(_, w)| w.eq_ignore_ascii_case("where") || w.eq_ignore_ascii_case("take"))
+    {
+        return None;
+    }
+
+    let type_index = words.windows(2).position(|pair| {
+        pair[0].1.eq_ignore_ascii_case("from") && pair[1].1.eq_ignore_ascii_case("type")
+    })?;
+    words[type_index + 2..]
+        .iter()
+        .map(|(_, word)| *word)
+        .take_while(|word| !is_clause_keyword(word))
+        .find(|word| broad_types.iter().any(|t| t.eq_ignore_ascii_case(word)))
+}
+
+/// 在查询末尾追加 `take` 子句限制返回的对象数，插入在 `|` 选项部分之前
+pub fn append_take(query: &str, count: usize) -> String {
+    let (head, options) = match split_query_options(query) {
+        Some((head, options)) => (head, Some(options)),
+        None => (query, None),
+    };
+    let mut result = format!("{} take {}", head.trim_end(), count);
+    if let Some(options) = options {
+        result.push_str(" |");
+        result.push_str(options);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn types() -> Vec<String> {
+        vec!["Sound".to_string(), "Event".to_string()]
+    }
+
+    #[test]
+    fn test_broad_query_type() {
+        let types = types();
+        assert_eq!(broad_query_type("$ from type Sound", &types), Some("Sound"));
+        assert_eq!(broad_query_type("$ FROM TYPE sound | name", &types), Some("sound"));
+        assert_eq!(
+            broad_query_type("$ from type Bus, Event select children", &types),
+            Some("Event")
+        );
+        assert_eq!(broad_query_type("$ from type Sound where name : \"a\"", &types), None);
+        assert_eq!(broad_query_type("$ from type Sound take 10", &types), None);
+        assert_eq!(broad_query_type("$ from type Bus", &types), None);
+        assert_eq!(broad_query_type("$ from object \"\\\\Events\"", &types), None);
+        assert_eq!(broad_query_type("$ from type Sound", &[]), None);
+    }
+
+    #[test]
+    fn test_append_take() {
+        assert_eq!(append_take("$ from type Sound ", 1000), "$ from type Sound take 1000");
+        assert_eq!(
+            append_take("$ from type Sound | name @Volume", 50),
+            "$ from type Sound take 50 | name @Volume"
+        );
+    }
+}
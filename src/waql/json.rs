@@ -0,0 +1,142 @@
+//! JSON 词法分析
+//!
+//! 将原始 JSON 结果切分为带类型的片段，用于结果视图的语法高亮。
+//! 输入可能是被截断的 JSON，因此不校验结构，未闭合的字符串延续到文本末尾
+
+use std::ops::Range;
+
+use super::TokenKind;
+
+/// 将 JSON 文本切分为高亮片段
+///
+/// 对象键使用 [`TokenKind::Type`]，字符串值使用 [`TokenKind::Str`]，
+/// 数字使用 [`TokenKind::Numeric`]，`true`/`false`/`null` 使用 [`TokenKind::Literal`]。
+/// 返回的范围覆盖整个输入且互不重叠
+pub fn tokenize_json(text: &str) -> Vec<(TokenKind, Range<usize>)> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let kind = match bytes[i] {
+            b'"' => {
+                i = string_end(bytes, i);
+                let next = bytes[i..].iter().find(|b| !b.is_ascii_whitespace());
+                if next == Some(&b':') {
+                    TokenKind::Type
+                } else {
+                    TokenKind::Str
+                }
+            }
+            b if b.is_ascii_whitespace() => {
+                i = scan_while(bytes, i, |b| b.is_ascii_whitespace());
+                TokenKind::Whitespace
+            }
+            b'-' | b'0'..=b'9' => {
+                i = scan_while(bytes, i + 1, |b| {
+                    b.is_ascii_digit() || matches!(b, b'.' | b'e' | b'E' | b'+' | b'-')
+                });
+                TokenKind::Numeric
+            }
+            b if b.is_ascii_alphabetic() => {
+                i = scan_while(bytes, i, |b| b.is_ascii_alphabetic());
+                match &text[start..i] {
+                    "true" | "false" | "null" => TokenKind::Literal,
+                    _ => TokenKind::Unknown,
+                }
+            }
+            b if b.is_ascii() => {
+                i += 1;
+                TokenKind::Punctuation
+            }
+            _ => {
+                // 字符串之外的非 ASCII 字符，整体作为未知片段以保持字符边界
+                i = scan_while(bytes, i, |b| !b.is_ascii());
+                TokenKind::Unknown
+            }
+        };
+        tokens.push((kind, start..i));
+    }
+    tokens
+}
+
+/// 返回从 `start` 处引号开始的字符串结束位置（闭合引号之后）
+fn string_end(bytes: &[u8], start: usize) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// 从 `start` 开始跳过满足条件的字节，返回第一个不满足条件的位置
+fn scan_while(bytes: &[u8], start: usize, predicate: impl Fn(u8) -> bool) -> usize {
+    bytes[start..]
+        .iter()
+        .position(|&b| !predicate(b))
+        .map(|offset| start + offset)
+        .unwrap_or(bytes.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(text: &str) -> Vec<(TokenKind, &str)> {
+        tokenize_json(text)
+            .into_iter()
+            .filter(|(kind, _)| *kind != TokenKind::Whitespace)
+            .map(|(kind, range)| (kind, &text[range]))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_object() {
+        assert_eq!(
+            kinds(r#"{"name": "Foot \"step\"", "@Volume": -3.5e1, "ok": true, "x": null}"#),
+            vec![
+                (TokenKind::Punctuation, "{"),
+                (TokenKind::Type, r#""name""#),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::Str, r#""Foot \"step\"""#),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Type, r#""@Volume""#),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::Numeric, "-3.5e1"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Type, r#""ok""#),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::Literal, "true"),
+                (TokenKind::Punctuation, ","),
+                (TokenKind::Type, r#""x""#),
+                (TokenKind::Punctuation, ":"),
+                (TokenKind::Literal, "null"),
+                (TokenKind::Punctuation, "}"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_covers_input() {
+        let text = "[\n  \"脚步\",\n  \"unterminated";
+        let tokens = tokenize_json(text);
+        let mut end = 0;
+        for (_, range) in &tokens {
+            assert_eq!(range.start, end);
+            end = range.end;
+        }
+        assert_eq!(end, text.len());
+        assert_eq!(tokens.last().unwrap().0, TokenKind::Str);
+    }
+
+    #[test]
+    fn test_tokenize_truncated_escape() {
+        let text = r#""abc\"#;
+        assert_eq!(tokenize_json(text), vec![(TokenKind::Str, 0..text.len())]);
+    }
+}
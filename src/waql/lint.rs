@@ -0,0 +1,139 @@
+//! 查询检查
+//!
+//! 以文本启发式方式找出查询中可能的错误，只产生提示，不阻止执行
+
+use super::select::{is_clause_keyword, words};
+use crate::query_executor::split_query_options;
+
+/// 检查提示的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    /// 用 `name =` 比较名称，可能想用 `:` 搜索名称中的文本
+    NameEquals,
+    /// `from type` 中的类型不在项目支持的类型中
+    UnknownType,
+    /// `where` 之后没有条件
+    EmptyWhere,
+}
+
+/// 一条检查提示
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    /// 类别
+    pub kind: LintKind,
+    /// 提示信息
+    pub message: String,
+}
+
+impl LintWarning {
+    fn new(kind: LintKind, message: String) -> Self {
+        Self { kind, message }
+    }
+}
+
+/// 检查查询中常见的错误
+///
+/// `types` 为项目支持的对象类型名称，为 `None`（尚未获取）时不检查类型。
+/// `|` 之后的选项部分不参与检查
+pub fn lint(query: &str, types: Option<&[String]>) -> Vec<LintWarning> {
+    let head = split_query_options(query).map_or(query, |(head, _)| head);
+    let words = words(head);
+    let mut warnings = Vec::new();
+
+    // `where` 子句中的 `name = ...`
+    let mut clause = "";
+    for &(start, word) in &words {
+        if is_clause_keyword(word) {
+            clause = word;
+        } else if clause.eq_ignore_ascii_case("where")
+            && word.eq_ignore_ascii_case("name")
+            && head[start + word.len()..].trim_start().starts_with('=')
+        {
+            warnings.push(LintWarning::new(
+                LintKind::NameEquals,
+                "`name =` 只匹配完整的名称，搜索名称中的文本请使用 `name :`".to_string(),
+            ));
+        }
+    }
+
+    // `from type` 中未知的类型
+    if let Some(types) = types {
+        for (index, pair) in words.windows(2).enumerate() {
+            if !(pair[0].1.eq_ignore_ascii_case("from") && pair[1].1.eq_ignore_ascii_case("type")) {
+                continue;
+            }
+            let unknown = words[index + 2..]
+                .iter()
+                .map(|(_, word)| *word)
+                .take_while(|word| !is_clause_keyword(word))
+                .filter(|word| !types.iter().any(|t| t.eq_ignore_ascii_case(word)));
+            for word in unknown {
+                warnings.push(LintWarning::new(
+                    LintKind::UnknownType,
+                    format!("项目中没有类型 `{}`", word),
+                ));
+            }
+        }
+    }
+
+    // 没有条件的 `where`
+    for (index, (_, word)) in words.iter().enumerate() {
+        if word.eq_ignore_ascii_case("where")
+            && words.get(index + 1).is_none_or(|(_, next)| is_clause_keyword(next))
+        {
+            warnings.push(LintWarning::new(
+                LintKind::EmptyWhere,
+                "`where` 之后没有条件".to_string(),
+            ));
+        }
+    }
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(query: &str, types: Option<&[String]>) -> Vec<LintKind> {
+        lint(query, types).into_iter().map(|w| w.kind).collect()
+    }
+
+    #[test]
+    fn test_lint_name_equals() {
+        assert_eq!(
+            kinds(r#"$ from type Sound where name = "foot""#, None),
+            vec![LintKind::NameEquals]
+        );
+        assert_eq!(
+            kinds(r#"$ from type Sound where @Volume < 0 and name="foot""#, None),
+            vec![LintKind::NameEquals]
+        );
+        assert!(kinds(r#"$ from type Sound where name : "foot""#, None).is_empty());
+        assert!(kinds(r#"$ from type Sound where name != "foot""#, None).is_empty());
+        assert!(kinds(r#"$ from type Sound where notes = "name = x""#, None).is_empty());
+        assert!(kinds("$ from type Sound select name", None).is_empty());
+    }
+
+    #[test]
+    fn test_lint_unknown_type() {
+        let types = vec!["Sound".to_string(), "Event".to_string()];
+        let warnings = lint("$ from type sound, Evnt select parent", Some(&types));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, LintKind::UnknownType);
+        assert!(warnings[0].message.contains("Evnt"));
+        assert!(kinds("$ from type Evnt", None).is_empty());
+        assert!(kinds(r#"$ from object "\\Events""#, Some(&types)).is_empty());
+    }
+
+    #[test]
+    fn test_lint_empty_where() {
+        assert_eq!(kinds("$ from type Sound where", None), vec![LintKind::EmptyWhere]);
+        assert_eq!(
+            kinds("$ from type Sound where select parent", None),
+            vec![LintKind::EmptyWhere]
+        );
+        assert_eq!(kinds("$ from type Sound where | name", None), vec![LintKind::EmptyWhere]);
+        assert!(kinds("$ from type Sound where @Volume < 0", None).is_empty());
+    }
+}
@@ -2,9 +2,33 @@
 //! 
 //! 包含 WAQL 语法定义、WAAPI 属性和访问器列表
 
+mod completion;
+mod examples;
+mod filter;
+mod fuzzy;
+mod guard;
+mod json;
+mod lint;
+mod palette;
 mod properties;
+mod quote;
+mod select;
+mod statements;
 mod syntax;
+mod tokenize;
 
+pub use completion::{token_at_caret, CaretToken, WordCompleter};
+pub use examples::{EXAMPLE_QUERIES, QUERY_TEMPLATES};
+pub use filter::{append_condition, WhereCondition, WhereOp};
+pub use fuzzy::{fuzzy_match, FuzzyMatch};
+pub use guard::{append_take, broad_query_type};
+pub use json::tokenize_json;
+pub use lint::{lint, LintKind, LintWarning};
+pub use palette::{palette_entries, search_palette, PaletteEntry, PaletteKind};
 pub use properties::WAAPI_ACCESSORS;
 pub use properties::WAAPI_PROPERTIES;
-pub use syntax::waql_syntax;
\ No newline at end of file
+pub use quote::{quote_string, quote_value};
+pub use select::{append_select, in_select_clause, select_fields};
+pub use statements::{split_statements, statement_at};
+pub use syntax::waql_syntax;
+pub use tokenize::{tokenize, tokenize_with, TokenKind};
\ No newline at end of file
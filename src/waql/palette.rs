@@ -0,0 +1,253 @@
+//! 插入面板条目
+//!
+//! 汇总 WAQL 关键字、对象类型、WAAPI 访问器和属性，
+//! 供命令面板检索和插入使用
+
+use std::collections::HashSet;
+
+use super::fuzzy::{fuzzy_match, FuzzyMatch};
+use super::properties::{WAAPI_ACCESSORS, WAAPI_PROPERTIES};
+use super::syntax::waql_syntax;
+
+/// 面板条目类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PaletteKind {
+    /// WAQL 关键字
+    Keyword,
+    /// Wwise 对象类型
+    Type,
+    /// WAAPI 访问器
+    Accessor,
+    /// WAAPI 属性
+    Property,
+}
+
+impl PaletteKind {
+    /// 类别的简短描述
+    pub fn description(&self) -> &'static str {
+        match self {
+            PaletteKind::Keyword => "WAQL keyword",
+            PaletteKind::Type => "Wwise object type",
+            PaletteKind::Accessor => "WAAPI accessor",
+            PaletteKind::Property => "WAAPI property",
+        }
+    }
+}
+
+/// 面板条目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PaletteEntry {
+    /// 插入的文本
+    pub text: &'static str,
+    /// 条目类别
+    pub kind: PaletteKind,
+    /// 条目的简短说明，只有关键字和访问器有说明
+    pub detail: Option<&'static str>,
+}
+
+/// WAQL 关键字的简短说明
+fn keyword_detail(text: &str) -> Option<&'static str> {
+    let detail = match text {
+        "$" => "Start of a WAQL query",
+        "@" => "Property prefix, e.g. @Volume",
+        "from" => "Start from objects, a type, a query, a search or the project",
+        "where" => "Keep objects matching a condition",
+        "skip" => "Skip the first N objects",
+        "take" => "Keep only the first N objects",
+        "select" => "Replace objects with related objects",
+        "orderby" => "Sort objects by an expression",
+        "distinct" => "Remove duplicate objects",
+        "any" => "Whether any element of a list matches",
+        "all" => "Whether every element of a list matches",
+        "first" => "First element of a list",
+        "last" => "Last element of a list",
+        "at" => "Element of a list at an index",
+        "and" => "Both conditions must match",
+        "or" => "Either condition must match",
+        "object" => "Objects by GUID, path or name",
+        "type" => "All objects of a type",
+        "query" => "Objects returned by a Query object",
+        "search" => "Objects whose name contains the text",
+        "project" => "The project root",
+        "children" => "Direct children",
+        "descendants" => "All objects below, recursively",
+        "this" => "The object itself",
+        "parent" => "Parent object",
+        "ancestors" => "All objects above, up to the root",
+        "referencesTo" => "Objects referencing the object",
+        "owner" => "Object owning a custom or local object",
+        "randomizer" => "Randomizer of a property",
+        "state" => "State of a state property",
+        "workunit" => "Work Unit containing the object",
+        "musicTransitionRoot" => "Root of the music transition list",
+        "musicTransitionObject" => "Object of a music transition",
+        "musicPlaylistRoot" => "Root item of a music playlist",
+        "maxDurationSource" => "Longest source below the object",
+        "maxDurationSourceObject" => "Object of the longest source below the object",
+        "maxRadiusAttenuation" => "Attenuation with the largest radius",
+        "maxRadiusAttenuationObject" => "Object of the attenuation with the largest radius",
+        "audioSourceLanguage" => "Language of an audio source",
+        "switchContainerChildContext" => "Switch assignment of a switch container child",
+        "switchGroupGameParameter" => "Game parameter driving a switch group",
+        "panner" => "Panner of the object",
+        "stateGroups" => "State groups used by the object",
+        "customStates" => "Custom states of the object",
+        "originalState" => "State a custom state belongs to",
+        "extractEvents" => "Events referenced by the objects",
+        "extractStructures" => "Structures referenced by the objects",
+        "extractMedia" => "Media referenced by the objects",
+        "soundbanksReferencingEvent" => "SoundBanks including the event",
+        _ => return None,
+    };
+    Some(detail)
+}
+
+/// WAAPI 访问器的简短说明，带前缀的旧写法与对应的新写法共用说明
+fn accessor_detail(text: &str) -> Option<&'static str> {
+    let detail = match text {
+        "id" => "Object GUID",
+        "name" => "Object name",
+        "notes" => "Object notes",
+        "type" => "Object type",
+        "pluginName" => "Plug-in name of a source or effect",
+        "shortId" => "Short ID used in SoundBanks",
+        "classId" => "Numeric class ID of the object type",
+        "category" => "Project Explorer category",
+        "filePath" => "Work Unit file path",
+        "workunit" => "Work Unit containing the object",
+        "parent" => "Parent object",
+        "owner" => "Object owning a custom or local object",
+        "path" => "Project path",
+        "isPlayable" => "Whether the object can be played",
+        "childrenCount" => "Number of direct children",
+        "totalSize" => "Total size of the object and its media",
+        "mediaSize" => "Size of the media",
+        "objectSize" => "Size of the object data",
+        "structureSize" => "Size of the structure data",
+        "sound:convertedWemFilePath" | "convertedWemFilePath" | "convertedFilePath" => {
+            "Converted WEM file path"
+        }
+        "sound:originalWavFilePath" | "originalWavFilePath" | "originalFilePath" => {
+            "Original source file path"
+        }
+        "originalRelativeFilePath" => "Original source file path relative to Originals",
+        "soundbank:bnkFilePath" | "soundbankBnkFilePath" => "Generated SoundBank file path",
+        "music:transitionRoot" | "musicTransitionRoot" => "Root of the music transition list",
+        "music:playlistRoot" | "musicPlaylistRoot" => "Root item of a music playlist",
+        "audioSource:playbackDuration" | "playbackDuration" => "Playback duration",
+        "duration" => "Duration of the media",
+        "audioSource:maxDurationSource" | "maxDurationSource" => "Longest source below the object",
+        "audioSource:trimValues" | "audioSourceTrimValues" => "Trim begin and end of a source",
+        "audioSource:maxRadiusAttenuation" | "maxRadiusAttenuation" => {
+            "Attenuation with the largest radius"
+        }
+        "audioSource:language" | "audioSourceLanguage" => "Language of an audio source",
+        "workunit:isDefault" | "workunitIsDefault" => "Whether the Work Unit is the default one",
+        "workunit:type" | "workunitType" => "Work Unit type",
+        "workunit:isDirty" | "workunitIsDirty" => "Whether the Work Unit has unsaved changes",
+        "switchContainerChild:context" | "switchContainerChildContext" => {
+            "Switch assignment of a switch container child"
+        }
+        "isExplicitMute" => "Whether the object is muted",
+        "isExplicitSolo" => "Whether the object is soloed",
+        "isImplicitMute" => "Whether the object is muted by a parent or another solo",
+        "isImplicitSolo" => "Whether the object is soloed through a parent or child",
+        "isIncluded" => "Whether the object is included in the current platform",
+        "points" => "Points of a curve",
+        "stateProperties" => "Properties that can vary by state",
+        "stateGroups" => "State groups used by the object",
+        "blendTracks" => "Blend tracks of a blend container",
+        _ => return None,
+    };
+    Some(detail)
+}
+
+/// 获取所有面板条目
+///
+/// 同名条目只保留第一次出现的类别（关键字优先，其次类型、访问器、属性）。
+/// 对象类型和属性数量多且名称已能说明含义，不附带说明
+pub fn palette_entries() -> Vec<PaletteEntry> {
+    let syntax = waql_syntax();
+    let keywords = syntax.special.iter().chain(syntax.keywords.iter());
+
+    let groups = keywords
+        .map(|text| (*text, PaletteKind::Keyword))
+        .chain(syntax.types.iter().map(|text| (*text, PaletteKind::Type)))
+        .chain(WAAPI_ACCESSORS.iter().map(|text| (*text, PaletteKind::Accessor)))
+        .chain(WAAPI_PROPERTIES.iter().map(|text| (*text, PaletteKind::Property)));
+
+    let mut seen = HashSet::new();
+    groups
+        .filter(|(text, _)| seen.insert(*text))
+        .map(|(text, kind)| PaletteEntry {
+            text,
+            kind,
+            detail: match kind {
+                PaletteKind::Keyword => keyword_detail(text),
+                PaletteKind::Accessor => accessor_detail(text),
+                PaletteKind::Type | PaletteKind::Property => None,
+            },
+        })
+        .collect()
+}
+
+/// 按模糊匹配得分检索面板条目
+///
+/// 返回匹配条目在 `entries` 中的索引及匹配结果，按得分从高到低排序
+pub fn search_palette(query: &str, entries: &[PaletteEntry]) -> Vec<(usize, FuzzyMatch)> {
+    let mut matches: Vec<(usize, FuzzyMatch)> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(index, entry)| fuzzy_match(query, entry.text).map(|m| (index, m)))
+        .collect();
+    // 稳定排序，同分时保持原有类别顺序
+    matches.sort_by_key(|m| std::cmp::Reverse(m.1.score));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry<'a>(entries: &'a [PaletteEntry], text: &str) -> &'a PaletteEntry {
+        entries.iter().find(|e| e.text == text).unwrap()
+    }
+
+    #[test]
+    fn test_palette_entries() {
+        let entries = palette_entries();
+        let mut texts = HashSet::new();
+        assert!(entries.iter().all(|e| texts.insert(e.text)));
+
+        // 同名条目保留关键字
+        assert_eq!(entry(&entries, "type").kind, PaletteKind::Keyword);
+        assert_eq!(entry(&entries, "where").detail, Some("Keep objects matching a condition"));
+        assert_eq!(entry(&entries, "Sound").kind, PaletteKind::Type);
+        assert_eq!(entry(&entries, "Sound").detail, None);
+        assert_eq!(entry(&entries, "id").kind, PaletteKind::Accessor);
+        assert_eq!(entry(&entries, "id").detail, Some("Object GUID"));
+        assert_eq!(entry(&entries, "Volume").kind, PaletteKind::Property);
+        assert_eq!(entry(&entries, "Volume").detail, None);
+
+        // 所有关键字和访问器都有说明
+        assert!(entries
+            .iter()
+            .filter(|e| matches!(e.kind, PaletteKind::Keyword | PaletteKind::Accessor))
+            .all(|e| e.detail.is_some()));
+    }
+
+    #[test]
+    fn test_search_palette() {
+        let entries = palette_entries();
+        let matches = search_palette("whr", &entries);
+        assert_eq!(entries[matches[0].0].text, "where");
+        assert!(matches.windows(2).all(|w| w[0].1.score >= w[1].1.score));
+
+        // 前缀匹配排在前面
+        let matches = search_palette("vol", &entries);
+        assert_eq!(entries[matches[0].0].text, "Volume");
+
+        assert_eq!(search_palette("", &entries).len(), entries.len());
+        assert!(search_palette("zzzz", &entries).is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! 值的引号处理
+//!
+//! 将用户输入的值写入查询时统一加引号和转义
+
+/// 将值转换为 WAQL 字面量
+///
+/// 数字、`true`/`false` 和 `null` 原样写入，其他值见 [`quote_string`]
+pub fn quote_value(value: &str) -> String {
+    let value = value.trim();
+    if is_number(value) || matches!(value, "true" | "false" | "null") {
+        value.to_string()
+    } else {
+        quote_string(value)
+    }
+}
+
+/// 是否为数字字面量：可选的正负号、整数部分、可选的小数部分和指数部分
+///
+/// 不接受 `inf`、`NaN` 等 [`f64`] 能解析但 WAQL 中不是数字的文本
+fn is_number(value: &str) -> bool {
+    fn digits(text: &str) -> usize {
+        text.bytes().take_while(u8::is_ascii_digit).count()
+    }
+    let rest = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let int = digits(rest);
+    if int == 0 {
+        return false;
+    }
+    let mut rest = &rest[int..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = digits(fraction);
+        if len == 0 {
+            return false;
+        }
+        rest = &fraction[len..];
+    }
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['-', '+']).unwrap_or(exponent);
+        let len = digits(exponent);
+        if len == 0 {
+            return false;
+        }
+        rest = &exponent[len..];
+    }
+    rest.is_empty()
+}
+
+/// 将值作为 WAQL 字符串写入：加双引号，并转义其中的反斜杠和双引号
+pub fn quote_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_value() {
+        assert_eq!(quote_value("my sound"), r#""my sound""#);
+        assert_eq!(quote_value(" -6.5 "), "-6.5");
+        assert_eq!(quote_value("true"), "true");
+        assert_eq!(quote_value("null"), "null");
+        assert_eq!(quote_value("True"), r#""True""#);
+        assert_eq!(quote_value("1e-3"), "1e-3");
+        assert_eq!(quote_value("+2"), "+2");
+    }
+
+    #[test]
+    fn test_quote_value_non_numbers() {
+        for value in ["inf", "-inf", "NaN", "infinity", "1.", ".5", "1e", "0x10", "1_000"] {
+            assert_eq!(quote_value(value), quote_string(value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_quote_string_escapes() {
+        assert_eq!(quote_string(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(
+            quote_string(r"\Actor-Mixer Hierarchy\Default Work Unit"),
+            r#""\\Actor-Mixer Hierarchy\\Default Work Unit""#
+        );
+        assert_eq!(quote_string(r#"a\"b"#), r#""a\\\"b""#);
+        assert_eq!(quote_string("12"), r#""12""#);
+    }
+}
@@ -0,0 +1,149 @@
+//! `select` 子句识别
+//!
+//! 以启发式方式识别查询中的 `select` 子句，不做完整的语法分析
+
+use crate::query_executor::split_query_options;
+
+/// 开始一个新子句的关键字
+const CLAUSE_KEYWORDS: &[&str] = &[
+    "from", "where", "select", "skip", "take", "orderby", "distinct",
+];
+
+/// 按单词分割，返回每个单词的字节起始位置
+///
+/// 字符串字面量中的内容不视为单词
+pub(super) fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut in_string = false;
+
+    for (i, ch) in text.char_indices() {
+        if ch == '"' {
+            in_string = !in_string;
+        }
+        let is_word_char =
+            !in_string && (ch.is_alphanumeric() || matches!(ch, '_' | '@' | ':' | '.'));
+        match (is_word_char, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s, &text[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &text[s..]));
+    }
+    words
+}
+
+/// 判断单词是否为子句关键字
+pub(super) fn is_clause_keyword(word: &str) -> bool {
+    CLAUSE_KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(word))
+}
+
+/// 判断光标是否位于 `select` 子句中
+///
+/// `caret` 为字符索引。光标之前最近的子句关键字为 `select` 时返回 `true`
+pub fn in_select_clause(text: &str, caret: usize) -> bool {
+    let caret_byte = text
+        .char_indices()
+        .nth(caret)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+
+    words(&text[..caret_byte])
+        .into_iter()
+        .rev()
+        .find(|(_, word)| is_clause_keyword(word))
+        .is_some_and(|(_, word)| word.eq_ignore_ascii_case("select"))
+}
+
+/// 提取查询中最后一个 `select` 子句选择的字段，按出现顺序排列
+///
+/// 字段以空白或逗号分隔，遇到下一个子句关键字或 `|` 选项部分时结束
+pub fn select_fields(query: &str) -> Vec<String> {
+    let query = split_query_options(query).map_or(query, |(query, _)| query);
+    let words = words(query);
+
+    let Some(select_index) = words
+        .iter()
+        .rposition(|(_, word)| word.eq_ignore_ascii_case("select"))
+    else {
+        return Vec::new();
+    };
+
+    words[select_index + 1..]
+        .iter()
+        .map(|(_, word)| *word)
+        .take_while(|word| !is_clause_keyword(word))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 查询没有投影时在末尾追加 `select` 子句，返回新的查询
+///
+/// 查询已经有 `select` 子句或 `|` 选项部分（可能包含返回字段）时返回 `None`，避免重复追加
+pub fn append_select(query: &str, fields: &[String]) -> Option<String> {
+    let fields: Vec<&str> = fields
+        .iter()
+        .map(|field| field.trim())
+        .filter(|field| !field.is_empty())
+        .collect();
+    if fields.is_empty() || split_query_options(query).is_some() {
+        return None;
+    }
+    if words(query).iter().any(|(_, word)| word.eq_ignore_ascii_case("select")) {
+        return None;
+    }
+    Some(format!("{} select {}", query.trim_end(), fields.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_select_clause() {
+        let text = "$ from type Sound select par";
+        assert!(in_select_clause(text, text.chars().count()));
+        assert!(!in_select_clause(text, 12));
+        assert!(!in_select_clause("$ from type Sound select parent where na", 40));
+        assert!(!in_select_clause("$ from type Sound where name : \"select\" and", 43));
+    }
+
+    #[test]
+    fn test_append_select() {
+        let fields = vec!["name".to_string(), "id".to_string(), "type".to_string()];
+        assert_eq!(
+            append_select("$ from type Sound where name : \"foot\" ", &fields).as_deref(),
+            Some("$ from type Sound where name : \"foot\" select name, id, type")
+        );
+        assert_eq!(append_select("$ from type Sound select name", &fields), None);
+        assert_eq!(append_select("$ from type Event select children", &fields), None);
+        assert_eq!(append_select("$ from type Sound | name", &fields), None);
+        assert_eq!(append_select("$ from type Sound", &[]), None);
+        assert_eq!(
+            append_select(r#"$ from type Sound where name : "select""#, &fields).as_deref(),
+            Some(r#"$ from type Sound where name : "select" select name, id, type"#)
+        );
+    }
+
+    #[test]
+    fn test_select_fields() {
+        assert_eq!(
+            select_fields("$ from type Sound select name, @Volume parent where name : \"x\""),
+            vec!["name", "@Volume", "parent"]
+        );
+        assert_eq!(
+            select_fields("$ from type Event select children | id"),
+            vec!["children"]
+        );
+        assert!(select_fields("$ from type Sound").is_empty());
+        assert_eq!(
+            select_fields(r#"$ from type Sound where name : "a|b" select name"#),
+            vec!["name"]
+        );
+    }
+}
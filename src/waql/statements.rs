@@ -0,0 +1,98 @@
+//! 语句分割
+//!
+//! 多行编辑器中以空行分隔多条 WAQL 语句
+
+use std::ops::Range;
+
+/// 按空行分割语句
+///
+/// # Returns
+///
+/// 返回每条语句的字节范围，不包含首尾空白
+pub fn split_statements(text: &str) -> Vec<Range<usize>> {
+    let mut statements = Vec::new();
+    let mut current: Option<Range<usize>> = None;
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+
+        if line.trim().is_empty() {
+            if let Some(range) = current.take() {
+                statements.push(range);
+            }
+            continue;
+        }
+
+        let start = line_start + (line.len() - line.trim_start().len());
+        let end = line_start + line.trim_end().len();
+        current = Some(match current {
+            Some(range) => range.start..end,
+            None => start..end,
+        });
+    }
+
+    if let Some(range) = current {
+        statements.push(range);
+    }
+    statements
+}
+
+/// 获取光标所在语句的字节范围
+///
+/// `caret` 为字符索引。光标位于语句末尾时也视为在语句内；
+/// 光标位于空行时返回 `None`
+pub fn statement_at(text: &str, caret: usize) -> Option<Range<usize>> {
+    let caret_byte = text
+        .char_indices()
+        .nth(caret)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len());
+
+    split_statements(text)
+        .into_iter()
+        .find(|range| range.start <= caret_byte && caret_byte <= range.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "$ from type Sound\n  where name : \"a\"\n\n\n$ from type Event  \n";
+
+    fn statements(text: &str) -> Vec<&str> {
+        split_statements(text)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect()
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            statements(TEXT),
+            vec!["$ from type Sound\n  where name : \"a\"", "$ from type Event"]
+        );
+        assert!(statements("  \n\n").is_empty());
+        assert_eq!(statements("$ from type Bus"), vec!["$ from type Bus"]);
+    }
+
+    #[test]
+    fn test_statement_at_caret() {
+        // 第一条语句第二行
+        let range = statement_at(TEXT, 20).unwrap();
+        assert_eq!(&TEXT[range], "$ from type Sound\n  where name : \"a\"");
+
+        // 第二条语句末尾
+        let caret = TEXT.find("Event").unwrap() + 5;
+        let range = statement_at(TEXT, caret).unwrap();
+        assert_eq!(&TEXT[range], "$ from type Event");
+    }
+
+    #[test]
+    fn test_statement_at_blank_line() {
+        let caret = TEXT.find("\n\n").unwrap() + 1;
+        assert!(statement_at(TEXT, caret).is_none());
+    }
+}
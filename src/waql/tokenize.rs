@@ -0,0 +1,121 @@
+//! WAQL 分词
+//!
+//! 封装 egui_code_editor 的分词器，返回不依赖 UI 类型的纯数据，
+//! 供语法高亮、校验和测试共用
+
+use std::ops::Range;
+
+use egui_code_editor::{Syntax, Token, TokenType};
+
+use super::syntax::waql_syntax;
+
+/// 词法单元类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    /// 关键字（如 `children`、`parent`）
+    Keyword,
+    /// Wwise 对象类型（如 `Sound`）
+    Type,
+    /// 特殊符号和子句关键字（如 `$`、`from`、`where`）
+    Special,
+    /// 普通标识符
+    Literal,
+    /// 数字
+    Numeric,
+    /// 字符串
+    Str,
+    /// 标点符号
+    Punctuation,
+    /// 空白
+    Whitespace,
+    /// 注释
+    Comment,
+    /// 函数
+    Function,
+    /// 无法识别的内容
+    Unknown,
+}
+
+impl From<TokenType> for TokenKind {
+    fn from(ty: TokenType) -> Self {
+        match ty {
+            TokenType::Keyword => TokenKind::Keyword,
+            TokenType::Type => TokenKind::Type,
+            TokenType::Special => TokenKind::Special,
+            TokenType::Literal => TokenKind::Literal,
+            TokenType::Numeric(_) => TokenKind::Numeric,
+            TokenType::Str(_) => TokenKind::Str,
+            TokenType::Punctuation(_) => TokenKind::Punctuation,
+            TokenType::Whitespace(_) => TokenKind::Whitespace,
+            TokenType::Comment(_) => TokenKind::Comment,
+            TokenType::Function => TokenKind::Function,
+            _ => TokenKind::Unknown,
+        }
+    }
+}
+
+/// 使用默认 WAQL 语法对查询语句分词
+///
+/// # Returns
+///
+/// 返回按顺序排列的词法单元类别和字节范围，所有范围首尾相连覆盖整个输入
+pub fn tokenize(query: &str) -> Vec<(TokenKind, Range<usize>)> {
+    tokenize_with(&waql_syntax(), query)
+}
+
+/// 使用指定语法对查询语句分词
+pub fn tokenize_with(syntax: &Syntax, query: &str) -> Vec<(TokenKind, Range<usize>)> {
+    let mut offset = 0;
+    Token::default()
+        .tokens(syntax, query)
+        .into_iter()
+        .map(|token| {
+            let start = offset;
+            offset += token.buffer().len();
+            (TokenKind::from(token.ty()), start..offset)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds_of<'a>(
+        query: &'a str,
+        tokens: &[(TokenKind, Range<usize>)],
+    ) -> Vec<(TokenKind, &'a str)> {
+        tokens
+            .iter()
+            .filter(|(kind, _)| *kind != TokenKind::Whitespace)
+            .map(|(kind, range)| (*kind, &query[range.clone()]))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_covers_input() {
+        let query = "$ from type Sound where name : \"脚步\"";
+        let tokens = tokenize(query);
+        let mut expected_start = 0;
+        for (_, range) in &tokens {
+            assert_eq!(range.start, expected_start);
+            expected_start = range.end;
+        }
+        assert_eq!(expected_start, query.len());
+    }
+
+    #[test]
+    fn test_tokenize_kinds() {
+        let query = "$ from type Sound select children";
+        let tokens = tokenize(query);
+        let kinds = kinds_of(query, &tokens);
+        assert!(kinds.contains(&(TokenKind::Special, "from")));
+        assert!(kinds.contains(&(TokenKind::Type, "Sound")));
+        assert!(kinds.contains(&(TokenKind::Keyword, "children")));
+    }
+
+    #[test]
+    fn test_tokenize_empty() {
+        assert!(tokenize("").is_empty());
+    }
+}